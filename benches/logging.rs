@@ -0,0 +1,73 @@
+use call_logger::CallLogger;
+use criterion::{criterion_group, criterion_main, Criterion};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+fn record_benchmarks(c: &mut Criterion) {
+    let logger = CallLogger::new()
+        .with_level(LevelFilter::Info)
+        .with_level_for("call_logger::bench::verbose", LevelFilter::Debug)
+        .with_noop_target()
+        .build()
+        .unwrap();
+    let filtered = Metadata::builder()
+        .level(Level::Debug)
+        .target("call_logger::bench")
+        .build();
+    let enabled = Metadata::builder()
+        .level(Level::Info)
+        .target("call_logger::bench")
+        .build();
+    c.bench_function("enabled filtered", |b| b.iter(|| logger.enabled(&filtered)));
+    c.bench_function("enabled", |b| b.iter(|| logger.enabled(&enabled)));
+    c.bench_function("format json", |b| {
+        b.iter(|| {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("benchmark message {}", 42))
+                    .module_path(Some("call_logger::bench"))
+                    .file(Some("benches/logging.rs"))
+                    .line(Some(1))
+                    .level(Level::Info)
+                    .build(),
+            )
+        })
+    });
+}
+
+fn dispatch_benchmarks(c: &mut Criterion) {
+    // the output is written to a file so that each call waits for the process to finish
+    let logger = CallLogger::new()
+        .with_call_target("true")
+        .to_file("target/bench_process.log")
+        .build()
+        .unwrap();
+    c.bench_function("dispatch process", |b| {
+        b.iter(|| {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("benchmark message"))
+                    .level(Level::Info)
+                    .build(),
+            )
+        })
+    });
+    let mut server = mockito::Server::new();
+    let _mock = server.mock("POST", "/").with_status(200).create();
+    let logger = CallLogger::new()
+        .with_call_target(server.url())
+        .build()
+        .unwrap();
+    c.bench_function("dispatch http", |b| {
+        b.iter(|| {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("benchmark message"))
+                    .level(Level::Info)
+                    .build(),
+            )
+        })
+    });
+}
+
+criterion_group!(benches, record_benchmarks, dispatch_benchmarks);
+criterion_main!(benches);
@@ -11,8 +11,14 @@ use multi_log::MultiLogger;
 /// cargo run --example log_filtering
 /// ```
 fn main() {
-    let l1 = CallLogger::new().with_level(LevelFilter::Info);
-    let l2 = CallLogger::new().with_level(LevelFilter::Error);
+    let l1 = CallLogger::new()
+        .with_level(LevelFilter::Info)
+        .build()
+        .expect("valid logger configuration");
+    let l2 = CallLogger::new()
+        .with_level(LevelFilter::Error)
+        .build()
+        .expect("valid logger configuration");
     let _ = MultiLogger::init(vec![Box::new(l1), Box::new(l2)], Level::Trace);
 
     // Only one log message at INFO level will be printed from l1 because l2 is filtered for error
@@ -19,7 +19,7 @@ fn main() {
             .format(|timestamp, message, record| {
                 format!(
                     "{{ \"content\": \"{} [{}] {} - {}\" }}",
-                    timestamp,
+                    timestamp.unwrap_or_default(),
                     record.level(),
                     record.module_path().unwrap_or_default(),
                     message
@@ -0,0 +1 @@
+Set-Content -Path $args[0] -Value $args[1]
@@ -0,0 +1,38 @@
+use std::path::Path;
+
+use crate::CallLoggerBuilder;
+
+impl CallLoggerBuilder {
+    /// Sets up the logger for compliance logs where losing a log event is unacceptable.  Each log event is delivered
+    /// while it is logged, and one that can't be delivered is written to the `spool` file, which is synced to the disk
+    /// before logging carries on, and can be re-sent with [`CallLogger::replay_dead_letters`].  Failures are passed to
+    /// the closure set with [`CallLoggerBuilder::on_call_error`] if there is one.  The default formatter adds the
+    /// sequence number of the log event as `seq`, so that gaps can be detected, and a formatter set with
+    /// [`CallLoggerBuilder::format_with`] can read it from [`FormatContext::sequence`](crate::FormatContext::sequence).
+    /// An application that is called is waited for, and an exit status other than zero is a failure.
+    ///
+    /// Audit mode can't be used with [`CallLoggerBuilder::with_workers`], [`CallLoggerBuilder::with_latency_budget`]
+    /// or [`CallLoggerBuilder::with_batching`] on any of the targets, which would return from logging before the log
+    /// event is delivered.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .audit_mode("audit_spool.ndjson")
+    ///     .on_call_error(|error| eprintln!("audit log event spooled, {error}"))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn audit_mode<P>(mut self, spool: P) -> CallLoggerBuilder
+    where
+        P: AsRef<Path>,
+    {
+        self.audit = true;
+        self.dispatcher.dead_letter_sync = true;
+        self.field_layout.sequence = true;
+        self.with_dead_letter_file(spool)
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    collections::hash_map::RandomState,
+    fs::{create_dir_all, rename, File},
+    hash::{BuildHasher, Hasher},
+    io::Write,
+    mem::take,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use log::{
+    kv::{Error, Key, Value, VisitSource},
+    Record,
+};
+
+use crate::{internal::report, BoxError, CallLoggerBuilder, CallTarget};
+
+/// The schema of the log events written to Avro files
+const SCHEMA: &str = r#"{"type":"record","name":"LogEvent","namespace":"call_logger","fields":[{"name":"ts","type":{"type":"long","logicalType":"timestamp-millis"}},{"name":"level","type":"string"},{"name":"target","type":"string"},{"name":"module_path","type":["null","string"]},{"name":"file","type":["null","string"]},{"name":"line","type":["null","long"]},{"name":"msg","type":"string"},{"name":"kv","type":{"type":"map","values":"string"}}]}"#;
+
+impl CallLoggerBuilder {
+    /// Writes log events to [Apache Avro][avro] object container files in the directory `dir` instead of calling the
+    /// call target, so that they can be bulk loaded into a data lake.  Log events are accumulated and a new file is
+    /// written when `records_per_file` events are waiting, once the oldest waiting event has waited for `max_delay`,
+    /// or when the logger is flushed or dropped.  Files are named `log-<epoch ms>-<n>.avro` and contain the
+    /// timestamp, level, target, module path, file, line, message and key-value pairs of each event.  Each file is
+    /// written with a `.tmp` extension and renamed once it is complete, so that a loader watching the directory never
+    /// reads a partial file.  The formatter is not used by this target.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// # use std::time::Duration;
+    /// CallLogger::new()
+    ///     .avro_sink("logs", 10_000, Duration::from_secs(300))
+    ///     .init();
+    /// ```
+    ///
+    /// [avro]: https://avro.apache.org/docs/1.11.1/specification/
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn avro_sink<P>(
+        mut self,
+        dir: P,
+        records_per_file: usize,
+        max_delay: Duration,
+    ) -> CallLoggerBuilder
+    where
+        P: AsRef<Path>,
+    {
+        self.dispatcher.target = Some(Box::new(AvroSink {
+            dir: dir.as_ref().to_path_buf(),
+            records_per_file: records_per_file.max(1),
+            max_delay,
+            pending: Mutex::new(Pending::default()),
+        }));
+        self
+    }
+}
+
+struct AvroSink {
+    dir: PathBuf,
+    records_per_file: usize,
+    max_delay: Duration,
+    pending: Mutex<Pending>,
+}
+
+#[derive(Default)]
+struct Pending {
+    data: Vec<u8>,
+    count: i64,
+    started: Option<Instant>,
+    files: u64,
+}
+
+impl AvroSink {
+    fn write_file(&self, data: &[u8], count: i64, n: u64) -> Result<(), BoxError> {
+        create_dir_all(&self.dir)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let path = self.dir.join(format!("log-{now}-{n}.avro"));
+        let mut partial = path.clone().into_os_string();
+        partial.push(".tmp");
+        let mut file = File::create(&partial)?;
+        let state = RandomState::new();
+        let mut sync = [0u8; 16];
+        for (i, chunk) in sync.chunks_mut(8).enumerate() {
+            let mut hasher = state.build_hasher();
+            hasher.write_u64(now as u64 ^ i as u64);
+            chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+        }
+        let mut header = b"Obj\x01".to_vec();
+        write_long(&mut header, 2);
+        write_bytes(&mut header, b"avro.schema");
+        write_bytes(&mut header, SCHEMA.as_bytes());
+        write_bytes(&mut header, b"avro.codec");
+        write_bytes(&mut header, b"null");
+        write_long(&mut header, 0);
+        header.extend_from_slice(&sync);
+        write_long(&mut header, count);
+        write_long(&mut header, data.len() as i64);
+        file.write_all(&header)?;
+        file.write_all(data)?;
+        file.write_all(&sync)?;
+        file.sync_all()?;
+        rename(&partial, &path)?;
+        Ok(())
+    }
+
+    fn write_pending(&self, pending: &mut Pending) -> Result<(), BoxError> {
+        if pending.count == 0 {
+            return Ok(());
+        }
+        let data = take(&mut pending.data);
+        let count = take(&mut pending.count);
+        pending.started = None;
+        pending.files += 1;
+        self.write_file(&data, count, pending.files)
+    }
+}
+
+impl CallTarget for AvroSink {
+    fn call(&self, _payload: &str, record: &Record) -> Result<(), BoxError> {
+        let mut datum = Vec::new();
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        write_long(&mut datum, ts as i64);
+        write_string(&mut datum, record.level().as_str());
+        write_string(&mut datum, record.target());
+        write_optional_string(&mut datum, record.module_path());
+        write_optional_string(&mut datum, record.file());
+        match record.line() {
+            Some(line) => {
+                write_long(&mut datum, 1);
+                write_long(&mut datum, line.into());
+            }
+            None => write_long(&mut datum, 0),
+        }
+        write_string(&mut datum, &record.args().to_string());
+        let mut visitor = MapVisitor { pairs: Vec::new() };
+        let _ = record.key_values().visit(&mut visitor);
+        if !visitor.pairs.is_empty() {
+            write_long(&mut datum, visitor.pairs.len() as i64);
+            for (key, value) in visitor.pairs {
+                write_string(&mut datum, &key);
+                write_string(&mut datum, &value);
+            }
+        }
+        write_long(&mut datum, 0);
+
+        let mut pending = self.pending.lock().unwrap_or_else(|x| x.into_inner());
+        if pending.started.is_none() {
+            pending.started = Some(Instant::now());
+        }
+        pending.data.extend_from_slice(&datum);
+        pending.count += 1;
+        let late = pending
+            .started
+            .is_some_and(|started| started.elapsed() >= self.max_delay);
+        if pending.count as usize >= self.records_per_file || late {
+            self.write_pending(&mut pending)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&self) {
+        let mut pending = self.pending.lock().unwrap_or_else(|x| x.into_inner());
+        if let Err(x) = self.write_pending(&mut pending) {
+            report(format_args!(
+                "logging call to {} failed {x}",
+                self.dir.display()
+            ));
+        }
+    }
+
+    fn flush_due(&self) -> Option<Duration> {
+        let mut pending = self.pending.lock().unwrap_or_else(|x| x.into_inner());
+        let waited = pending.started.map(|started| started.elapsed());
+        match waited {
+            Some(waited) if waited < self.max_delay => return Some(self.max_delay - waited),
+            Some(_) => {
+                if let Err(x) = self.write_pending(&mut pending) {
+                    report(format_args!(
+                        "logging call to {} failed {x}",
+                        self.dir.display()
+                    ));
+                }
+            }
+            None => {}
+        }
+        Some(self.max_delay)
+    }
+}
+
+/// Writes a long using the zig-zag variable length encoding
+fn write_long(buffer: &mut Vec<u8>, value: i64) {
+    let mut value = ((value << 1) ^ (value >> 63)) as u64;
+    while value > 0x7f {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn write_bytes(buffer: &mut Vec<u8>, value: &[u8]) {
+    write_long(buffer, value.len() as i64);
+    buffer.extend_from_slice(value);
+}
+
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    write_bytes(buffer, value.as_bytes());
+}
+
+/// Writes a `["null","string"]` union
+fn write_optional_string(buffer: &mut Vec<u8>, value: Option<&str>) {
+    match value {
+        Some(value) => {
+            write_long(buffer, 1);
+            write_string(buffer, value);
+        }
+        None => write_long(buffer, 0),
+    }
+}
+
+// Visitor for collecting the kv pairs in a log record in order.
+struct MapVisitor {
+    pairs: Vec<(String, String)>,
+}
+
+impl<'kvs> VisitSource<'kvs> for MapVisitor {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        self.pairs.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    base64, batch::BatchFormat, internal::report, vector::vector_formatter, CallLoggerBuilder,
+};
+
+/// The name of the custom log table, which Azure Monitor suffixes with `_CL`
+const LOG_TYPE: &str = "CallLogger";
+
+/// The most log events that are sent in each request unless [`CallLoggerBuilder::with_batching`] is called
+const MAX_RECORDS: usize = 500;
+
+/// The longest that a log event waits to be sent unless [`CallLoggerBuilder::with_batching`] is called
+const MAX_DELAY: Duration = Duration::from_secs(10);
+
+impl CallLoggerBuilder {
+    /// Sends log events to the `CallLogger_CL` custom log table of an Azure Monitor Log Analytics workspace with the
+    /// [HTTP Data Collector API][collector], signing each request with the `shared_key` of the workspace.  Each log
+    /// event is a record with the `timestamp`, `message`, `level`, `host` and `target` of the log event and its
+    /// key-value pairs, and the `timestamp` is used as the `TimeGenerated` of the record.  The formatter is not used.
+    ///
+    /// Log events are sent in batches of up to 500, or every 10 seconds, which can be changed with
+    /// [`CallLoggerBuilder::with_batching`].  Call `log::logger().flush()` before the application exits to send any
+    /// partial batch.  If the shared key is not valid base64, the error is printed and requests will be rejected.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .azure_log_analytics("00000000-0000-0000-0000-000000000000", "c2hhcmVkLWtleQ==")
+    ///     .init();
+    /// ```
+    ///
+    /// [collector]: https://learn.microsoft.com/en-us/azure/azure-monitor/logs/data-collector-api
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn azure_log_analytics<W, K>(mut self, workspace_id: W, shared_key: K) -> CallLoggerBuilder
+    where
+        W: Into<String>,
+        K: AsRef<str>,
+    {
+        let workspace_id = workspace_id.into();
+        let key = decode_base64(shared_key.as_ref()).unwrap_or_else(|| {
+            report(format_args!(
+                "the shared key of the Log Analytics workspace is not base64"
+            ));
+            Vec::new()
+        });
+        self.dispatcher
+            .http_headers
+            .push(("Log-Type".to_string(), LOG_TYPE.to_string()));
+        if cfg!(feature = "timestamps") {
+            self.dispatcher
+                .http_headers
+                .push(("time-generated-field".to_string(), "timestamp".to_string()));
+        }
+        let workspace = workspace_id.clone();
+        self.dispatcher.signer = Some(Box::new(move |body| {
+            let date = http_date(SystemTime::now());
+            let string_to_sign = format!(
+                "POST\n{}\napplication/json\nx-ms-date:{date}\n/api/logs",
+                body.len()
+            );
+            let signature = base64(&hmac_sha256(&key, string_to_sign.as_bytes()));
+            vec![
+                ("x-ms-date".to_string(), date),
+                (
+                    "Authorization".to_string(),
+                    format!("SharedKey {workspace}:{signature}"),
+                ),
+            ]
+        }));
+        self.formatter = Box::new(vector_formatter);
+        self.dispatcher.batch_format = BatchFormat::JsonArray;
+        self.with_call_target(format!(
+            "https://{workspace_id}.ods.opinsights.azure.com/api/logs?api-version=2016-04-01"
+        ))
+        .with_batching(MAX_RECORDS, MAX_DELAY)
+    }
+}
+
+/// The date in the format of an HTTP header, e.g. `Thu, 01 Jan 1970 00:00:00 GMT`
+pub(crate) fn http_date(time: SystemTime) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let days = seconds / 86400;
+    // the civil date of the number of days since the epoch, counting years from March so that leap days are last
+    let shifted = days + 719468;
+    let era = shifted / 146097;
+    let day_of_era = shifted % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = (month_index + 2) % 12;
+    let year = year_of_era + era * 400 + u64::from(month < 2);
+    format!(
+        "{}, {day:02} {} {year} {:02}:{:02}:{:02} GMT",
+        DAYS[(days % 7) as usize],
+        MONTHS[month as usize],
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Decodes standard base64 with padding, or `None` if it is not valid
+pub(crate) fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(encoded.len() / 4 * 3);
+    let (mut bits, mut count) = (0u32, 0);
+    for byte in encoded.trim_end_matches('=').bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        bits = bits << 6 | u32::from(value);
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            decoded.push((bits >> count) as u8);
+        }
+    }
+    Some(decoded)
+}
+
+/// The HMAC-SHA256 of a message
+pub(crate) fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; 64];
+    if key.len() > 64 {
+        block[..32].copy_from_slice(&sha256(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner = block
+        .iter()
+        .map(|x| x ^ 0x36)
+        .chain(message.iter().copied());
+    let inner_hash = sha256(&inner.collect::<Vec<u8>>());
+    let outer = block.iter().map(|x| x ^ 0x5c).chain(inner_hash);
+    sha256(&outer.collect::<Vec<u8>>())
+}
+
+/// The SHA-256 hash of a message
+fn sha256(message: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    // the message is padded with a one bit, zeros and its length in bits to a multiple of 64 bytes
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+    for chunk in padded.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (total, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *total = total.wrapping_add(value);
+        }
+    }
+    let mut hash = [0u8; 32];
+    for (bytes, word) in hash.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    hash
+}
@@ -0,0 +1,210 @@
+use std::{
+    mem::take,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::Level;
+
+use crate::{CallLoggerBuilder, Dispatcher, Event, Target};
+
+/// How the log events in a batch are written in the body of the request, see [`CallLogger::with_batch_format`].
+///
+/// [`CallLogger::with_batch_format`]: crate::CallLoggerBuilder::with_batch_format
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum BatchFormat {
+    /// The log events are separated by new lines and sent as `application/json`
+    #[default]
+    Lines,
+    /// The log events are the elements of a JSON array, sent as `application/json`
+    JsonArray,
+    /// Newline-delimited JSON, where every log event is followed by a new line, sent as `application/x-ndjson` as
+    /// expected by e.g. the Elasticsearch `_bulk` API and the HTTP source of Vector
+    Ndjson,
+}
+
+impl BatchFormat {
+    /// The body of the request for the formatted output of the log events in a batch
+    fn body(self, payloads: &[String]) -> String {
+        match self {
+            BatchFormat::Lines => payloads.join("\n"),
+            BatchFormat::JsonArray => format!("[{}]", payloads.join(",")),
+            BatchFormat::Ndjson => {
+                let mut body = payloads.join("\n");
+                body.push('\n');
+                body
+            }
+        }
+    }
+
+    /// The `Content-Type` of the request, unless another one has been set
+    fn content_type(self) -> &'static str {
+        match self {
+            BatchFormat::Lines | BatchFormat::JsonArray => "application/json",
+            BatchFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+}
+
+impl CallLoggerBuilder {
+    /// Batches the formatted output of log events sent to a URL call target, so that a single request is made with
+    /// up to `max_records` log events, separated by new lines.  A batch is sent when it is full, once the oldest event
+    /// in the batch has waited for `max_delay`, when the logger is flushed, or when the logger is dropped.  Call
+    /// `log::logger().flush()` before the application exits to send any partial batch.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// # use std::time::Duration;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_batching(100, Duration::from_secs(5))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_batching(mut self, max_records: usize, max_delay: Duration) -> CallLoggerBuilder {
+        self.dispatcher.batch = Some(Batch::new(max_records, max_delay));
+        self
+    }
+
+    /// Sets how the log events in a batch are written in the body of the request when
+    /// [`CallLoggerBuilder::with_batching`] is used.  By default they are separated by new lines.
+    ///
+    /// Example - Send batches to the Elasticsearch bulk API
+    /// ```rust
+    /// # use call_logger::{BatchFormat, CallLogger};
+    /// # use std::time::Duration;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_batching(100, Duration::from_secs(5))
+    ///     .with_batch_format(BatchFormat::Ndjson)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_batch_format(mut self, format: BatchFormat) -> CallLoggerBuilder {
+        self.dispatcher.batch_format = format;
+        self
+    }
+}
+
+impl Target {
+    /// Batches the formatted output of log events sent to this target, which must be a URL, in the same way as
+    /// [`CallLoggerBuilder::with_batching`].
+    #[inline]
+    #[must_use]
+    pub fn with_batching(mut self, max_records: usize, max_delay: Duration) -> Target {
+        self.dispatcher.batch = Some(Batch::new(max_records, max_delay));
+        self
+    }
+}
+
+impl Dispatcher {
+    /// Adds the formatted output of a log event to the batch, sending the batch if it is due
+    pub(crate) fn add_to_batch(&self, batch: &Batch, params: String, event: Event) {
+        let level = event.level;
+        let due = {
+            let mut pending = batch.pending.lock().unwrap_or_else(|x| x.into_inner());
+            if pending.payloads.is_empty() {
+                pending.started = Some(Instant::now());
+            }
+            pending.payloads.push(params);
+            pending.events.push(event);
+            pending.level = Some(pending.level.map_or(level, |x| x.min(level)));
+            let full = pending.payloads.len() >= batch.max_records;
+            let late = pending
+                .started
+                .is_some_and(|started| started.elapsed() >= batch.max_delay);
+            if full || late {
+                Some(take(&mut *pending))
+            } else {
+                None
+            }
+        };
+        if let Some(due) = due {
+            self.send_batch(due);
+        }
+    }
+
+    /// Sends any log events waiting in the batch
+    pub(crate) fn flush_batch(&self) {
+        if let Some(batch) = &self.batch {
+            let due = take(&mut *batch.pending.lock().unwrap_or_else(|x| x.into_inner()));
+            if !due.payloads.is_empty() {
+                self.send_batch(due);
+            }
+        }
+    }
+
+    /// Sends the batch if its oldest log event has waited for the delay, and returns how long until it could be due
+    pub(crate) fn flush_due_batch(&self) -> Option<Duration> {
+        let batch = self.batch.as_ref()?;
+        let mut pending = batch.pending.lock().unwrap_or_else(|x| x.into_inner());
+        let waited = pending.started.map(|started| started.elapsed());
+        match waited {
+            Some(waited) if waited < batch.max_delay => Some(batch.max_delay - waited),
+            Some(_) if !pending.payloads.is_empty() => {
+                let due = take(&mut *pending);
+                drop(pending);
+                self.send_batch(due);
+                Some(batch.max_delay)
+            }
+            _ => Some(batch.max_delay),
+        }
+    }
+
+    fn send_batch(&self, mut due: Pending) {
+        // the log events that have waited in the batch for too long are dropped
+        if self.event_ttl.is_some() {
+            let (payloads, events) = take(&mut due.payloads)
+                .into_iter()
+                .zip(take(&mut due.events))
+                .filter(|(_, event)| !self.expires(event.logged, Duration::ZERO))
+                .unzip();
+            due.payloads = payloads;
+            due.events = events;
+            if due.payloads.is_empty() {
+                return;
+            }
+        }
+        let level = due.level.unwrap_or(Level::Error);
+        let format = self.batch_format;
+        if let Err(x) = self.call_url(
+            &self.call_target,
+            &format.body(&due.payloads),
+            level,
+            format.content_type(),
+        ) {
+            for (payload, event) in due.payloads.iter().zip(due.events.iter()) {
+                self.call_failed(payload, event, x.clone());
+            }
+        }
+    }
+}
+
+/// The configuration and the pending log events of a batching logger
+pub(crate) struct Batch {
+    max_records: usize,
+    max_delay: Duration,
+    pending: Mutex<Pending>,
+}
+
+impl Batch {
+    fn new(max_records: usize, max_delay: Duration) -> Batch {
+        Batch {
+            max_records: max_records.max(1),
+            max_delay,
+            pending: Mutex::new(Pending::default()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Pending {
+    payloads: Vec<String>,
+    events: Vec<Event>,
+    started: Option<Instant>,
+    /// The most severe level in the batch, passed to the request customizer
+    level: Option<Level>,
+}
@@ -0,0 +1,53 @@
+use crate::CallLoggerBuilder;
+
+impl CallLoggerBuilder {
+    /// Adds the version and the git commit of the application as the `version` and `git_sha` fields of every log
+    /// event, in the same way as [`CallLoggerBuilder::with_static_field`], so that an alert shows which build raised
+    /// it.  The `git_sha` field is left out if `git_sha` is empty.  See [`with_build_info!`](crate::with_build_info)
+    /// to take them from the environment that the application is built in.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_build_info("1.4.2", "3f9c2a1")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_build_info<V, S>(self, version: V, git_sha: S) -> CallLoggerBuilder
+    where
+        V: Into<String>,
+        S: Into<String>,
+    {
+        let git_sha = git_sha.into();
+        let builder = self.with_static_field("version", version);
+        if git_sha.is_empty() {
+            builder
+        } else {
+            builder.with_static_field("git_sha", git_sha)
+        }
+    }
+}
+
+/// Calls [`CallLoggerBuilder::with_build_info`] on a builder with the `CARGO_PKG_VERSION` of the crate that uses the
+/// macro, and the `VERGEN_GIT_SHA` that [vergen](https://docs.rs/vergen) sets when the crate is built, or `GIT_SHA`
+/// if that isn't set, e.g. from a CI pipeline.  The git commit is left out if neither is set.
+///
+/// Example
+/// ```rust
+/// # use call_logger::CallLogger;
+/// call_logger::with_build_info!(CallLogger::new().with_call_target("echo")).init();
+/// ```
+#[macro_export]
+macro_rules! with_build_info {
+    ($builder:expr) => {
+        $builder.with_build_info(
+            env!("CARGO_PKG_VERSION"),
+            match option_env!("VERGEN_GIT_SHA") {
+                Some(git_sha) => git_sha,
+                None => option_env!("GIT_SHA").unwrap_or_default(),
+            },
+        )
+    };
+}
@@ -0,0 +1,87 @@
+use std::fmt::Write;
+
+use chrono::{SecondsFormat, Utc};
+use log::Level;
+
+use crate::{push_json_escaped, CallLoggerBuilder, FormatContext};
+
+impl CallLoggerBuilder {
+    /// Formats each log event as a [Bunyan][bunyan] v0 record, so that the output can be piped into the `bunyan` CLI
+    /// and other node tooling.  The record has the `name` of the application, the hostname, the process id, the
+    /// numeric Bunyan level, the time in UTC and the message, followed by the key-value pairs and static fields of the
+    /// log event.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .bunyan_format("my_app")
+    ///     .json_stdout()
+    ///     .init();
+    /// log::info!("msg");
+    /// ```
+    ///
+    /// [bunyan]: https://github.com/trentm/node-bunyan#core-fields
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn bunyan_format<T>(mut self, name: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        let name = name.into();
+        self.formatter = Box::new(move |buffer, context| bunyan_formatter(buffer, &name, context));
+        self
+    }
+}
+
+/// The numeric Bunyan level of a log level
+fn bunyan_level(level: Level) -> u8 {
+    match level {
+        Level::Error => 50,
+        Level::Warn => 40,
+        Level::Info => 30,
+        Level::Debug => 20,
+        Level::Trace => 10,
+    }
+}
+
+fn bunyan_formatter(buffer: &mut String, name: &str, context: &FormatContext) {
+    buffer.push_str("{\"v\":0,\"name\":\"");
+    push_json_escaped(buffer, name);
+    buffer.push_str("\",\"hostname\":\"");
+    push_json_escaped(buffer, context.hostname());
+    // writing to a String cannot fail
+    let _ = write!(
+        buffer,
+        "\",\"pid\":{},\"level\":{},\"time\":\"{}\",",
+        std::process::id(),
+        bunyan_level(context.record().level()),
+        Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+    );
+    if let (Some(trace_id), Some(span_id)) = (context.trace_id(), context.span_id()) {
+        buffer.push_str("\"trace_id\":\"");
+        push_json_escaped(buffer, trace_id);
+        buffer.push_str("\",\"span_id\":\"");
+        push_json_escaped(buffer, span_id);
+        buffer.push_str("\",");
+    }
+    for (key, value) in context
+        .kv()
+        .iter()
+        .chain(context.scoped_fields().iter().map(|(k, v)| (k, v)))
+        .chain(context.static_fields().iter().map(|(k, v)| (k, v)))
+    {
+        buffer.push('"');
+        push_json_escaped(buffer, key);
+        buffer.push_str("\":\"");
+        push_json_escaped(buffer, value);
+        buffer.push_str("\",");
+    }
+    buffer.push_str("\"msg\":\"");
+    let message = context.message();
+    match message.as_str() {
+        Some(message) => push_json_escaped(buffer, message),
+        None => push_json_escaped(buffer, &message.to_string()),
+    }
+    buffer.push_str("\"}");
+}
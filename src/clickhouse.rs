@@ -0,0 +1,78 @@
+use crate::{encode_query, BatchFormat, CallLoggerBuilder};
+
+impl CallLoggerBuilder {
+    /// Inserts each log event as a row of a ClickHouse table using the [HTTP interface][http-interface] and the
+    /// `JSONEachRow` format.  The rows are the JSON objects produced by the default formatter, or by a custom
+    /// formatter that produces one JSON object per log event, and unknown fields are skipped.  Inserts are batched as
+    /// per [`CallLoggerBuilder::with_batching`], with a default of 1000 rows or 5 seconds, which can be changed by
+    /// calling `with_batching` after this.  The `table` can be qualified with the database, e.g. `my_db.logs`, and each
+    /// part of it is quoted as an identifier.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .clickhouse("http://localhost:8123", "logs")
+    ///     .init();
+    /// ```
+    ///
+    /// [http-interface]: https://clickhouse.com/docs/en/interfaces/http
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn clickhouse<U, T>(self, url: U, table: T) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+        T: AsRef<str>,
+    {
+        self.clickhouse_columns(url, table, &[] as &[&str])
+    }
+
+    /// As [`CallLoggerBuilder::clickhouse`], but only inserts the given columns, which are taken from the fields of the
+    /// same name in the formatted output.  The column names are quoted as identifiers.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .clickhouse_columns("http://localhost:8123", "logs", &["ts", "level", "msg"])
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn clickhouse_columns<U, T, C>(self, url: U, table: T, columns: &[C]) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+        T: AsRef<str>,
+        C: AsRef<str>,
+    {
+        let columns = if columns.is_empty() {
+            String::new()
+        } else {
+            let columns = columns
+                .iter()
+                .map(|x| quote_identifier(x.as_ref()))
+                .collect::<Vec<_>>();
+            format!(" ({})", columns.join(", "))
+        };
+        let table = table
+            .as_ref()
+            .split('.')
+            .map(quote_identifier)
+            .collect::<Vec<_>>()
+            .join(".");
+        let query = format!("INSERT INTO {table}{columns} FORMAT JSONEachRow");
+        let url = format!(
+            "{}/?input_format_skip_unknown_fields=1&query={}",
+            url.into().trim_end_matches('/'),
+            encode_query(&query)
+        );
+        self.with_call_target(url)
+            .with_batching(1000, std::time::Duration::from_secs(5))
+            .with_batch_format(BatchFormat::Ndjson)
+    }
+}
+
+/// Quotes a ClickHouse identifier with backticks, so that it can't change the meaning of the query
+fn quote_identifier(identifier: &str) -> String {
+    format!("`{}`", identifier.replace('\\', "\\\\").replace('`', "\\`"))
+}
@@ -0,0 +1,92 @@
+use std::{
+    fmt::Write,
+    io::{stdout, IsTerminal},
+};
+
+use log::Level;
+
+use crate::{CallLoggerBuilder, FormatContext};
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+
+impl CallLoggerBuilder {
+    /// Formats each log event as a line for a person reading a terminal, in the style of `env_logger`, e.g.
+    /// `[2024-05-01T12:00:00Z INFO  my_app::db] connected host=db1`.  The level is colored and the timestamp, target
+    /// and fields are dimmed, so that the message stands out while developing locally.  Colors are only written when
+    /// stdout is a terminal and the `NO_COLOR` environment variable is not set, so the output stays plain when it is
+    /// piped or collected.  Use it with the default `echo` call target, or [`CallLoggerBuilder::json_stdout`].  The
+    /// source file and line are written after the target once [`CallLoggerBuilder::with_source_links`] is set.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .colored_console_format()
+    ///     .json_stdout()
+    ///     .init();
+    /// log::info!(host = "db1"; "connected");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn colored_console_format(mut self) -> CallLoggerBuilder {
+        let colored = stdout().is_terminal()
+            && std::env::var_os("NO_COLOR").map_or(true, |no_color| no_color.is_empty());
+        self.formatter =
+            Box::new(move |buffer, context| console_formatter(buffer, colored, context));
+        self
+    }
+}
+
+/// The color of the label of a log level, as in `env_logger`
+fn level_color(level: Level) -> &'static str {
+    match level {
+        Level::Error => "\x1b[1;31m",
+        Level::Warn => "\x1b[1;33m",
+        Level::Info => "\x1b[1;32m",
+        Level::Debug => "\x1b[1;34m",
+        Level::Trace => "\x1b[1;36m",
+    }
+}
+
+pub(crate) fn console_formatter(buffer: &mut String, colored: bool, context: &FormatContext) {
+    let (dim, reset) = if colored { (DIM, RESET) } else { ("", "") };
+    buffer.push_str(dim);
+    buffer.push('[');
+    if let Some(timestamp) = context.timestamp() {
+        buffer.push_str(timestamp);
+        buffer.push(' ');
+    }
+    buffer.push_str(reset);
+    if colored {
+        buffer.push_str(level_color(context.record().level()));
+    }
+    // writing to a String cannot fail
+    let _ = write!(buffer, "{:<5}", context.level());
+    buffer.push_str(reset);
+    let _ = write!(buffer, "{dim} {}", context.record().target());
+    if context.source_url().is_some() {
+        if let Some(location) = context.source_location() {
+            let _ = write!(buffer, " {location}");
+        }
+    }
+    let _ = write!(buffer, "]{reset} {}", context.message());
+    // the key-value pairs are sorted so that the same fields are always in the same place
+    let mut kv = context.kv().iter().collect::<Vec<_>>();
+    kv.sort();
+    let fields = kv
+        .into_iter()
+        .chain(context.scoped_fields().iter().map(|(k, v)| (k, v)))
+        .chain(context.static_fields().iter().map(|(k, v)| (k, v)))
+        .collect::<Vec<_>>();
+    if !fields.is_empty() {
+        buffer.push_str(dim);
+        for (key, value) in fields {
+            let _ = write!(buffer, " {key}={value}");
+        }
+        buffer.push_str(reset);
+    }
+    if let Some(backtrace) = context.backtrace() {
+        let _ = write!(buffer, "\n{dim}{}{reset}", backtrace.trim_end());
+    }
+}
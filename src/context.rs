@@ -0,0 +1,241 @@
+use std::{cell::OnceCell, collections::HashMap, fmt::Arguments};
+
+use crate::{fields::FieldLayout, source_links::SourceLinker};
+
+use log::{
+    kv::{Error, Key, Value, VisitSource},
+    LevelFilter, Record,
+};
+
+/// The details of a log event that are passed to a formatter set with
+/// [`CallLoggerBuilder::format_with`](crate::CallLoggerBuilder::format_with).  The details are read with methods so
+/// that more of them can be added without changing the signature of the formatter.
+pub struct FormatContext<'a> {
+    timestamp: Option<&'a str>,
+    level: &'a str,
+    record: &'a Record<'a>,
+    backtrace: Option<&'a str>,
+    trace_context: Option<&'a (String, String)>,
+    kv: OnceCell<HashMap<String, String>>,
+    #[cfg(feature = "kv_serde")]
+    kv_json: OnceCell<HashMap<String, String>>,
+    scoped_fields: &'a [(String, String)],
+    static_fields: &'a [(String, String)],
+    sequence: u64,
+    hostname: &'a str,
+    field_layout: &'a FieldLayout,
+    level_filter: LevelFilter,
+    channel: &'a str,
+    channels: OnceCell<Vec<&'a str>>,
+    routed_channels: &'a dyn Fn() -> Vec<&'a str>,
+    source_links: Option<&'a SourceLinker>,
+}
+
+impl<'a> FormatContext<'a> {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        timestamp: Option<&'a str>,
+        level: &'a str,
+        record: &'a Record<'a>,
+        backtrace: Option<&'a str>,
+        trace_context: Option<&'a (String, String)>,
+        scoped_fields: &'a [(String, String)],
+        static_fields: &'a [(String, String)],
+        sequence: u64,
+        hostname: &'a str,
+        field_layout: &'a FieldLayout,
+        level_filter: LevelFilter,
+        channel: &'a str,
+        routed_channels: &'a dyn Fn() -> Vec<&'a str>,
+        source_links: Option<&'a SourceLinker>,
+    ) -> FormatContext<'a> {
+        FormatContext {
+            timestamp,
+            level,
+            record,
+            backtrace,
+            trace_context,
+            kv: OnceCell::new(),
+            #[cfg(feature = "kv_serde")]
+            kv_json: OnceCell::new(),
+            scoped_fields,
+            static_fields,
+            sequence,
+            hostname,
+            field_layout,
+            level_filter,
+            channel,
+            channels: OnceCell::new(),
+            routed_channels,
+            source_links,
+        }
+    }
+
+    /// The formatted timestamp of the log event, or `None` if the `timestamps` feature is not enabled
+    pub fn timestamp(&self) -> Option<&str> {
+        self.timestamp
+    }
+
+    /// The label of the level of the log event, which is the name of the level unless it has been changed with
+    /// [`CallLoggerBuilder::map_level`](crate::CallLoggerBuilder::map_level)
+    pub fn level(&self) -> &str {
+        self.level
+    }
+
+    /// The message that was logged
+    pub fn message(&self) -> &Arguments<'a> {
+        self.record.args()
+    }
+
+    /// The record of the log event
+    pub fn record(&self) -> &Record<'a> {
+        self.record
+    }
+
+    /// The backtrace of where the log event was logged, if it is at a level set with
+    /// [`CallLoggerBuilder::with_backtraces`](crate::CallLoggerBuilder::with_backtraces)
+    pub fn backtrace(&self) -> Option<&str> {
+        self.backtrace
+    }
+
+    /// The id of the trace that the log event was logged in, from the closure set with
+    /// [`CallLoggerBuilder::with_trace_context_provider`](crate::CallLoggerBuilder::with_trace_context_provider)
+    pub fn trace_id(&self) -> Option<&str> {
+        self.trace_context.map(|(trace_id, _)| trace_id.as_str())
+    }
+
+    /// The id of the span that the log event was logged in, from the closure set with
+    /// [`CallLoggerBuilder::with_trace_context_provider`](crate::CallLoggerBuilder::with_trace_context_provider)
+    pub fn span_id(&self) -> Option<&str> {
+        self.trace_context.map(|(_, span_id)| span_id.as_str())
+    }
+
+    /// The key-value pairs of the log event, as strings.  They are only collected the first time this is called.
+    pub fn kv(&self) -> &HashMap<String, String> {
+        self.kv.get_or_init(|| {
+            let mut visitor = MapVisitor(HashMap::new());
+            let _ = self.record.key_values().visit(&mut visitor);
+            visitor.0
+        })
+    }
+
+    /// The key-value pairs of the log event as JSON, with numbers, booleans and the maps and sequences captured with
+    /// `serde` written as they are rather than as strings, e.g. `log::info!(order:serde = order; "placed")`.  They
+    /// are only collected the first time this is called.
+    #[cfg(feature = "kv_serde")]
+    pub fn kv_json(&self) -> &HashMap<String, String> {
+        self.kv_json.get_or_init(|| {
+            let mut visitor = JsonVisitor(HashMap::new());
+            let _ = self.record.key_values().visit(&mut visitor);
+            visitor.0
+        })
+    }
+
+    /// The fields that have been added to the log events of this thread with [`push_context`](crate::push_context),
+    /// oldest first, followed by those added by the closures set with
+    /// [`CallLoggerBuilder::enrich_with`](crate::CallLoggerBuilder::enrich_with)
+    pub fn scoped_fields(&self) -> &[(String, String)] {
+        self.scoped_fields
+    }
+
+    /// The fields that are added to every log event, set with
+    /// [`CallLoggerBuilder::with_static_field`](crate::CallLoggerBuilder::with_static_field)
+    pub fn static_fields(&self) -> &[(String, String)] {
+        self.static_fields
+    }
+
+    /// The number of the log event, counting from `0` for the first event that this logger formats
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// The name of the host that is logging
+    pub fn hostname(&self) -> &str {
+        self.hostname
+    }
+
+    /// The level filter that let the log event through, which is the one set for its module with
+    /// [`CallLoggerBuilder::with_level_for`](crate::CallLoggerBuilder::with_level_for) or for its crate, or else the
+    /// level of the logger
+    pub fn level_filter(&self) -> LevelFilter {
+        self.level_filter
+    }
+
+    /// The name of the target that the output is being formatted for, which is the call target or the name set with
+    /// [`CallLoggerBuilder::with_channel_name`](crate::CallLoggerBuilder::with_channel_name), e.g. to write
+    /// `"channel":"pager"` in the payload.  Targets without their own formatter are sent the output that was formatted
+    /// for the call target.
+    pub fn channel(&self) -> &str {
+        self.channel
+    }
+
+    /// The names of all the targets that the log event is routed to, starting with the call target, leaving out those
+    /// that suppress its module or are outside of their hours
+    pub fn channels(&self) -> &[&'a str] {
+        self.channels.get_or_init(self.routed_channels)
+    }
+
+    /// The source file and line of the log event, written as `file:line`, or as a link if it has been set up with
+    /// [`CallLoggerBuilder::with_source_links`](crate::CallLoggerBuilder::with_source_links).  The link is meant for
+    /// a terminal, see [`FormatContext::source_url`] for other formats.
+    pub fn source_location(&self) -> Option<String> {
+        let (file, line) = (self.record.file()?, self.record.line()?);
+        Some(match self.source_links {
+            Some(source_links) => source_links.location(file, line, self.hostname),
+            None => format!("{file}:{line}"),
+        })
+    }
+
+    /// The URL of the source file and line of the log event, if source links have been set up with
+    /// [`CallLoggerBuilder::with_source_links`](crate::CallLoggerBuilder::with_source_links), e.g. to write a link in
+    /// Markdown or HTML
+    pub fn source_url(&self) -> Option<String> {
+        let (file, line) = (self.record.file()?, self.record.line()?);
+        Some(self.source_links?.url(file, line, self.hostname))
+    }
+
+    /// Sets the target that the output is being formatted for
+    pub(crate) fn set_channel(&mut self, channel: &'a str) {
+        self.channel = channel;
+    }
+
+    /// Where the default formatter writes the fields of the log event
+    pub(crate) fn field_layout(&self) -> &FieldLayout {
+        self.field_layout
+    }
+}
+
+/// Collects the key-value pairs of a record as strings
+struct MapVisitor(HashMap<String, String>);
+
+impl<'kvs> VisitSource<'kvs> for MapVisitor {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        self.0.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+}
+
+/// The name of this host, from the environment or `/etc/hostname`, or `localhost` if it can't be found
+pub(crate) fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+/// Collects the key-value pairs of a record as JSON, falling back to a string of the value if it can't be serialized
+#[cfg(feature = "kv_serde")]
+struct JsonVisitor(HashMap<String, String>);
+
+#[cfg(feature = "kv_serde")]
+impl<'kvs> VisitSource<'kvs> for JsonVisitor {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        let json = serde_json::to_string(&value)
+            .unwrap_or_else(|_| serde_json::Value::String(value.to_string()).to_string());
+        self.0.insert(key.to_string(), json);
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+#[cfg(feature = "files")]
+use std::{
+    fs::{metadata, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "files")]
+use log::Record;
+
+#[cfg(feature = "files")]
+use crate::{BoxError, CallTarget};
+use crate::{CallLoggerBuilder, FormatContext};
+
+impl CallLoggerBuilder {
+    /// Formats each log event as a row of comma-separated values with the given `columns`, for log pipelines that end
+    /// in a spreadsheet or a `COPY` into a database.  A column is one of `ts`, `level`, `target`, `msg`,
+    /// `module_path`, `file`, `line`, `host`, `seq`, `trace_id` or `span_id`, or the key of a key-value pair, scoped
+    /// field or static field of the log event.  Columns that the log event doesn't have are left empty, and values
+    /// that contain a comma, a quote or a line break are quoted as described in RFC 4180.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .csv_format(["ts", "level", "msg", "user_id"])
+    ///     .json_stdout()
+    ///     .init();
+    /// log::info!(user_id = 42; "logged in");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn csv_format<I, T>(mut self, columns: I) -> CallLoggerBuilder
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let columns = columns.into_iter().map(Into::into).collect::<Vec<_>>();
+        self.formatter = Box::new(move |buffer, context| csv_formatter(buffer, &columns, context));
+        self
+    }
+
+    /// Appends each log event as a row of comma-separated values with the given `columns` to a file, in the same
+    /// format as [`CallLoggerBuilder::csv_format`].  A header row with the names of the columns is written first
+    /// whenever the file is created or empty, so that each file has a header after it has been rotated, e.g. by
+    /// `logrotate`.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .csv_file("app_log.csv", ["ts", "level", "target", "msg"])
+    ///     .init();
+    /// ```
+    #[cfg(feature = "files")]
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn csv_file<P, I, T>(mut self, file: P, columns: I) -> CallLoggerBuilder
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let columns = columns.into_iter().map(Into::into).collect::<Vec<String>>();
+        let mut header = String::new();
+        for (i, column) in columns.iter().enumerate() {
+            if i > 0 {
+                header.push(',');
+            }
+            push_csv_escaped(&mut header, column);
+        }
+        self.dispatcher.target = Some(Box::new(CsvFile {
+            path: file.as_ref().to_path_buf(),
+            header,
+        }));
+        self.csv_format(columns)
+    }
+}
+
+fn csv_formatter(buffer: &mut String, columns: &[String], context: &FormatContext) {
+    let record = context.record();
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            buffer.push(',');
+        }
+        match column.as_str() {
+            "ts" => push_csv_escaped(buffer, context.timestamp().unwrap_or_default()),
+            "level" => push_csv_escaped(buffer, context.level()),
+            "target" => push_csv_escaped(buffer, record.target()),
+            "msg" => {
+                let message = context.message();
+                match message.as_str() {
+                    Some(message) => push_csv_escaped(buffer, message),
+                    None => push_csv_escaped(buffer, &message.to_string()),
+                }
+            }
+            "module_path" => push_csv_escaped(buffer, record.module_path().unwrap_or_default()),
+            "file" => push_csv_escaped(buffer, record.file().unwrap_or_default()),
+            "line" => {
+                if let Some(line) = record.line() {
+                    buffer.push_str(&line.to_string());
+                }
+            }
+            "host" => push_csv_escaped(buffer, context.hostname()),
+            "seq" => buffer.push_str(&context.sequence().to_string()),
+            "trace_id" => push_csv_escaped(buffer, context.trace_id().unwrap_or_default()),
+            "span_id" => push_csv_escaped(buffer, context.span_id().unwrap_or_default()),
+            key => {
+                let value = context.kv().get(key).map(String::as_str).or_else(|| {
+                    context
+                        .scoped_fields()
+                        .iter()
+                        .chain(context.static_fields())
+                        .find(|(k, _)| k == key)
+                        .map(|(_, v)| v.as_str())
+                });
+                push_csv_escaped(buffer, value.unwrap_or_default());
+            }
+        }
+    }
+}
+
+/// Writes a value to a row, in quotes with any quotes doubled if it contains a comma, a quote or a line break
+fn push_csv_escaped(buffer: &mut String, value: &str) {
+    if value.contains([',', '"', '\r', '\n']) {
+        buffer.push('"');
+        buffer.push_str(&value.replace('"', "\"\""));
+        buffer.push('"');
+    } else {
+        buffer.push_str(value);
+    }
+}
+
+/// Appends each row to a file, after a header row if the file is new or has been emptied
+#[cfg(feature = "files")]
+struct CsvFile {
+    path: PathBuf,
+    header: String,
+}
+
+#[cfg(feature = "files")]
+impl CallTarget for CsvFile {
+    fn call(&self, payload: &str, _record: &Record) -> Result<(), BoxError> {
+        let new_file = metadata(&self.path).map_or(true, |x| x.len() == 0);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        if new_file {
+            file.write_all(format!("{}\n{payload}\n", self.header).as_bytes())?;
+        } else {
+            file.write_all(format!("{payload}\n").as_bytes())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    fs::{read_to_string, remove_file, rename, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    thread::sleep,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use log::Level;
+
+use crate::{escape_json, internal::report, CallLogger, CallLoggerBuilder, Dispatcher, Event};
+
+impl CallLoggerBuilder {
+    /// Writes log events that could not be delivered to the call target to a dead-letter file, so that they are not
+    /// lost and can be re-sent later with [`CallLogger::replay_dead_letters`].  Each line of the file is a JSON
+    /// object with the time of the failure in milliseconds since the epoch (`ts`), the `level`, `target` and `msg` of
+    /// the log event, the `reason` the delivery failed and the formatted `payload` that could not be delivered.  The
+    /// payload is written after the middleware, so that it is scrubbed or encrypted in the file as it would have been
+    /// when delivered, and the `msg` is left empty when there is any middleware.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_dead_letter_file("dead_letters.ndjson")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_dead_letter_file<P>(mut self, file: P) -> CallLoggerBuilder
+    where
+        P: AsRef<Path>,
+    {
+        self.dispatcher.dead_letter_file = Some(file.as_ref().to_path_buf());
+        self
+    }
+
+    /// Limits the number of payloads per second that are re-sent by [`CallLogger::replay`], so that the backlog of an
+    /// outage does not overwhelm the call target when it is drained.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// let logger = CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_replay_rate(5);
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_replay_rate(mut self, per_second: u32) -> CallLoggerBuilder {
+        self.replay_interval = Some(Duration::from_secs(1) / per_second.max(1));
+        self
+    }
+}
+
+impl CallLogger {
+    /// Re-sends the payloads in the dead-letter file to the call target, e.g. at startup once an outage is over, as
+    /// per [`CallLogger::replay`].  Returns the number of payloads that were delivered, or `0` if there is no
+    /// dead-letter file.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// let logger = CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_dead_letter_file("dead_letters.ndjson")
+    ///     .build()
+    ///     .unwrap();
+    /// let _ = logger.replay_dead_letters();
+    /// logger.init();
+    /// ```
+    pub fn replay_dead_letters(&self) -> io::Result<usize> {
+        match &self.dispatcher.dead_letter_file {
+            Some(file) if file.exists() || replaying(file).exists() => self.replay(file),
+            _ => Ok(0),
+        }
+    }
+
+    /// Re-sends the payloads in a file to the call target, at the rate set by [`CallLoggerBuilder::with_replay_rate`].
+    /// Each line of the file that was written to a dead-letter file is re-sent with the level, target and message of
+    /// the original log event, any other line is re-sent as it is at the info level.  Payloads are re-sent as they are,
+    /// without the middleware, which they went through before they were written.  The file is renamed with a
+    /// `.replaying` extension while it is replayed and removed once every line has been delivered or written to the
+    /// dead-letter file again, which can be the same file.  If the replay is interrupted, e.g. by a crash, the next
+    /// replay of the file starts again from the `.replaying` file, so some payloads may be delivered twice.  Lines that
+    /// were written longer ago than [`CallLoggerBuilder::with_event_ttl`] are dropped.  Returns the number of payloads
+    /// that were delivered.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// let logger = CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_dead_letter_file("dead_letters.ndjson")
+    ///     .with_replay_rate(5)
+    ///     .build()
+    ///     .unwrap();
+    /// let _ = logger.replay("outage.ndjson");
+    /// logger.init();
+    /// ```
+    pub fn replay<P>(&self, file: P) -> io::Result<usize>
+    where
+        P: AsRef<Path>,
+    {
+        let file = file.as_ref();
+        let replaying = replaying(file);
+        // the file of a replay that was interrupted is replayed before any more lines are taken from the file
+        if !replaying.exists() {
+            rename(file, &replaying)?;
+        }
+        let lines = read_to_string(&replaying)?;
+        let mut delivered = 0;
+        let mut last = None::<Instant>;
+        for line in lines.lines().filter(|x| !x.trim().is_empty()) {
+            let (payload, event) = match (json_field(line, "payload"), parse_event(line)) {
+                (Some(payload), Some(event)) => (payload, event),
+                _ => (
+                    line.to_string(),
+                    Event {
+                        level: Level::Info,
+                        target: module_path!().to_string(),
+                        msg: line.to_string(),
+                        module_path: None,
+                        file: None,
+                        line: None,
+                        logged: SystemTime::now(),
+                    },
+                ),
+            };
+            if self.dispatcher.expires(event.logged, Duration::ZERO) {
+                continue;
+            }
+            if let (Some(interval), Some(last)) = (self.replay_interval, last) {
+                sleep(interval.saturating_sub(last.elapsed()));
+            }
+            last = Some(Instant::now());
+            match event.with_record(|record| self.dispatcher.deliver(&payload, record)) {
+                Ok(()) => delivered += 1,
+                Err(x) => self.dispatcher.call_failed(&payload, &event, x),
+            }
+        }
+        self.dispatcher.flush_batch();
+        remove_file(&replaying)?;
+        Ok(delivered)
+    }
+}
+
+/// The file that a file is renamed to while it is replayed
+fn replaying(file: &Path) -> PathBuf {
+    let mut replaying = file.to_path_buf().into_os_string();
+    replaying.push(".replaying");
+    PathBuf::from(replaying)
+}
+
+impl Dispatcher {
+    /// Appends a log event that could not be delivered to the dead-letter file
+    pub(crate) fn write_dead_letter(&self, params: &str, event: &Event, reason: &str) {
+        if let Some(file) = &self.dead_letter_file {
+            let ts = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|x| x.as_millis())
+                .unwrap_or_default();
+            let line = format!(
+                "{{\"ts\":{ts},\"level\":\"{}\",\"target\":\"{}\",\"msg\":\"{}\",\"reason\":\"{}\",\"payload\":\"{}\"}}\n",
+                event.level,
+                escape_json(&event.target),
+                escape_json(if self.dead_letter_redact { "" } else { &event.msg }),
+                escape_json(reason),
+                escape_json(params)
+            );
+            let written = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(file)
+                .and_then(|mut file| {
+                    file.write_all(line.as_bytes())?;
+                    if self.dead_letter_sync {
+                        file.sync_all()?;
+                    }
+                    Ok(())
+                });
+            if let Err(x) = written {
+                report(format_args!(
+                    "logging to dead-letter file {} failed {x}",
+                    file.display()
+                ));
+            }
+        }
+    }
+}
+
+fn parse_event(line: &str) -> Option<Event> {
+    Some(Event {
+        level: json_field(line, "level")?.parse::<Level>().ok()?,
+        target: json_field(line, "target")?,
+        msg: json_field(line, "msg")?,
+        module_path: None,
+        file: None,
+        line: None,
+        logged: json_number(line, "ts")
+            .map_or_else(SystemTime::now, |ts| UNIX_EPOCH + Duration::from_millis(ts)),
+    })
+}
+
+/// Reads the value of a whole number field from a JSON object written by [`Dispatcher::write_dead_letter`]
+fn json_number(line: &str, name: &str) -> Option<u64> {
+    let start = line.find(&format!("\"{name}\":"))? + name.len() + 3;
+    let digits = line[start..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect::<String>();
+    digits.parse().ok()
+}
+
+/// Reads the value of a string field from a JSON object written by [`Dispatcher::write_dead_letter`]
+pub(crate) fn json_field(line: &str, name: &str) -> Option<String> {
+    let start = line.find(&format!("\"{name}\":\""))? + name.len() + 4;
+    let mut value = String::new();
+    let mut chars = line[start..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\"' => return Some(value),
+            '\\' => match chars.next()? {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                'u' => {
+                    let code = chars.by_ref().take(4).collect::<String>();
+                    value.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+                }
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
@@ -0,0 +1,103 @@
+use std::{
+    process::{Child, Command},
+    sync::Mutex,
+};
+
+use log::{Level, Record};
+
+use crate::{BoxError, CallLoggerBuilder, CallTarget};
+
+impl CallLoggerBuilder {
+    /// Shows warning and error log events as native desktop notifications instead of calling the call target.  The
+    /// notification title is the level and target of the log event and the body is the message, the formatter is not
+    /// used.  Notifications are shown with `notify-send` on Linux and other unix systems, `osascript` on macOS and a
+    /// PowerShell toast on Windows.  The title and message are passed as separate arguments or environment variables,
+    /// so they do not need to be quoted.  The commands are not waited for, the ones that have exited are reaped when
+    /// the next notification is shown.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .desktop_notify()
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn desktop_notify(mut self) -> CallLoggerBuilder {
+        self.dispatcher.target = Some(Box::<DesktopNotify>::default());
+        self
+    }
+}
+
+/// Shows desktop notifications, keeping the commands that are still running so that they can be reaped
+#[derive(Default)]
+struct DesktopNotify {
+    children: Mutex<Vec<Child>>,
+}
+
+impl DesktopNotify {
+    /// Reaps the commands that have exited, dropping the ones that can't be waited for
+    fn reap(children: &mut Vec<Child>) {
+        children.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+    }
+}
+
+impl CallTarget for DesktopNotify {
+    fn call(&self, _payload: &str, record: &Record) -> Result<(), BoxError> {
+        if record.level() <= Level::Warn {
+            let title = format!("{} - {}", record.level(), record.target());
+            let mut children = self.children.lock().unwrap_or_else(|x| x.into_inner());
+            DesktopNotify::reap(&mut children);
+            children.push(notify_command(&title, &record.args().to_string()).spawn()?);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DesktopNotify {
+    fn drop(&mut self) {
+        DesktopNotify::reap(self.children.get_mut().unwrap_or_else(|x| x.into_inner()));
+    }
+}
+
+/// Builds the command that shows a desktop notification on this platform
+#[cfg(target_os = "macos")]
+pub(crate) fn notify_command(title: &str, message: &str) -> Command {
+    fn quote(value: &str) -> String {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('\"', "\\\""))
+    }
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(format!(
+        "display notification {} with title {}",
+        quote(message),
+        quote(title)
+    ));
+    command
+}
+
+/// Builds the command that shows a desktop notification on this platform
+#[cfg(windows)]
+pub(crate) fn notify_command(title: &str, message: &str) -> Command {
+    const SCRIPT: &str = "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null; \
+        $template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+        $text = $template.GetElementsByTagName('text'); \
+        $text.Item(0).AppendChild($template.CreateTextNode($env:CALL_LOGGER_TITLE)) | Out-Null; \
+        $text.Item(1).AppendChild($template.CreateTextNode($env:CALL_LOGGER_MESSAGE)) | Out-Null; \
+        $toast = [Windows.UI.Notifications.ToastNotification]::new($template); \
+        [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('call_logger').Show($toast)";
+    let mut command = Command::new("powershell");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("CALL_LOGGER_TITLE", title)
+        .env("CALL_LOGGER_MESSAGE", message);
+    command
+}
+
+/// Builds the command that shows a desktop notification on this platform
+#[cfg(not(any(target_os = "macos", windows)))]
+pub(crate) fn notify_command(title: &str, message: &str) -> Command {
+    let mut command = Command::new("notify-send");
+    command.arg("--").arg(title).arg(message);
+    command
+}
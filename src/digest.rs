@@ -0,0 +1,220 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    mem::take,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::{Level, Record};
+
+use crate::{
+    dispatch::Dispatcher, fingerprint::FingerprintSlot, push_json_escaped, CallLoggerBuilder,
+    Event, Middleware, Target,
+};
+
+/// The number of payloads that are kept as samples in a digest
+const MAX_SAMPLES: usize = 5;
+
+impl CallLoggerBuilder {
+    /// Sends a summary of the log events below error every `interval` instead of a call for each of them, e.g. for a
+    /// chat channel that should only hear about warnings now and then, while errors are still delivered straight
+    /// away.  The summary is a JSON object with the number of log events, the count for each level and module, and
+    /// the payloads of the first few different log events as they would have been delivered, e.g.
+    /// `{"digest":12,"levels":{"WARN":3,"INFO":9},"modules":{"my_app":12},"samples":["..."]}`.  The formatter is not
+    /// used for it, but it is passed through the middleware and encrypted like any other payload.  The summary is sent
+    /// once the interval has passed since the first log event in it, when the logger is flushed, or when the logger is
+    /// dropped.  Call `log::logger().flush()` before the application exits to send the last summary.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// # use std::time::Duration;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .digest(Duration::from_secs(15 * 60))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn digest(mut self, interval: Duration) -> CallLoggerBuilder {
+        self.dispatcher.digest = Some(Digest::new(interval));
+        self
+    }
+}
+
+impl Target {
+    /// Sends a summary of the log events below error to this target every `interval`, in the same way as
+    /// [`CallLoggerBuilder::digest`].
+    #[inline]
+    #[must_use]
+    pub fn digest(mut self, interval: Duration) -> Target {
+        self.dispatcher.digest = Some(Digest::new(interval));
+        self
+    }
+}
+
+/// The log events that have been summed up since the last summary was sent
+pub(crate) struct Digest {
+    interval: Duration,
+    pending: Mutex<Summary>,
+}
+
+#[derive(Default)]
+struct Summary {
+    started: Option<Instant>,
+    count: u64,
+    levels: BTreeMap<Level, u64>,
+    modules: BTreeMap<String, u64>,
+    samples: Vec<String>,
+    /// The fingerprints of the log events that the samples are from
+    sampled: Vec<String>,
+}
+
+impl Digest {
+    fn new(interval: Duration) -> Digest {
+        Digest {
+            interval,
+            pending: Mutex::new(Summary::default()),
+        }
+    }
+
+    /// Adds the formatted output of a log event to the summary
+    fn add(&self, params: &str, record: &Record, fingerprint: &FingerprintSlot) {
+        let mut pending = self.pending.lock().unwrap_or_else(|x| x.into_inner());
+        pending.started.get_or_insert_with(Instant::now);
+        pending.count += 1;
+        *pending.levels.entry(record.level()).or_default() += 1;
+        let module = record.module_path().unwrap_or(record.target());
+        *pending.modules.entry(module.to_string()).or_default() += 1;
+        if pending.samples.len() < MAX_SAMPLES {
+            // only the first log event with each fingerprint is a sample, so that one noisy event doesn't fill them
+            let fingerprint = fingerprint.of(record);
+            if !pending.sampled.contains(&fingerprint) {
+                pending.samples.push(params.to_string());
+                pending.sampled.push(fingerprint);
+            }
+        }
+    }
+
+    /// Takes the summary if it has log events and, unless `now` is set, its interval has passed
+    fn take(&self, now: bool) -> Option<Summary> {
+        let mut pending = self.pending.lock().unwrap_or_else(|x| x.into_inner());
+        let due = pending
+            .started
+            .is_some_and(|started| now || started.elapsed() >= self.interval);
+        due.then(|| take(&mut *pending))
+    }
+}
+
+impl Summary {
+    /// The JSON object that is delivered for the summary
+    fn payload(&self) -> String {
+        // writing to a String cannot fail
+        let mut payload = format!("{{\"digest\":{},\"levels\":{{", self.count);
+        for (i, (level, count)) in self.levels.iter().enumerate() {
+            let comma = if i == 0 { "" } else { "," };
+            let _ = write!(payload, "{comma}\"{level}\":{count}");
+        }
+        payload.push_str("},\"modules\":{");
+        for (i, (module, count)) in self.modules.iter().enumerate() {
+            if i > 0 {
+                payload.push(',');
+            }
+            payload.push('"');
+            push_json_escaped(&mut payload, module);
+            let _ = write!(payload, "\":{count}");
+        }
+        payload.push_str("},\"samples\":[");
+        for (i, sample) in self.samples.iter().enumerate() {
+            if i > 0 {
+                payload.push(',');
+            }
+            payload.push('"');
+            push_json_escaped(&mut payload, sample);
+            payload.push('"');
+        }
+        payload.push_str("]}");
+        payload
+    }
+}
+
+impl Dispatcher {
+    /// Adds the log event to the digest unless it is an error, and sends the summary if it is due.  Returns whether
+    /// the log event was added, in which case it isn't delivered on its own.
+    pub(crate) fn add_to_digest(&self, params: &str, record: &Record) -> bool {
+        let Some(digest) = &self.digest else {
+            return false;
+        };
+        let digested = record.level() != Level::Error;
+        if digested {
+            digest.add(params, record, &self.fingerprint);
+        }
+        if let Some(summary) = digest.take(false) {
+            self.send_summary(&summary);
+        }
+        digested
+    }
+
+    /// Sends the summary of the log events in the digest, if there are any
+    pub(crate) fn flush_digest(&self) {
+        if let Some(summary) = self.digest.as_ref().and_then(|digest| digest.take(true)) {
+            self.send_summary(&summary);
+        }
+    }
+
+    /// Sends the summary if its interval has passed, and returns how long until it could be due
+    pub(crate) fn flush_due_digest(&self) -> Option<Duration> {
+        let digest = self.digest.as_ref()?;
+        if let Some(summary) = digest.take(false) {
+            self.send_summary(&summary);
+        }
+        let pending = digest.pending.lock().unwrap_or_else(|x| x.into_inner());
+        let waited = pending
+            .started
+            .map_or(Duration::ZERO, |started| started.elapsed());
+        Some(digest.interval.saturating_sub(waited))
+    }
+
+    /// Whether this dispatcher or its fallback sends a digest
+    pub(crate) fn digests(&self) -> bool {
+        self.digest.is_some()
+            || self
+                .over_limit_fallback
+                .as_ref()
+                .is_some_and(|fallback| fallback.digests())
+    }
+
+    /// Shares the middleware of the logger with this dispatcher and its fallback, for the summaries of their digests
+    pub(crate) fn share_middleware(&mut self, middleware: &Arc<[Box<Middleware>]>) {
+        self.middleware = middleware.clone();
+        if let Some(fallback) = &mut self.over_limit_fallback {
+            fallback.share_middleware(middleware);
+        }
+    }
+
+    /// Passes the summary through the middleware of the logger, so that it is scrubbed and encrypted in the same way
+    /// as other log events, then sends it
+    fn send_summary(&self, summary: &Summary) {
+        let Some(payload) = self
+            .middleware
+            .iter()
+            .try_fold(summary.payload(), |payload, middleware| middleware(payload))
+        else {
+            return;
+        };
+        let level = summary.levels.keys().next().copied().unwrap_or(Level::Warn);
+        let send = |record: &Record| {
+            if let Err(x) = self.send(&payload, record) {
+                self.call_failed(&payload, &Event::from(record), x);
+            }
+        };
+        send(
+            &Record::builder()
+                .args(format_args!("digest of {} log events", summary.count))
+                .level(level)
+                .target("call_logger")
+                .build(),
+        );
+    }
+}
@@ -0,0 +1,1082 @@
+use std::{
+    borrow::Cow,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex, OnceLock, Weak,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "process")]
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+#[cfg(all(
+    feature = "process",
+    not(feature = "no-exec"),
+    not(target_arch = "wasm32")
+))]
+use std::process::{Command, Stdio};
+
+#[cfg(any(feature = "process", feature = "files"))]
+use std::path::PathBuf;
+
+use log::{Level, Record};
+
+#[cfg(feature = "timestamps")]
+use crate::quiet_hours::HourRange;
+#[cfg(all(
+    feature = "process",
+    not(feature = "no-exec"),
+    not(target_arch = "wasm32")
+))]
+use crate::shell;
+#[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+use crate::OutputMapper;
+use crate::{
+    batch::{Batch, BatchFormat},
+    digest::Digest,
+    echo::{self, Echo, Outcome},
+    event_ttl::EventTtl,
+    filter::glob_match,
+    fingerprint::FingerprintSlot,
+    in_flight::InFlight,
+    internal::report,
+    rate_limit::{DailyBudget, RateLimit},
+    threshold::Threshold,
+    CallError, CallErrorKind, CallTarget, DeliveryFilter, ErrorHandler, Event, HttpTransport,
+    Middleware, RequestSigner, Route,
+};
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+use crate::{dns::DnsCache, IpFamily, RequestCustomizer, StatusPolicy};
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+use std::ops::RangeInclusive;
+
+/// The modules of the HTTP client, whose log events are not sent to a URL
+const HTTP_CLIENT_MODULES: &[&str] = &["ureq::*", "rustls::*"];
+
+/// The part of the logger that delivers the formatted output of log events, which is shared with the threads that
+/// deliver in the background.
+pub(crate) struct Dispatcher {
+    /// The target call to make every time a logging event occurs
+    pub(crate) call_target: String,
+
+    /// The name of the target that formatters are told the log event is routed to, instead of the call target
+    pub(crate) channel_name: Option<String>,
+
+    /// The file to write the output of the call to
+    #[cfg(feature = "process")]
+    pub(crate) file: Option<PathBuf>,
+
+    /// The buffered writer for the file, opened when the output of the first call is written
+    #[cfg(feature = "process")]
+    file_writer: Mutex<Option<FileWriter>>,
+
+    /// The longest time that the output of a call is buffered before it is written to the file
+    #[cfg(feature = "process")]
+    pub(crate) file_flush_interval: Duration,
+
+    /// A closure that turns the output of the call into what is written to the file
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    pub(crate) output_mapper: Option<Box<OutputMapper>>,
+
+    /// The URL that the output of the call is posted to
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    pub(crate) pipe_url: Option<String>,
+
+    /// The executable that is run instead of looking up the call target in `PATH`
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    pub(crate) exec_path: Option<PathBuf>,
+
+    /// The only applications that can be called, if they are restricted
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    pub(crate) allowed_programs: Option<Vec<String>>,
+
+    /// Run the call target through the system shell
+    #[cfg(feature = "process")]
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub(crate) via_shell: bool,
+
+    /// How the formatted output is passed to the application
+    #[cfg(feature = "process")]
+    pub(crate) payload_delivery: PayloadDelivery,
+
+    /// Pass `--` before the formatted output, so that it is never taken as an option by the application
+    #[cfg(feature = "process")]
+    pub(crate) options_terminator: bool,
+
+    /// Wait for the application to exit and treat a non-zero exit status as a failure, for audit mode
+    #[cfg(all(
+        feature = "process",
+        not(feature = "no-exec"),
+        not(target_arch = "wasm32")
+    ))]
+    pub(crate) wait_for_exit: bool,
+
+    /// Echo everything to console just before making the call, to aid debugging.
+    pub(crate) echo: Option<Echo>,
+
+    /// Headers that are added to the HTTP request sent to a URL call target
+    pub(crate) http_headers: Vec<(String, String)>,
+
+    /// A closure that can alter the HTTP request before it is sent to a URL call target
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub(crate) request_customizer: Option<Box<RequestCustomizer>>,
+
+    /// What to do with the responses with these statuses, checked in order
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub(crate) status_policies: Vec<(RangeInclusive<u16>, StatusPolicy)>,
+
+    /// The cache of resolved hostnames and the HTTP client that uses it
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub(crate) dns: Option<Arc<DnsCache>>,
+
+    /// The family of addresses that are tried first when a hostname resolves to both
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub(crate) prefer_ip: Option<IpFamily>,
+
+    /// The HTTP client that uses the DNS cache and the preferred family, created when the logger is built
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub(crate) agent: Option<ureq::Agent>,
+
+    /// A closure that sends the HTTP request to a URL call target instead of the built in HTTP client
+    pub(crate) http_transport: Option<Box<HttpTransport>>,
+
+    /// Limits the number of HTTP requests that are sent at the same time
+    pub(crate) in_flight: Option<InFlight>,
+
+    /// A target that is called with the formatted output instead of the call target
+    pub(crate) target: Option<Box<dyn CallTarget>>,
+
+    /// Only the log events that this accepts are delivered
+    pub(crate) accepts: Option<Box<DeliveryFilter>>,
+
+    /// Picks the URL of each log event instead of the call target
+    pub(crate) route: Option<Box<Route>>,
+
+    /// The key of the key-value pair that holds the URL of a log event, instead of the call target
+    pub(crate) url_key: Option<String>,
+
+    /// The only schemes of the URLs that log events can be sent to, if they are restricted
+    pub(crate) allowed_schemes: Option<Vec<String>>,
+
+    /// The glob patterns of the only hosts that log events can be sent to, if they are restricted
+    pub(crate) allowed_hosts: Option<Vec<String>>,
+
+    /// The glob patterns of the modules whose log events are not delivered
+    pub(crate) suppressed_modules: Vec<String>,
+
+    /// The most log events that are delivered each second, if they are limited
+    pub(crate) rate_limit: Option<RateLimit>,
+
+    /// The most log events that are delivered each day, if they are limited
+    pub(crate) daily_budget: Option<DailyBudget>,
+
+    /// Where the log events over the rate limit or daily budget are delivered instead
+    pub(crate) over_limit_fallback: Option<Box<Dispatcher>>,
+
+    /// The hours that log events are routed to another target instead
+    #[cfg(feature = "timestamps")]
+    pub(crate) quiet_hours: Vec<HourRange>,
+
+    /// The only hours that log events are delivered, when quiet hours are routed to this target
+    #[cfg(feature = "timestamps")]
+    pub(crate) only_during: Option<HourRange>,
+
+    /// The JSON Schema that the formatted output must match, or why it couldn't be parsed
+    #[cfg(feature = "schema")]
+    pub(crate) schema: Option<Result<serde_json::Value, String>>,
+
+    /// Adds the headers that sign the body of each HTTP request
+    pub(crate) signer: Option<Box<RequestSigner>>,
+
+    /// What makes log events the same event, for the features that group them
+    pub(crate) fingerprint: FingerprintSlot,
+
+    /// The number of warnings with the same fingerprint that are needed before this target is called
+    pub(crate) threshold: Option<Threshold>,
+
+    /// The summary of the log events below error that is sent instead of them
+    pub(crate) digest: Option<Digest>,
+
+    /// The middleware of the logger, which the summary of the digest is passed through as the logger doesn't format it
+    pub(crate) middleware: Arc<[Box<Middleware>]>,
+
+    /// How old a log event can be when it is delivered
+    pub(crate) event_ttl: Option<EventTtl>,
+
+    /// The dispatcher that the target reports the log events that it buffered but couldn't deliver to, and makes its
+    /// own HTTP requests with, which is this one unless it is an over-limit fallback
+    pub(crate) owner: DispatcherSlot,
+
+    /// Log events waiting to be sent together to a URL call target
+    pub(crate) batch: Option<Batch>,
+
+    /// How the log events in a batch are written in the body of the request
+    pub(crate) batch_format: BatchFormat,
+
+    /// The file that log events that could not be delivered are written to
+    #[cfg(feature = "files")]
+    pub(crate) dead_letter_file: Option<PathBuf>,
+
+    /// Sync each write to the dead-letter file to the disk
+    #[cfg(feature = "files")]
+    pub(crate) dead_letter_sync: bool,
+
+    /// Leave the message of the log event out of the dead-letter file, as the payload has been through middleware
+    /// that may have scrubbed or encrypted it
+    #[cfg(feature = "files")]
+    pub(crate) dead_letter_redact: bool,
+
+    /// A closure that is told about log events that could not be delivered instead of printing them
+    pub(crate) error_handler: Option<Arc<ErrorHandler>>,
+}
+
+impl Dispatcher {
+    pub(crate) fn new() -> Dispatcher {
+        Dispatcher {
+            // default to calling echo which will output the log event to console
+            call_target: "echo".into(),
+            channel_name: None,
+            #[cfg(feature = "process")]
+            file: None,
+            #[cfg(feature = "process")]
+            file_writer: Mutex::new(None),
+            #[cfg(feature = "process")]
+            file_flush_interval: Duration::ZERO,
+            #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+            output_mapper: None,
+            #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+            pipe_url: None,
+            #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+            exec_path: None,
+            #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+            allowed_programs: None,
+            #[cfg(feature = "process")]
+            via_shell: false,
+            #[cfg(feature = "process")]
+            payload_delivery: PayloadDelivery::Argument,
+            #[cfg(feature = "process")]
+            options_terminator: false,
+            #[cfg(all(
+                feature = "process",
+                not(feature = "no-exec"),
+                not(target_arch = "wasm32")
+            ))]
+            wait_for_exit: false,
+            echo: None,
+            http_headers: Vec::new(),
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            request_customizer: None,
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            status_policies: Vec::new(),
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            dns: None,
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            prefer_ip: None,
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            agent: None,
+            http_transport: None,
+            in_flight: None,
+            target: None,
+            accepts: None,
+            route: None,
+            url_key: None,
+            allowed_schemes: None,
+            allowed_hosts: None,
+            suppressed_modules: Vec::new(),
+            rate_limit: None,
+            daily_budget: None,
+            over_limit_fallback: None,
+            #[cfg(feature = "timestamps")]
+            quiet_hours: Vec::new(),
+            #[cfg(feature = "timestamps")]
+            only_during: None,
+            #[cfg(feature = "schema")]
+            schema: None,
+            signer: None,
+            fingerprint: FingerprintSlot::default(),
+            threshold: None,
+            digest: None,
+            middleware: Arc::new([]),
+            event_ttl: None,
+            owner: DispatcherSlot::default(),
+            batch: None,
+            batch_format: BatchFormat::default(),
+            #[cfg(feature = "files")]
+            dead_letter_file: None,
+            #[cfg(feature = "files")]
+            dead_letter_sync: false,
+            #[cfg(feature = "files")]
+            dead_letter_redact: false,
+            error_handler: None,
+        }
+    }
+
+    /// Delivers the formatted output of a log event to the target
+    pub(crate) fn deliver(&self, params: &str, record: &Record) -> Result<(), CallError> {
+        if self
+            .accepts
+            .as_ref()
+            .is_some_and(|accepts| !accepts(params, record))
+            || self.below_threshold(record)
+        {
+            return Ok(());
+        }
+        #[cfg(feature = "timestamps")]
+        if !self.is_on_duty() {
+            return Ok(());
+        }
+        #[cfg(feature = "schema")]
+        if let Some(Ok(schema)) = &self.schema {
+            crate::schema::check(schema, params)?;
+        }
+        if !self.suppresses(record) && self.add_to_digest(params, record) {
+            return Ok(());
+        }
+        self.send(params, record)
+    }
+
+    /// Delivers the formatted output of a log event to the target once it has been through the filters
+    pub(crate) fn send(&self, params: &str, record: &Record) -> Result<(), CallError> {
+        let echo = self
+            .echo
+            .as_ref()
+            .is_some_and(|echo| echo.sampled(record.level()));
+        let started = echo.then(Instant::now);
+        let outcome = if self.suppresses(record) {
+            Ok(Outcome::Dropped)
+        } else if let Some(reason) = self.over_limit() {
+            match &self.over_limit_fallback {
+                Some(fallback) => fallback.deliver(params, record).map(|_| Outcome::Delivered),
+                None => Err(CallError::new(CallErrorKind::OverLimit, reason)),
+            }
+        } else if let Some(target) = &self.target {
+            if echo {
+                println!("Calling: `{params}`");
+            }
+            target
+                .call(params, record)
+                .map(|_| Outcome::Delivered)
+                .map_err(|x| CallError::new(CallErrorKind::Target, x.to_string()))
+        } else if self.is_url() {
+            let url = match (self.kv_url(record), &self.route) {
+                (Some(url), _) => Cow::Owned(url),
+                (None, Some(route)) => Cow::Owned(route(params, record)),
+                (None, None) => Cow::Borrowed(self.call_target.as_str()),
+            };
+            if let Err(reason) = self.check_url_policy(&url) {
+                return Err(CallError::new(
+                    CallErrorKind::Unsupported,
+                    format!("the URL `{url}` can't be used, {reason}"),
+                ));
+            }
+            if echo {
+                println!("Calling: `{url}\n\t{params}`");
+            }
+            match &self.batch {
+                Some(batch) => {
+                    self.add_to_batch(batch, params.to_string(), Event::from(record));
+                    Ok(Outcome::Batched)
+                }
+                None => self
+                    .call_url(&url, params, record.level(), "application/json")
+                    .map(|status| status.map_or(Outcome::Delivered, Outcome::Status)),
+            }
+        } else {
+            self.call_process(params, record.level(), echo)
+        };
+        if let Some(started) = started {
+            println!(
+                "{}",
+                echo::outcome_line(params, started.elapsed(), &outcome)
+            );
+        }
+        outcome.map(|_| ())
+    }
+
+    /// Calls the application with the formatted output of a log event as an argument, or on its stdin, then writes
+    /// its output to the file and posts it to the pipe URL if they are set
+    #[cfg(all(
+        feature = "process",
+        not(feature = "no-exec"),
+        not(target_arch = "wasm32")
+    ))]
+    fn call_process(&self, params: &str, level: Level, echo: bool) -> Result<Outcome, CallError> {
+        if !self.allows_program() {
+            return Err(CallError::new(
+                CallErrorKind::Spawn,
+                "the application is not one of the call targets that are allowed",
+            ));
+        }
+        let mut command = match &self.exec_path {
+            Some(path) => {
+                let mut args = Vec::new();
+                self.push_payload(&mut args, params);
+                if echo {
+                    println!("Calling: `{} {}`", path.display(), args.join(" "));
+                }
+                let mut command = Command::new(path);
+                command.args(args);
+                command
+            }
+            None => {
+                let mut args = self.process_args(params);
+                if echo {
+                    println!("Calling: `{}`", args.join(" "));
+                }
+                let call_target = args.remove(0);
+                shell::command(call_target, &args, self.via_shell)
+            }
+        };
+        let via_stdin = self.payload_delivery == PayloadDelivery::Stdin;
+        if via_stdin {
+            command.stdin(Stdio::piped());
+        }
+        if let PayloadDelivery::Env(name) = &self.payload_delivery {
+            command.env(name, params);
+        }
+        let keep_output = self.file.is_some() || self.pipe_url.is_some();
+        if keep_output {
+            if !via_stdin {
+                command.stdin(Stdio::null());
+            }
+            command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+        let mut child = command
+            .spawn()
+            .map_err(|x| CallError::new(CallErrorKind::Spawn, x.to_string()))?;
+        let stdin_failed = |x: io::Error| {
+            CallError::new(CallErrorKind::Io, format!("writing to stdin failed {x}"))
+        };
+        let mut writer = None;
+        if let Some(mut stdin) = child.stdin.take() {
+            // the pipe is closed when `stdin` is dropped, so that the application sees the end of the payload.  When
+            // the output is kept, the payload is written on a thread of its own while the output is read, as an
+            // application that fills the output pipe before it has read all of the payload would otherwise block.
+            if keep_output {
+                let payload = params.to_owned();
+                writer = Some(thread::spawn(move || stdin.write_all(payload.as_bytes())));
+            } else {
+                stdin.write_all(params.as_bytes()).map_err(stdin_failed)?;
+            }
+        }
+        if !keep_output {
+            // the application is only waited for when the call is echoed, to show its exit code, or in audit mode
+            if !echo && !self.wait_for_exit {
+                return Ok(Outcome::Started);
+            }
+            let status = child
+                .wait()
+                .map_err(|x| CallError::new(CallErrorKind::Io, x.to_string()))?;
+            if self.wait_for_exit && !status.success() {
+                return Err(CallError::new(
+                    CallErrorKind::Spawn,
+                    format!(
+                        "{} failed with {status}",
+                        command.get_program().to_string_lossy()
+                    ),
+                ));
+            }
+            return Ok(Outcome::Exit(status.code()));
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|x| CallError::new(CallErrorKind::Io, x.to_string()))?;
+        if let Some(writer) = writer {
+            writer
+                .join()
+                .unwrap_or_else(|_| {
+                    Err(io::Error::new(io::ErrorKind::Other, "the writer panicked"))
+                })
+                .map_err(stdin_failed)?;
+        }
+        if let Some(file) = &self.file {
+            let written = match &self.output_mapper {
+                Some(output_mapper) => self.write_file(
+                    file,
+                    output_mapper(
+                        &String::from_utf8_lossy(&output.stdout),
+                        &String::from_utf8_lossy(&output.stderr),
+                        output.status,
+                    )
+                    .as_bytes(),
+                ),
+                None => self.write_file(file, &output.stdout),
+            };
+            written.map_err(|x| {
+                CallError::new(
+                    CallErrorKind::Io,
+                    format!("writing to {} failed {x}", file.display()),
+                )
+            })?;
+        }
+        if (self.pipe_url.is_some() || self.wait_for_exit) && !output.status.success() {
+            return Err(CallError::new(
+                CallErrorKind::Spawn,
+                format!(
+                    "{} failed with {}",
+                    command.get_program().to_string_lossy(),
+                    output.status
+                ),
+            ));
+        }
+        if let Some(url) = &self.pipe_url {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stdout = stdout.trim_end();
+            // a filter that outputs nothing drops the log event
+            if !stdout.is_empty() {
+                if echo {
+                    println!("Calling: `{url}\n\t{stdout}`");
+                }
+                self.call_url(url, stdout, level, "application/json")?;
+            }
+        }
+        Ok(Outcome::Exit(output.status.code()))
+    }
+
+    /// The application and arguments of the call target.  The payload replaces the first argument that is exactly
+    /// `{}`, or is added after the others if there isn't one, unless it is passed on stdin or in an environment variable.
+    #[cfg(all(
+        feature = "process",
+        not(feature = "no-exec"),
+        not(target_arch = "wasm32")
+    ))]
+    fn process_args<'a>(&'a self, params: &'a str) -> Vec<&'a str> {
+        let mut args = Vec::new();
+        let mut placed = false;
+        for arg in self.call_target.split_whitespace() {
+            if arg == "{}" && !placed {
+                self.push_payload(&mut args, params);
+                placed = true;
+            } else {
+                args.push(arg);
+            }
+        }
+        if !placed {
+            self.push_payload(&mut args, params);
+        }
+        args
+    }
+
+    #[cfg(all(
+        feature = "process",
+        not(feature = "no-exec"),
+        not(target_arch = "wasm32")
+    ))]
+    fn push_payload<'a>(&self, args: &mut Vec<&'a str>, params: &'a str) {
+        if self.payload_delivery == PayloadDelivery::Argument {
+            if self.options_terminator {
+                args.push("--");
+            }
+            args.push(params);
+        }
+    }
+
+    /// Applications can only be called with the `process` feature and not from WebAssembly, otherwise only URLs and
+    /// targets can be used
+    #[cfg(any(not(feature = "process"), feature = "no-exec", target_arch = "wasm32"))]
+    fn call_process(
+        &self,
+        _params: &str,
+        _level: Level,
+        _echo: bool,
+    ) -> Result<Outcome, CallError> {
+        #[cfg(feature = "no-exec")]
+        if self.call_target == "echo" {
+            println!("{_params}");
+            return Ok(Outcome::Delivered);
+        }
+        Err(CallError::new(
+            CallErrorKind::Unsupported,
+            "calling an application needs the `process` feature and is not supported on wasm32",
+        ))
+    }
+
+    /// The call target, or `target` when log events are delivered to a [`CallTarget`]
+    pub(crate) fn target_name(&self) -> &str {
+        match self.target {
+            Some(_) => "target",
+            None => &self.call_target,
+        }
+    }
+
+    /// The name that formatters are told this target has, which is the call target unless it has been named
+    pub(crate) fn channel_name(&self) -> &str {
+        self.channel_name.as_deref().unwrap_or(self.target_name())
+    }
+
+    /// Whether the log event is routed to this target, which it isn't if its module is suppressed or it is outside of
+    /// the hours that the target delivers.  The filter of the target is left out, as it is passed the formatted output.
+    pub(crate) fn routes(&self, record: &Record) -> bool {
+        #[cfg(feature = "timestamps")]
+        if !self.is_on_duty() {
+            return false;
+        }
+        !self.suppresses(record)
+    }
+
+    /// Whether the log event is from a module whose log events are not delivered.  The log events of the HTTP client
+    /// are never sent to a URL, as sending them would log more of them.
+    fn suppresses(&self, record: &Record) -> bool {
+        let module = record.module_path().unwrap_or(record.target());
+        self.suppressed_modules
+            .iter()
+            .map(String::as_str)
+            .chain(
+                if self.is_url() {
+                    HTTP_CLIENT_MODULES
+                } else {
+                    &[]
+                }
+                .iter()
+                .copied(),
+            )
+            .any(|pattern| glob_match(pattern, module))
+    }
+
+    /// Whether the call target is a URL rather than an application
+    pub(crate) fn is_url(&self) -> bool {
+        self.call_target.starts_with("http://") || self.call_target.starts_with("https://")
+    }
+
+    /// Reports a log event that could not be delivered, and writes it to the dead-letter file if there is one
+    pub(crate) fn call_failed(&self, params: &str, event: &Event, error: CallError) {
+        // an over-limit fallback reports its failures with the dispatcher that it is the fallback of
+        if let Some(owner) = self.owner.get() {
+            if !std::ptr::eq(Arc::as_ptr(&owner), self) {
+                return owner.call_failed(params, event, error);
+            }
+        }
+        let error = error.with_call(self.target_name(), params);
+        if let Some(error_handler) = &self.error_handler {
+            error_handler(&error);
+        } else if self.target.is_some() {
+            report(format_args!("logging call failed {error}"));
+        } else {
+            report(format_args!(
+                "logging call to {} failed {error}",
+                self.call_target
+            ));
+        }
+        self.write_dead_letter(params, event, &error.to_string());
+    }
+
+    /// There is no dead-letter file without the `files` feature
+    #[cfg(not(feature = "files"))]
+    pub(crate) fn write_dead_letter(&self, _params: &str, _event: &Event, _reason: &str) {}
+
+    /// Makes the HTTP request to a URL, with the HTTP transport if one has been set, returning the status of the
+    /// response if it is known.  The `content_type` is used unless a `Content-Type` header has been set.
+    pub(crate) fn call_url(
+        &self,
+        url: &str,
+        params: &str,
+        level: Level,
+        content_type: &str,
+    ) -> Result<Option<u16>, CallError> {
+        let _permit = self.in_flight.as_ref().map(InFlight::acquire);
+        match &self.http_transport {
+            Some(transport) => {
+                let signature = self.sign(params);
+                let mut headers = self
+                    .http_headers
+                    .iter()
+                    .chain(&signature)
+                    .map(|(header, value)| (header.as_str(), value.as_str()))
+                    .collect::<Vec<(&str, &str)>>();
+                if !headers
+                    .iter()
+                    .any(|(header, _)| header.eq_ignore_ascii_case("Content-Type"))
+                {
+                    headers.push(("Content-Type", content_type));
+                }
+                transport(url, &headers, params)
+                    .map(|_| None)
+                    .map_err(|x| CallError::new(CallErrorKind::Target, x.to_string()))
+            }
+            None => self.call_ureq(url, params, level, content_type),
+        }
+    }
+
+    /// The headers that sign the body of an HTTP request
+    fn sign(&self, body: &str) -> Vec<(String, String)> {
+        self.signer
+            .as_ref()
+            .map(|signer| signer(body))
+            .unwrap_or_default()
+    }
+
+    /// Sends the HTTP request with the built in HTTP client, returning the status of the response
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub(crate) fn send_ureq(
+        &self,
+        url: &str,
+        params: &str,
+        level: Level,
+        content_type: &str,
+    ) -> Result<u16, CallError> {
+        let mut request = match &self.agent {
+            Some(agent) => agent.post(url),
+            None => ureq::post(url),
+        };
+        for (header, value) in self.http_headers.iter().chain(&self.sign(params)) {
+            request = request.set(header, value);
+        }
+        if let Some(customizer) = &self.request_customizer {
+            request = customizer(level, request);
+        }
+        if request.header("Content-Type").is_none() {
+            request = request.set("Content-Type", content_type);
+        }
+        match request.send_string(params) {
+            Ok(response) => Ok(response.status()),
+            Err(ureq::Error::Status(status, _)) => Ok(status),
+            Err(ureq::Error::Transport(x)) => Err(transport_error(&x)),
+        }
+    }
+
+    /// Makes an HTTP request for a target that needs the response, e.g. to look up an issue before opening one, with
+    /// the HTTP client, URL policy and in-flight limit of this dispatcher, returning the body of the response.  The
+    /// closure set with [`CallLoggerBuilder::with_http_transport`] can't be used, as it doesn't return the response.
+    ///
+    /// [`CallLoggerBuilder::with_http_transport`]: crate::CallLoggerBuilder::with_http_transport
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub(crate) fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<&str>,
+    ) -> Result<String, CallError> {
+        if let Err(reason) = self.check_url_policy(url) {
+            return Err(CallError::new(
+                CallErrorKind::Unsupported,
+                format!("the URL `{url}` can't be used, {reason}"),
+            ));
+        }
+        if self.http_transport.is_some() {
+            return Err(CallError::new(
+                CallErrorKind::Unsupported,
+                "this target needs the responses to its requests, which the HTTP transport doesn't return",
+            ));
+        }
+        let _permit = self.in_flight.as_ref().map(InFlight::acquire);
+        let mut request = match &self.agent {
+            Some(agent) => agent.request(method, url),
+            None => ureq::request(method, url),
+        };
+        for (header, value) in headers {
+            request = request.set(header, value);
+        }
+        let response = match body {
+            Some(body) => request.send_string(body),
+            None => request.call(),
+        };
+        match response {
+            Ok(response) => response
+                .into_string()
+                .map_err(|x| CallError::new(CallErrorKind::Io, x.to_string())),
+            Err(ureq::Error::Status(status, _)) => Err(CallError::new(
+                CallErrorKind::Http(status),
+                format!("{url}: status code {status}"),
+            )),
+            Err(ureq::Error::Transport(x)) => Err(transport_error(&x)),
+        }
+    }
+
+    #[cfg(any(not(feature = "http"), target_arch = "wasm32"))]
+    fn call_ureq(
+        &self,
+        _url: &str,
+        _params: &str,
+        _level: Level,
+        _content_type: &str,
+    ) -> Result<Option<u16>, CallError> {
+        Err(CallError::new(
+            CallErrorKind::Unsupported,
+            "there is no HTTP client without the `http` feature or on wasm32, see CallLogger::with_http_transport",
+        ))
+    }
+
+    /// Appends the output of a call to the file, flushing the buffered output if it is due
+    #[cfg(feature = "process")]
+    #[cfg_attr(any(target_arch = "wasm32", feature = "no-exec"), allow(dead_code))]
+    fn write_file(&self, file: &Path, output: &[u8]) -> io::Result<()> {
+        let mut file_writer = self.file_writer.lock().unwrap_or_else(|x| x.into_inner());
+        if file_writer.is_none() {
+            let file = OpenOptions::new().create(true).append(true).open(file)?;
+            *file_writer = Some(FileWriter {
+                writer: BufWriter::new(file),
+                flushed: Instant::now(),
+            });
+        }
+        if let Some(file_writer) = file_writer.as_mut() {
+            file_writer.writer.write_all(output)?;
+            if file_writer.flushed.elapsed() >= self.file_flush_interval {
+                file_writer.writer.flush()?;
+                file_writer.flushed = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    /// Delivers anything that is buffered
+    pub(crate) fn flush(&self) {
+        #[cfg(feature = "process")]
+        {
+            let mut file_writer = self.file_writer.lock().unwrap_or_else(|x| x.into_inner());
+            if let Some(file_writer) = file_writer.as_mut() {
+                if let Err(x) = file_writer.writer.flush() {
+                    report(format_args!(
+                        "logging call to {} failed {x}",
+                        self.call_target
+                    ));
+                }
+                file_writer.flushed = Instant::now();
+            }
+        }
+        self.flush_digest();
+        self.flush_batch();
+        if let Some(target) = &self.target {
+            target.flush();
+        }
+        if let Some(fallback) = &self.over_limit_fallback {
+            fallback.flush();
+        }
+    }
+
+    /// Delivers what has been held for as long as it can be, e.g. a partial batch, and returns how long until this
+    /// should be called again, or `None` if nothing is held for a time
+    pub(crate) fn flush_due(&self) -> Option<Duration> {
+        [
+            self.flush_due_digest(),
+            self.flush_due_batch(),
+            self.target.as_ref().and_then(|target| target.flush_due()),
+            self.over_limit_fallback
+                .as_ref()
+                .and_then(|fallback| fallback.flush_due()),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+}
+
+/// The dispatcher that a target delivers with, which is set once the logger has been built.  The target reports the
+/// log events that it couldn't deliver to it once the call that they were passed to has returned, e.g. those that it
+/// buffers, and makes the HTTP requests that it needs the responses of with it.
+#[derive(Clone, Default)]
+pub(crate) struct DispatcherSlot(Arc<OnceLock<Weak<Dispatcher>>>);
+
+impl DispatcherSlot {
+    pub(crate) fn set(&self, dispatcher: &Arc<Dispatcher>) {
+        let _ = self.0.set(Arc::downgrade(dispatcher));
+    }
+
+    fn get(&self) -> Option<Arc<Dispatcher>> {
+        self.0.get().and_then(Weak::upgrade)
+    }
+
+    /// Makes an HTTP request with the dispatcher, as [`Dispatcher::request`] does
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub(crate) fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<&str>,
+    ) -> Result<String, CallError> {
+        match self.get() {
+            Some(dispatcher) => dispatcher.request(method, url, headers, body),
+            None => Err(CallError::new(
+                CallErrorKind::Unsupported,
+                "requests can only be made once the logger has been built",
+            )),
+        }
+    }
+
+    /// Reports a log event that couldn't be delivered, and writes it to the dead-letter file, as
+    /// [`Dispatcher::call_failed`] does
+    #[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+    pub(crate) fn call_failed(&self, params: &str, event: &Event, error: CallError) {
+        match self.get() {
+            Some(dispatcher) => dispatcher.call_failed(params, event, error),
+            None => report(format_args!("logging call failed {error}")),
+        }
+    }
+}
+
+/// The error for a request that got no response, which is a timeout if the I/O error underneath it timed out
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+fn transport_error(x: &ureq::Transport) -> CallError {
+    let timed_out = std::iter::successors(std::error::Error::source(x), |source| source.source())
+        .filter_map(|source| source.downcast_ref::<std::io::Error>())
+        .any(|error| {
+            matches!(
+                error.kind(),
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+            )
+        });
+    let kind = if timed_out {
+        CallErrorKind::Timeout
+    } else {
+        CallErrorKind::Io
+    };
+    CallError::new(kind, x.to_string())
+}
+
+/// The shortest time that the flush thread sleeps for, so that it doesn't spin on a delay of zero
+#[cfg(not(target_arch = "wasm32"))]
+const MIN_FLUSH_WAIT: Duration = Duration::from_millis(10);
+
+/// Starts a thread that delivers what the dispatchers have held for as long as they can, so that e.g. a partial batch
+/// is sent after its delay even if no more log events are logged.  The thread stops once the logger has been dropped.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn start_flusher<'a>(dispatchers: impl Iterator<Item = &'a Arc<Dispatcher>>) {
+    let dispatchers = dispatchers
+        .filter(|dispatcher| dispatcher.flush_due().is_some())
+        .map(Arc::downgrade)
+        .collect::<Vec<_>>();
+    if dispatchers.is_empty() {
+        return;
+    }
+    thread::spawn(move || loop {
+        let mut wait = None::<Duration>;
+        for dispatcher in &dispatchers {
+            let Some(dispatcher) = dispatcher.upgrade() else {
+                return;
+            };
+            if let Some(due) = dispatcher.flush_due() {
+                wait = Some(wait.map_or(due, |wait| wait.min(due)));
+            }
+        }
+        thread::sleep(wait.unwrap_or(MIN_FLUSH_WAIT).max(MIN_FLUSH_WAIT));
+    });
+}
+
+/// How the formatted output of a log event is passed to an application
+#[cfg(feature = "process")]
+#[derive(PartialEq, Debug)]
+pub(crate) enum PayloadDelivery {
+    /// As an argument, in place of `{}` or after the other arguments
+    Argument,
+    /// On the application's stdin
+    Stdin,
+    /// In an environment variable of the application with this name
+    Env(String),
+}
+
+/// The file that the output of calls is written to
+#[cfg(feature = "process")]
+struct FileWriter {
+    writer: BufWriter<File>,
+    /// When the buffered output was last written to the file
+    flushed: Instant,
+}
+
+/// The work for a background delivery thread
+pub(crate) enum Job {
+    /// Deliver the formatted output of a log event with the dispatcher, and signal `done` afterwards
+    Deliver {
+        dispatcher: Arc<Dispatcher>,
+        params: String,
+        event: Event,
+        done: Option<Sender<()>>,
+    },
+    /// Signal that all of the jobs sent before this one are done
+    Flush(Sender<()>),
+}
+
+/// The threads that deliver log events in the background
+pub(crate) struct Workers {
+    senders: Vec<Mutex<Sender<Job>>>,
+    next: AtomicUsize,
+}
+
+impl Workers {
+    pub(crate) fn start(count: usize) -> Workers {
+        let senders = (0..count.max(1))
+            .map(|_| {
+                let (sender, receiver) = channel();
+                thread::spawn(move || work(receiver));
+                Mutex::new(sender)
+            })
+            .collect();
+        Workers {
+            senders,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Queues a job for a background thread.  Jobs with the same `order_key` are queued for the same thread so that
+    /// they are done in order, other jobs are shared between the threads in turn.
+    pub(crate) fn send(&self, job: Job, order_key: Option<&str>) {
+        let index = match order_key {
+            Some(order_key) => {
+                let mut hasher = DefaultHasher::new();
+                order_key.hash(&mut hasher);
+                hasher.finish() as usize
+            }
+            None => self.next.fetch_add(1, Ordering::Relaxed),
+        } % self.senders.len();
+        let sender = self.senders[index]
+            .lock()
+            .unwrap_or_else(|x| x.into_inner());
+        if sender.send(job).is_err() {
+            report(format_args!(
+                "logging call failed, the background delivery thread has stopped"
+            ));
+        }
+    }
+
+    /// Waits for the jobs that have been queued to be done
+    pub(crate) fn flush(&self) {
+        let waiting = self
+            .senders
+            .iter()
+            .filter_map(|sender| {
+                let (done, flushed) = channel();
+                let sender = sender.lock().unwrap_or_else(|x| x.into_inner());
+                sender.send(Job::Flush(done)).ok().map(|_| flushed)
+            })
+            .collect::<Vec<Receiver<()>>>();
+        for flushed in waiting {
+            let _ = flushed.recv();
+        }
+    }
+}
+
+fn work(receiver: Receiver<Job>) {
+    for job in receiver {
+        match job {
+            Job::Deliver {
+                dispatcher,
+                params,
+                event,
+                done,
+            } => {
+                // a log event that waited in the queue for too long is dropped
+                if !dispatcher.expires(event.logged, Duration::ZERO) {
+                    if let Err(x) = event.with_record(|record| dispatcher.deliver(&params, record))
+                    {
+                        dispatcher.call_failed(&params, &event, x);
+                    }
+                }
+                if let Some(done) = done {
+                    let _ = done.send(());
+                }
+            }
+            Job::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
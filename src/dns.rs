@@ -0,0 +1,140 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::{SocketAddr, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{internal::report, CallLoggerBuilder};
+
+impl CallLoggerBuilder {
+    /// Caches the addresses that the hostnames of URL call targets resolve to for `ttl`, so that log events don't each
+    /// wait for a DNS lookup or fail when the resolver is flaky.  The hostname of each URL call target is resolved when
+    /// the logger is built, and again when a request is sent after the `ttl` has passed.  If a lookup fails, the
+    /// addresses from the last lookup are used.  The connection is made to the address with the hostname still used
+    /// for TLS, so certificates are checked as usual.  This is not used when a closure is set with
+    /// [`CallLoggerBuilder::with_http_transport`].
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// # use std::time::Duration;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_dns_ttl(Duration::from_secs(300))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_dns_ttl(mut self, ttl: Duration) -> CallLoggerBuilder {
+        self.dispatcher.dns = Some(DnsCache::new(ttl));
+        self
+    }
+
+    /// Connects to the addresses of `family` first when the hostname of a URL call target resolves to both IPv4 and
+    /// IPv6 addresses, e.g. on a network where IPv6 is advertised but not routed.  The other addresses are tried if
+    /// none of them can be connected to.  This is not used when a closure is set with
+    /// [`CallLoggerBuilder::with_http_transport`].
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::{CallLogger, IpFamily};
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .prefer_ip(IpFamily::V4)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn prefer_ip(mut self, family: IpFamily) -> CallLoggerBuilder {
+        self.dispatcher.prefer_ip = Some(family);
+        self
+    }
+}
+
+/// A family of IP addresses, set with [`CallLoggerBuilder::prefer_ip`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpFamily {
+    /// IPv4 addresses
+    V4,
+    /// IPv6 addresses
+    V6,
+}
+
+/// An HTTP client that resolves hostnames with the cache if there is one, with the addresses of the preferred family
+/// first
+pub(crate) fn agent(cache: Option<Arc<DnsCache>>, prefer: Option<IpFamily>) -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .resolver(move |netloc: &str| {
+            let mut addresses = match &cache {
+                Some(cache) => cache.resolve(netloc)?,
+                None => netloc.to_socket_addrs()?.collect(),
+            };
+            if let Some(family) = prefer {
+                addresses.sort_by_key(|address| address.is_ipv4() != (family == IpFamily::V4));
+            }
+            Ok(addresses)
+        })
+        .build()
+}
+
+/// The addresses that hostnames resolved to, with the HTTP client that connects to them
+pub(crate) struct DnsCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Vec<SocketAddr>)>>,
+}
+
+impl DnsCache {
+    pub(crate) fn new(ttl: Duration) -> Arc<DnsCache> {
+        Arc::new(DnsCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Resolves the hostname of a URL so that the first request doesn't wait for it
+    pub(crate) fn prefetch(&self, url: &str) {
+        if let Some(netloc) = netloc(url) {
+            if let Err(x) = self.resolve(&netloc) {
+                report(format_args!("could not resolve {netloc}: {x}"));
+            }
+        }
+    }
+
+    /// The addresses of a `host:port`, from the cache unless they are older than the TTL
+    fn resolve(&self, netloc: &str) -> io::Result<Vec<SocketAddr>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|x| x.into_inner());
+        let stale = match entries.get(netloc) {
+            Some((resolved, addresses)) if resolved.elapsed() < self.ttl => {
+                return Ok(addresses.clone())
+            }
+            Some((_, addresses)) => Some(addresses.clone()),
+            None => None,
+        };
+        match netloc.to_socket_addrs() {
+            Ok(addresses) => {
+                let addresses = addresses.collect::<Vec<SocketAddr>>();
+                entries.insert(netloc.to_string(), (Instant::now(), addresses.clone()));
+                Ok(addresses)
+            }
+            Err(x) => stale.ok_or(x),
+        }
+    }
+}
+
+/// The `host:port` of a URL, with the default port of the scheme if it has none
+fn netloc(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit('@').next()?;
+    let has_port = match host_port.rfind(']') {
+        Some(end) => host_port[end..].contains(':'),
+        None => host_port.contains(':'),
+    };
+    match (has_port, scheme) {
+        (true, _) => Some(host_port.to_string()),
+        (false, "https") => Some(format!("{host_port}:443")),
+        (false, _) => Some(format!("{host_port}:80")),
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use log::Level;
+
+use crate::{CallError, CallErrorKind, CallLoggerBuilder};
+
+impl CallLoggerBuilder {
+    /// Writes only every `n`th call to console, as [`CallLoggerBuilder::echo`] does for every call, so that a burst of
+    /// log events doesn't flood the terminal while debugging.  The first call is always written.
+    ///
+    /// Example - Write one call in every hundred
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .echo_sampled(100)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn echo_sampled(mut self, n: usize) -> CallLoggerBuilder {
+        self.dispatcher.echo.get_or_insert_with(Echo::default).every = n.max(1);
+        self
+    }
+
+    /// Writes only the calls for error log events to console, as [`CallLoggerBuilder::echo`] does for every call.  This
+    /// can be combined with [`CallLoggerBuilder::echo_sampled`] to write every `n`th error.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .echo_errors_only()
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn echo_errors_only(mut self) -> CallLoggerBuilder {
+        self.dispatcher
+            .echo
+            .get_or_insert_with(Echo::default)
+            .errors_only = true;
+        self
+    }
+}
+
+/// Which calls are written to console for debugging
+#[derive(Debug)]
+pub(crate) struct Echo {
+    /// Every `every`th call is written
+    pub(crate) every: usize,
+
+    /// Only the calls for error log events are written
+    pub(crate) errors_only: bool,
+
+    /// The number of calls that could have been written so far
+    calls: AtomicUsize,
+}
+
+impl Default for Echo {
+    fn default() -> Self {
+        Echo {
+            every: 1,
+            errors_only: false,
+            calls: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Echo {
+    /// Whether the call for a log event of this level is written
+    pub(crate) fn sampled(&self, level: Level) -> bool {
+        if self.errors_only && level != Level::Error {
+            return false;
+        }
+        self.calls.fetch_add(1, Ordering::Relaxed) % self.every == 0
+    }
+}
+
+/// What happened to a log event that was delivered, which is written to console after an echoed call
+pub(crate) enum Outcome {
+    /// The target or the HTTP transport was called
+    Delivered,
+    /// The URL responded with this status
+    Status(u16),
+    /// The application exited with this code, or `None` if it was killed by a signal
+    #[cfg(all(
+        feature = "process",
+        not(feature = "no-exec"),
+        not(target_arch = "wasm32")
+    ))]
+    Exit(Option<i32>),
+    /// The application was started and not waited for
+    #[cfg(all(
+        feature = "process",
+        not(feature = "no-exec"),
+        not(target_arch = "wasm32")
+    ))]
+    Started,
+    /// The log event was added to the batch that is sent later
+    Batched,
+    /// The log event was dropped, e.g. because it came from the HTTP client
+    Dropped,
+}
+
+/// The line that is written after an echoed call, with the size of the payload, how long the call took and its outcome
+pub(crate) fn outcome_line(
+    params: &str,
+    elapsed: Duration,
+    outcome: &Result<Outcome, CallError>,
+) -> String {
+    let outcome = match outcome {
+        Ok(Outcome::Delivered) => "delivered".to_string(),
+        Ok(Outcome::Status(status)) => format!("HTTP status {status}"),
+        #[cfg(all(
+            feature = "process",
+            not(feature = "no-exec"),
+            not(target_arch = "wasm32")
+        ))]
+        Ok(Outcome::Exit(Some(code))) => format!("exit code {code}"),
+        #[cfg(all(
+            feature = "process",
+            not(feature = "no-exec"),
+            not(target_arch = "wasm32")
+        ))]
+        Ok(Outcome::Exit(None)) => "killed by a signal".to_string(),
+        #[cfg(all(
+            feature = "process",
+            not(feature = "no-exec"),
+            not(target_arch = "wasm32")
+        ))]
+        Ok(Outcome::Started) => "started".to_string(),
+        Ok(Outcome::Batched) => "batched".to_string(),
+        Ok(Outcome::Dropped) => "dropped".to_string(),
+        Err(x) => match x.kind {
+            CallErrorKind::Http(status) => format!("failed with HTTP status {status}"),
+            _ => format!("failed: {}", x.message),
+        },
+    };
+    format!(
+        "Called: {} bytes in {}, {outcome}",
+        params.len(),
+        format_duration(elapsed)
+    )
+}
+
+/// A duration in the unit that suits it, e.g. `850us`, `12.3ms` or `1.50s`
+fn format_duration(elapsed: Duration) -> String {
+    let micros = elapsed.as_micros();
+    if micros < 1000 {
+        format!("{micros}us")
+    } else if micros < 1_000_000 {
+        format!("{:.1}ms", micros as f64 / 1000.0)
+    } else {
+        format!("{:.2}s", elapsed.as_secs_f64())
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt::Write;
+
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305},
+    agreement::{self, EphemeralPrivateKey, UnparsedPublicKey, X25519},
+    error::Unspecified,
+    hkdf::{KeyType, Salt, HKDF_SHA256},
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+
+use crate::{base64, internal::report, CallLoggerBuilder};
+
+/// The first line of the header of an age file
+const AGE_VERSION: &str = "age-encryption.org/v1";
+
+/// The number of bytes of the payload that are encrypted in each chunk
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The characters of bech32, indexed by their value
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+impl CallLoggerBuilder {
+    /// Encrypts the formatted output of each log event to an [age][age] recipient before it is delivered, so that only
+    /// the holder of the matching identity can read it, e.g. when a webhook relay is run by a third party.  The
+    /// `recipient` is an X25519 public key, e.g. from `age-keygen`, that starts with `age1`.  Each payload is a
+    /// separate ASCII-armored age file that can be decrypted with `age --decrypt`.  The payload is encrypted after
+    /// the middleware, so it is what is written to the dead-letter file, also for the log events that are held while
+    /// the logger is paused or silenced, and the message of the log event is left out of the file.  A target that
+    /// reads the payload, e.g. to filter or route it, only sees the encrypted payload.  The logger can't be built if
+    /// the recipient isn't valid.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .encrypt_payload_with("age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p")
+    ///     .init();
+    /// ```
+    ///
+    /// [age]: https://age-encryption.org/v1
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn encrypt_payload_with<T>(mut self, recipient: T) -> CallLoggerBuilder
+    where
+        T: AsRef<str>,
+    {
+        self.encryption = Some(Recipient::parse(recipient.as_ref()));
+        self
+    }
+}
+
+/// The X25519 public key of an age recipient
+pub(crate) struct Recipient {
+    public_key: [u8; 32],
+    rng: SystemRandom,
+}
+
+impl Recipient {
+    /// Parses a recipient from its bech32 encoding, or the reason that it can't be
+    pub(crate) fn parse(recipient: &str) -> Result<Recipient, String> {
+        let (hrp, data) = bech32_decode(recipient)
+            .ok_or_else(|| format!("`{recipient}` is not a valid bech32 string"))?;
+        if hrp != "age" {
+            return Err(format!("`{recipient}` is not an age X25519 recipient"));
+        }
+        let public_key = data
+            .try_into()
+            .map_err(|_| format!("`{recipient}` is not a 32 byte X25519 public key"))?;
+        Ok(Recipient {
+            public_key,
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Encrypts the payload as an ASCII-armored age file, or returns `None` if it couldn't be encrypted
+    pub(crate) fn encrypt(&self, payload: &str) -> Option<String> {
+        match self.encrypt_armored(payload.as_bytes()) {
+            Ok(armored) => Some(armored),
+            Err(_) => {
+                report(format_args!(
+                    "logging payload could not be encrypted, it was dropped"
+                ));
+                None
+            }
+        }
+    }
+
+    fn encrypt_armored(&self, plaintext: &[u8]) -> Result<String, Unspecified> {
+        let mut file_key = [0u8; 16];
+        self.rng.fill(&mut file_key)?;
+
+        // wrap the file key with the secret shared with the recipient
+        let ephemeral = EphemeralPrivateKey::generate(&X25519, &self.rng)?;
+        let mut share = [0u8; 32];
+        share.copy_from_slice(ephemeral.compute_public_key()?.as_ref());
+        let mut salt = share.to_vec();
+        salt.extend_from_slice(&self.public_key);
+        let wrap_key = agreement::agree_ephemeral(
+            ephemeral,
+            &UnparsedPublicKey::new(&X25519, &self.public_key),
+            |shared| hkdf_sha256(shared, &salt, b"age-encryption.org/v1/X25519"),
+        )??;
+        let mut body = file_key.to_vec();
+        seal(&wrap_key, [0; 12], &mut body)?;
+
+        let mut header = format!(
+            "{AGE_VERSION}\n-> X25519 {}\n{}\n---",
+            base64_unpadded(&share),
+            base64_unpadded(&body)
+        );
+        let mac_key = hkdf_sha256(&file_key, &[], b"header")?;
+        let mac = hmac::sign(
+            &hmac::Key::new(hmac::HMAC_SHA256, &mac_key),
+            header.as_bytes(),
+        );
+        // writing to a String cannot fail
+        let _ = writeln!(header, " {}", base64_unpadded(mac.as_ref()));
+
+        // the payload is encrypted in chunks with a key derived from the file key and a nonce
+        let mut file = header.into_bytes();
+        let mut nonce = [0u8; 16];
+        self.rng.fill(&mut nonce)?;
+        file.extend_from_slice(&nonce);
+        let payload_key = hkdf_sha256(&file_key, &nonce, b"payload")?;
+        let chunks = match plaintext.is_empty() {
+            true => vec![plaintext],
+            false => plaintext.chunks(CHUNK_SIZE).collect(),
+        };
+        for (counter, chunk) in chunks.iter().enumerate() {
+            let mut chunk_nonce = [0u8; 12];
+            chunk_nonce[3..11].copy_from_slice(&(counter as u64).to_be_bytes());
+            chunk_nonce[11] = u8::from(counter == chunks.len() - 1);
+            let mut sealed = chunk.to_vec();
+            seal(&payload_key, chunk_nonce, &mut sealed)?;
+            file.extend_from_slice(&sealed);
+        }
+        Ok(armor(&file))
+    }
+}
+
+/// Encrypts the data in place with ChaCha20-Poly1305 and appends the tag
+fn seal(key: &[u8; 32], nonce: [u8; 12], data: &mut Vec<u8>) -> Result<(), Unspecified> {
+    let key = LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key)?);
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), data)
+}
+
+/// Derives a 32 byte key with HKDF-SHA256
+fn hkdf_sha256(secret: &[u8], salt: &[u8], info: &[u8]) -> Result<[u8; 32], Unspecified> {
+    struct Len32;
+    impl KeyType for Len32 {
+        fn len(&self) -> usize {
+            32
+        }
+    }
+    let mut key = [0u8; 32];
+    Salt::new(HKDF_SHA256, salt)
+        .extract(secret)
+        .expand(&[info], Len32)?
+        .fill(&mut key)?;
+    Ok(key)
+}
+
+/// Wraps a file in the ASCII armor of age, which is base64 in lines of 64 characters
+fn armor(file: &[u8]) -> String {
+    let encoded = base64(file);
+    let mut armored = String::with_capacity(encoded.len() + encoded.len() / 64 + 70);
+    armored.push_str("-----BEGIN AGE ENCRYPTED FILE-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        // base64 is ASCII
+        armored.push_str(std::str::from_utf8(line).unwrap_or_default());
+        armored.push('\n');
+    }
+    armored.push_str("-----END AGE ENCRYPTED FILE-----\n");
+    armored
+}
+
+/// Encodes bytes as standard base64 without padding, as it is in the header of an age file
+fn base64_unpadded(bytes: &[u8]) -> String {
+    base64(bytes).trim_end_matches('=').to_string()
+}
+
+/// Decodes a bech32 string into its human readable part and data, checking its checksum
+pub(crate) fn bech32_decode(encoded: &str) -> Option<(String, Vec<u8>)> {
+    if encoded.chars().any(|c| c.is_ascii_uppercase())
+        && encoded.chars().any(|c| c.is_ascii_lowercase())
+    {
+        return None;
+    }
+    let encoded = encoded.to_ascii_lowercase();
+    let (hrp, data) = encoded.rsplit_once('1')?;
+    if hrp.is_empty() || data.len() < 6 || !hrp.bytes().all(|b| (33..=126).contains(&b)) {
+        return None;
+    }
+    let values = data
+        .bytes()
+        .map(|b| BECH32_CHARSET.iter().position(|c| *c == b).map(|x| x as u8))
+        .collect::<Option<Vec<u8>>>()?;
+    if bech32_polymod(hrp, &values) != 1 {
+        return None;
+    }
+    // the 5 bit groups without the checksum are regrouped into bytes, and any padding bits must be zero
+    let mut bytes = Vec::new();
+    let (mut accumulator, mut bits) = (0u32, 0);
+    for value in &values[..values.len() - 6] {
+        accumulator = ((accumulator << 5) | u32::from(*value)) & 0x1fff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((accumulator >> bits) as u8);
+        }
+    }
+    if bits >= 5 || accumulator & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some((hrp.to_string(), bytes))
+}
+
+/// The bech32 checksum of the human readable part and the 5 bit values
+pub(crate) fn bech32_polymod(hrp: &str, values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let expanded = hrp
+        .bytes()
+        .map(|b| b >> 5)
+        .chain([0])
+        .chain(hrp.bytes().map(|b| b & 31))
+        .chain(values.iter().copied());
+    let mut checksum = 1u32;
+    for value in expanded {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ u32::from(value);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
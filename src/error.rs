@@ -0,0 +1,218 @@
+use std::{error::Error, fmt};
+
+use log::SetLoggerError;
+
+#[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+use std::path::PathBuf;
+
+/// The reason that the configuration of a [`CallLoggerBuilder`](crate::CallLoggerBuilder) can't be built into a
+/// logger.
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The call target is empty
+    EmptyTarget,
+    /// The call target looks like a URL but isn't a valid one
+    InvalidUrl(String),
+    /// The host or port of a URL isn't valid, e.g. an IPv6 address that isn't in square brackets, with the reason
+    InvalidHost(String, &'static str),
+    /// The executable set with [`CallLoggerBuilder::with_call_target_path`](crate::CallLoggerBuilder::with_call_target_path)
+    /// can't be run, with the reason
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    NotExecutable(PathBuf, &'static str),
+    /// The application of the call target isn't one of those allowed by
+    /// [`CallLoggerBuilder::restrict_targets_to`](crate::CallLoggerBuilder::restrict_targets_to)
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    TargetNotAllowed(String),
+    /// The call target is an application, which can't be called when the `no-exec` feature is enabled
+    #[cfg(feature = "no-exec")]
+    ExecDisabled(String),
+    /// The URL isn't allowed by
+    /// [`CallLoggerBuilder::allow_schemes`](crate::CallLoggerBuilder::allow_schemes) or
+    /// [`CallLoggerBuilder::allow_hosts`](crate::CallLoggerBuilder::allow_hosts), with the reason
+    UrlNotAllowed(String, &'static str),
+    /// The recipient set with
+    /// [`CallLoggerBuilder::encrypt_payload_with`](crate::CallLoggerBuilder::encrypt_payload_with) is not a valid age
+    /// recipient, with the reason
+    #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+    InvalidRecipient(String),
+    /// The hours set with [`CallLoggerBuilder::quiet_hours`](crate::CallLoggerBuilder::quiet_hours) are not valid,
+    /// with the reason
+    #[cfg(feature = "timestamps")]
+    InvalidQuietHours(String),
+    /// Options have been set that can't be used together or with the call target
+    Conflict(&'static str),
+    /// The address set with [`CallLoggerBuilder::serve_sse`](crate::CallLoggerBuilder::serve_sse) can't be listened
+    /// on, with the reason
+    CannotListen(String),
+    /// The schema set with
+    /// [`CallLoggerBuilder::validate_against_schema`](crate::CallLoggerBuilder::validate_against_schema) is not
+    /// valid JSON, with the reason
+    #[cfg(feature = "schema")]
+    InvalidSchema(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::EmptyTarget => write!(f, "the call target is empty"),
+            ConfigError::InvalidUrl(url) => write!(f, "the call target `{url}` is not a valid URL"),
+            ConfigError::InvalidHost(url, reason) => {
+                write!(f, "the host of `{url}` is not valid, {reason}")
+            }
+            #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+            ConfigError::NotExecutable(path, reason) => {
+                write!(
+                    f,
+                    "the call target `{}` can't be run, {reason}",
+                    path.display()
+                )
+            }
+            #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+            ConfigError::TargetNotAllowed(program) => {
+                write!(f, "the call target `{program}` is not one of those allowed")
+            }
+            #[cfg(feature = "no-exec")]
+            ConfigError::ExecDisabled(call_target) => write!(
+                f,
+                "the call target `{call_target}` is not a URL, and applications can't be called with the `no-exec` feature"
+            ),
+            ConfigError::UrlNotAllowed(url, reason) => {
+                write!(f, "the URL `{url}` can't be used, {reason}")
+            }
+            #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+            ConfigError::InvalidRecipient(reason) => write!(f, "{reason}"),
+            #[cfg(feature = "timestamps")]
+            ConfigError::InvalidQuietHours(reason) => write!(f, "{reason}"),
+            ConfigError::Conflict(reason) => write!(f, "{reason}"),
+            ConfigError::CannotListen(reason) => {
+                write!(f, "the address can't be listened on, {reason}")
+            }
+            #[cfg(feature = "schema")]
+            ConfigError::InvalidSchema(reason) => {
+                write!(f, "the schema is not valid JSON, {reason}")
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// The reason that a [`CallLoggerBuilder`](crate::CallLoggerBuilder) could not be set up as the logger.
+#[derive(Debug)]
+pub enum InitError {
+    /// The configuration is not valid
+    Config(ConfigError),
+    /// A logger has already been set up for the `log` crate
+    SetLogger(SetLoggerError),
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::Config(x) => write!(f, "the logger is misconfigured, {x}"),
+            InitError::SetLogger(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+impl Error for InitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InitError::Config(x) => Some(x),
+            InitError::SetLogger(x) => Some(x),
+        }
+    }
+}
+
+impl From<ConfigError> for InitError {
+    fn from(x: ConfigError) -> Self {
+        InitError::Config(x)
+    }
+}
+
+impl From<SetLoggerError> for InitError {
+    fn from(x: SetLoggerError) -> Self {
+        InitError::SetLogger(x)
+    }
+}
+
+/// The number of characters of the payload that are kept in a [`CallError`]
+const PAYLOAD_SNIPPET_LENGTH: usize = 200;
+
+/// The reason that a log event could not be delivered, passed to the closure set with
+/// [`CallLoggerBuilder::on_call_error`](crate::CallLoggerBuilder::on_call_error).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CallError {
+    /// The call target, or `target` when the log event was delivered to a target set with a closure or a
+    /// [`CallTarget`](crate::CallTarget)
+    pub target: String,
+    /// The kind of failure
+    pub kind: CallErrorKind,
+    /// The description of the failure
+    pub message: String,
+    /// The start of the formatted output of the log event
+    pub payload_snippet: String,
+    /// The number of times that the delivery was attempted
+    pub attempts: u32,
+}
+
+/// The kind of failure of a [`CallError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CallErrorKind {
+    /// The URL responded with an HTTP status that isn't a success
+    Http(u16),
+    /// Reading or writing failed, e.g. the connection to the URL or the file that the output is written to
+    Io,
+    /// The call timed out
+    Timeout,
+    /// The application could not be started, or it failed
+    Spawn,
+    /// A target set with a closure, a [`CallTarget`](crate::CallTarget) or an HTTP transport returned an error
+    Target,
+    /// The call target can't be used in this build, e.g. an application without the `process` feature
+    Unsupported,
+    /// The log event is over the rate limit or daily budget of the target, and there is no fallback, see
+    /// [`CallLoggerBuilder::with_rate_limit`](crate::CallLoggerBuilder::with_rate_limit)
+    OverLimit,
+    /// The formatted output doesn't match the schema set with
+    /// [`CallLoggerBuilder::validate_against_schema`](crate::CallLoggerBuilder::validate_against_schema)
+    #[cfg(feature = "schema")]
+    Schema,
+}
+
+impl CallError {
+    /// Creates an error of the call, the target and payload are added when it is reported
+    pub(crate) fn new<M>(kind: CallErrorKind, message: M) -> CallError
+    where
+        M: Into<String>,
+    {
+        CallError {
+            target: String::new(),
+            kind,
+            message: message.into(),
+            payload_snippet: String::new(),
+            attempts: 1,
+        }
+    }
+
+    /// Adds the call target and the start of the payload to the error
+    pub(crate) fn with_call<T>(mut self, target: T, payload: &str) -> CallError
+    where
+        T: Into<String>,
+    {
+        self.target = target.into();
+        self.payload_snippet = payload.chars().take(PAYLOAD_SNIPPET_LENGTH).collect();
+        self
+    }
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for CallError {}
@@ -0,0 +1,86 @@
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+use crate::{dispatch::Dispatcher, CallLoggerBuilder};
+
+thread_local! {
+    /// When the log event that is being delivered on this thread was logged, if it was rebuilt from an [`Event`]
+    ///
+    /// [`Event`]: crate::Event
+    static LOGGED_AT: Cell<Option<SystemTime>> = const { Cell::new(None) };
+}
+
+impl CallLoggerBuilder {
+    /// Drops the log events that are older than `ttl` instead of delivering them, e.g. so that an alert about a
+    /// problem that was fixed hours ago isn't sent when a webhook comes back after an outage.  The age of a log event
+    /// is checked when a background delivery thread takes it from its queue, before each retry of a request, before a
+    /// batch is sent, and when it is replayed from the dead-letter file or the audit spool, where it is as old as
+    /// the line in the file.  Dropped log events are not reported as failures or written to the dead-letter file,
+    /// they are counted by [`CallLoggerHandle::expired_count`](crate::CallLoggerHandle::expired_count).
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// # use std::time::Duration;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_workers(2)
+    ///     .with_event_ttl(Duration::from_secs(10 * 60))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_event_ttl(mut self, ttl: Duration) -> CallLoggerBuilder {
+        self.dispatcher.event_ttl = Some(EventTtl {
+            ttl,
+            expired: Arc::default(),
+        });
+        self
+    }
+}
+
+/// How old a log event can be when it is delivered, and the number of log events that were older, which is shared by
+/// the targets and the handles of the logger
+#[derive(Clone)]
+pub(crate) struct EventTtl {
+    ttl: Duration,
+    pub(crate) expired: Arc<AtomicU64>,
+}
+
+impl Dispatcher {
+    /// Whether a log event that was logged at `logged` is older than the TTL, or will be after waiting for `delay`, in
+    /// which case it is counted as expired
+    pub(crate) fn expires(&self, logged: SystemTime, delay: Duration) -> bool {
+        let Some(event_ttl) = &self.event_ttl else {
+            return false;
+        };
+        // a clock that has gone backwards makes the log event new rather than dropping it
+        let age = logged.elapsed().unwrap_or_default() + delay;
+        let expired = age > event_ttl.ttl;
+        if expired {
+            event_ttl.expired.fetch_add(1, Ordering::Relaxed);
+        }
+        expired
+    }
+}
+
+/// When the log event being delivered on this thread was logged, which is now unless it was rebuilt from an event
+pub(crate) fn logged_at() -> SystemTime {
+    LOGGED_AT
+        .with(|logged_at| logged_at.get())
+        .unwrap_or_else(SystemTime::now)
+}
+
+/// Calls the closure with `logged` as the time that the log event being delivered on this thread was logged
+pub(crate) fn with_logged_at<R>(logged: SystemTime, f: impl FnOnce() -> R) -> R {
+    let previous = LOGGED_AT.with(|logged_at| logged_at.replace(Some(logged)));
+    let result = f();
+    LOGGED_AT.with(|logged_at| logged_at.set(previous));
+    result
+}
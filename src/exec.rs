@@ -0,0 +1,115 @@
+use std::{borrow::Cow, fs, path::Path, path::PathBuf};
+
+use crate::{dispatch::Dispatcher, CallLoggerBuilder, ConfigError};
+
+impl CallLoggerBuilder {
+    /// Runs exactly the executable at `path` for each log event, without looking it up in `PATH` or splitting it into
+    /// arguments, so that a different program with the same name can't be run instead, e.g. another `echo` earlier in
+    /// `PATH`.  The formatted output of the log event is passed as the only argument, or on stdin with
+    /// [`CallLoggerBuilder::payload_via_stdin`](crate::CallLoggerBuilder::payload_via_stdin).  The logger can't be
+    /// built if the file doesn't exist or, on Unix, isn't executable.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target_path("/bin/echo")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_call_target_path<P>(mut self, path: P) -> CallLoggerBuilder
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        self.dispatcher.call_target = path.display().to_string();
+        self.dispatcher.exec_path = Some(path);
+        self
+    }
+
+    /// Only allows the applications in `programs` to be called, so that a call target that comes from an environment
+    /// variable or a configuration file can't be used to run any other command.  The application of the call target,
+    /// which is its first word or the path set with [`CallLoggerBuilder::with_call_target_path`], must be exactly one
+    /// of them, e.g. `echo` doesn't allow `/bin/echo`.  This is checked when the logger is built and again before each
+    /// call, and applies to the targets added with [`CallLoggerBuilder::with_target`] as well.  URL call targets are
+    /// not affected, and the shell can't be used as it could run anything.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// let call_target = std::env::var("LOG_CALL_TARGET").unwrap_or("echo".to_string());
+    /// CallLogger::new()
+    ///     .with_call_target(call_target)
+    ///     .restrict_targets_to(["/usr/local/bin/logship", "echo"])
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn restrict_targets_to<I, T>(mut self, programs: I) -> CallLoggerBuilder
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.dispatcher.allowed_programs = Some(programs.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+impl Dispatcher {
+    /// The application that the call target runs
+    fn program(&self) -> Cow<'_, str> {
+        match &self.exec_path {
+            Some(path) => path.to_string_lossy(),
+            None => Cow::Borrowed(
+                self.call_target
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+
+    /// Checks that the application of the call target is allowed by [`CallLoggerBuilder::restrict_targets_to`]
+    pub(crate) fn validate_program(&self) -> Result<(), ConfigError> {
+        if self.allowed_programs.is_none() {
+            return Ok(());
+        }
+        if self.via_shell {
+            return Err(ConfigError::Conflict(
+                "the shell can't be used when the call targets are restricted",
+            ));
+        }
+        if !self.allows_program() {
+            return Err(ConfigError::TargetNotAllowed(self.program().into_owned()));
+        }
+        Ok(())
+    }
+
+    /// Whether the application of the call target is allowed to be run
+    pub(crate) fn allows_program(&self) -> bool {
+        self.allowed_programs.as_ref().map_or(true, |allowed| {
+            let program = self.program();
+            !self.via_shell && allowed.iter().any(|x| *x == program)
+        })
+    }
+}
+
+/// Checks that the file at `path` exists and can be run
+pub(crate) fn validate_executable(path: &Path) -> Result<(), ConfigError> {
+    let not_executable = |reason| Err(ConfigError::NotExecutable(path.to_path_buf(), reason));
+    let Ok(metadata) = fs::metadata(path) else {
+        return not_executable("it doesn't exist");
+    };
+    if !metadata.is_file() {
+        return not_executable("it isn't a file");
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return not_executable("it isn't executable");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::borrow::Cow;
+
+use crate::{push_json_escaped, CallLoggerBuilder, FormatContext};
+
+/// The keys that the default formatter writes itself, which the fields of a log event can collide with
+pub(crate) const RESERVED_KEYS: [&str; 11] = [
+    "ts",
+    "level",
+    "target",
+    "file",
+    "line",
+    "module_path",
+    "trace_id",
+    "span_id",
+    "backtrace",
+    "msg",
+    "seq",
+];
+
+/// What the default formatter does with a field of a log event whose key has already been used, either by the
+/// formatter itself, e.g. `level` or `msg`, or by another field, set with [`CallLoggerBuilder::on_key_collision`].
+#[derive(Clone, PartialEq, Debug, Default)]
+#[non_exhaustive]
+pub enum KeyCollision {
+    /// Write the field anyway, so that the JSON object has the key twice, this is the default
+    #[default]
+    Duplicate,
+    /// Leave the field out
+    Skip,
+    /// Write the field with this prefix added to its key, e.g. `fields.` so that `level` is written as `fields.level`
+    Prefix(String),
+}
+
+/// Where the default formatter writes the fields of a log event
+#[derive(Default, Debug)]
+pub(crate) struct FieldLayout {
+    /// The key of the object that the fields are nested in, or `None` to write them with the other members
+    nest_under: Option<String>,
+
+    /// What to do with a field whose key has already been used
+    collision: KeyCollision,
+
+    /// Write the sequence number of the log event as `seq`, for audit mode
+    pub(crate) sequence: bool,
+}
+
+impl CallLoggerBuilder {
+    /// Nests the key-value pairs, scoped fields and static fields of each log event in an object with this `key` in
+    /// the output of the default formatter, e.g. `{"ts":...,"fields":{"user":"42"},"msg":"..."}`, rather than writing
+    /// them with the members that the formatter writes itself.  The object is written even when it is empty, so that
+    /// consumers with a strict schema always find it.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .nest_kv_under("fields")
+    ///     .init();
+    /// log::info!(user = "42"; "logged in");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn nest_kv_under<K>(mut self, key: K) -> CallLoggerBuilder
+    where
+        K: Into<String>,
+    {
+        self.field_layout.nest_under = Some(key.into());
+        self
+    }
+
+    /// Sets what the default formatter does with a field of a log event whose key has already been used, e.g. a
+    /// key-value pair called `level`.  By default the field is written anyway, so that the JSON object has the key
+    /// twice.  When the fields are nested with [`CallLoggerBuilder::nest_kv_under`], they can only collide with each
+    /// other, e.g. a key-value pair and a static field with the same key.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::{CallLogger, KeyCollision};
+    /// CallLogger::new()
+    ///     .on_key_collision(KeyCollision::Prefix("fields.".to_string()))
+    ///     .init();
+    /// log::info!(level = "high"; "logged as fields.level");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn on_key_collision(mut self, policy: KeyCollision) -> CallLoggerBuilder {
+        self.field_layout.collision = policy;
+        self
+    }
+}
+
+/// Writes the key-value pairs, scoped fields and static fields of a log event as JSON members that are each followed
+/// by a comma, nested or renamed as set with [`CallLoggerBuilder::nest_kv_under`] and
+/// [`CallLoggerBuilder::on_key_collision`].  `reserved` are the keys of the other members of the object.
+pub(crate) fn push_json_fields(buffer: &mut String, context: &FormatContext, reserved: &[&str]) {
+    let layout = context.field_layout();
+    let reserved = match &layout.nest_under {
+        Some(key) => {
+            buffer.push('"');
+            push_json_escaped(buffer, key);
+            buffer.push_str("\":{");
+            &[]
+        }
+        None => reserved,
+    };
+    let start = buffer.len();
+    let mut written: Vec<&str> = Vec::new();
+    // the key-value pairs are already JSON when they are serialized, the other fields are always strings
+    #[cfg(feature = "kv_serde")]
+    let kv = context.kv_json().iter().map(|(k, v)| (k, v, true));
+    #[cfg(not(feature = "kv_serde"))]
+    let kv = context.kv().iter().map(|(k, v)| (k, v, false));
+    for (key, value, is_json) in kv
+        .chain(context.scoped_fields().iter().map(|(k, v)| (k, v, false)))
+        .chain(context.static_fields().iter().map(|(k, v)| (k, v, false)))
+    {
+        let collides = reserved.contains(&key.as_str()) || written.contains(&key.as_str());
+        let name = match &layout.collision {
+            _ if !collides => Cow::Borrowed(key.as_str()),
+            KeyCollision::Duplicate => Cow::Borrowed(key.as_str()),
+            KeyCollision::Skip => continue,
+            KeyCollision::Prefix(prefix) => Cow::Owned(format!("{prefix}{key}")),
+        };
+        written.push(key);
+        buffer.push('"');
+        push_json_escaped(buffer, &name);
+        buffer.push_str("\":");
+        if is_json {
+            buffer.push_str(value);
+        } else {
+            buffer.push('"');
+            push_json_escaped(buffer, value);
+            buffer.push('"');
+        }
+        buffer.push(',');
+    }
+    if layout.nest_under.is_some() {
+        // the comma after the last field is inside the object
+        if buffer.len() > start {
+            buffer.pop();
+        }
+        buffer.push_str("},");
+    }
+}
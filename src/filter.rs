@@ -0,0 +1,132 @@
+use crate::CallLoggerBuilder;
+
+impl CallLoggerBuilder {
+    /// Only logs events whose target matches the glob `pattern`, where `*` matches any number of characters and `?`
+    /// matches a single character.  When this is called more than once, events whose target matches any of the
+    /// patterns are logged.  The target is the module path unless it was set in the logging macro, e.g.
+    /// `log::info!(target: "audit", ...)`.
+    ///
+    /// Example - Only log audit events
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .filter_target("audit*")
+    ///     .init();
+    /// log::info!(target: "audit", "user logged in");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn filter_target<T>(mut self, pattern: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        self.target_filter.include.push(pattern.into());
+        self
+    }
+
+    /// Doesn't log events whose target matches the glob `pattern`, where `*` matches any number of characters and `?`
+    /// matches a single character.  This takes precedence over [`CallLoggerBuilder::filter_target`].
+    ///
+    /// Example - Don't log the events of the HTTP client
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .exclude_target("hyper::*")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn exclude_target<T>(mut self, pattern: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        self.target_filter.exclude.push(pattern.into());
+        self
+    }
+
+    /// Doesn't deliver the log events of a module or any of its submodules, e.g. a chatty dependency, without
+    /// changing the level that is logged for everything else.  The log events of the built in HTTP client are never
+    /// sent to a URL call target, whatever is suppressed.  This applies to the targets added with
+    /// [`CallLoggerBuilder::with_target`] as well.
+    ///
+    /// Example - Don't deliver the log events of the HTTP server
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .suppress_module("hyper")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn suppress_module<T>(mut self, module: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        let module = module.into();
+        let submodules = format!("{module}::*");
+        self.dispatcher.suppressed_modules.push(module);
+        self.dispatcher.suppressed_modules.push(submodules);
+        self
+    }
+
+    /// Doesn't deliver the log events of the modules that match the glob `pattern`, where `*` matches any number of
+    /// characters and `?` matches a single character.  The module path of a log event is used, or its target if it
+    /// has none.
+    ///
+    /// Example - Don't deliver the log events of the AWS SDK crates
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .suppress_modules_matching("aws_*")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn suppress_modules_matching<T>(mut self, pattern: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        self.dispatcher.suppressed_modules.push(pattern.into());
+        self
+    }
+}
+
+/// The glob patterns that the target of a log event is matched against
+#[derive(Default, Debug)]
+pub(crate) struct TargetFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl TargetFilter {
+    /// Whether a log event with this target should be logged
+    pub(crate) fn allows(&self, target: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|x| glob_match(x, target)))
+            && !self.exclude.iter().any(|x| glob_match(x, target))
+    }
+}
+
+/// Matches text against a glob pattern where `*` matches any number of characters and `?` matches one
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let text = text.chars().collect::<Vec<char>>();
+    let (mut p, mut t) = (0, 0);
+    // the position after the last `*` and the position in the text that it was matched up to
+    let mut star = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p + 1, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|x| *x == '*')
+}
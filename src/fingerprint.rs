@@ -0,0 +1,66 @@
+use std::sync::{Arc, OnceLock};
+
+use log::Record;
+
+use crate::{CallLoggerBuilder, Fingerprinter};
+
+impl CallLoggerBuilder {
+    /// Sets what makes two log events the same event, for the features that group them: the count of
+    /// [`CallLoggerBuilder::escalate_after`], the samples of [`CallLoggerBuilder::digest`], and the issues and tickets
+    /// opened by [`CallLoggerBuilder::github_issues`] and [`CallLoggerBuilder::jira`].  The closure returns a key for
+    /// the log event, and log events with the same key have the same fingerprint.  By default the key is the module
+    /// and the message, so a message with an id in it is a different event for each id.
+    ///
+    /// Example - Treat messages that only differ in their numbers as the same event
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .fingerprint_with(|record| {
+    ///         let message = record.args().to_string();
+    ///         let message = message.replace(|c: char| c.is_ascii_digit(), "#");
+    ///         format!("{}\n{message}", record.target())
+    ///     })
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn fingerprint_with<F>(mut self, fingerprinter: F) -> CallLoggerBuilder
+    where
+        F: Fn(&Record) -> String + Sync + Send + 'static,
+    {
+        self.fingerprinter = Some(Arc::new(fingerprinter));
+        self
+    }
+}
+
+/// The closure that makes the key of the fingerprint of a log event, which is shared by a dispatcher and its target
+/// and set when the logger is built
+#[derive(Clone, Default)]
+pub(crate) struct FingerprintSlot(Arc<OnceLock<Arc<Fingerprinter>>>);
+
+impl FingerprintSlot {
+    /// Sets the closure, unless it has already been set
+    pub(crate) fn set(&self, fingerprinter: &Arc<Fingerprinter>) {
+        let _ = self.0.set(fingerprinter.clone());
+    }
+
+    /// The fingerprint of a log event, which is the same for each occurrence of the same event
+    pub(crate) fn of(&self, record: &Record) -> String {
+        let key = match self.0.get() {
+            Some(fingerprinter) => fingerprinter(record),
+            None => format!(
+                "{}\n{}",
+                record.module_path().unwrap_or(record.target()),
+                record.args()
+            ),
+        };
+        format!("call-logger-{:016x}", fnv1a(&key))
+    }
+}
+
+/// The 64 bit FNV-1a hash, which unlike the hasher of the standard library doesn't change between releases of Rust
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use log::{Level, Record};
+
+use crate::{
+    dispatch::DispatcherSlot, encode_query, escape_json, fingerprint::FingerprintSlot,
+    json_first_element, json_member, BoxError, CallError, CallLoggerBuilder, CallTarget,
+};
+
+/// The REST API of github.com
+const API_URL: &str = "https://api.github.com";
+
+/// The longest title of an issue that is made from the message of a log event
+const MAX_TITLE: usize = 100;
+
+impl CallLoggerBuilder {
+    /// Reports error log events as issues of a GitHub repository, e.g. so that a small open source project gets crash
+    /// reports as issues.  Each error is fingerprinted by its module and message, or with the closure set with
+    /// [`CallLoggerBuilder::fingerprint_with`], and an error with the same fingerprint as an open issue is added to it
+    /// as a comment instead of opening another issue.  Once that issue is closed, the next error opens another one.
+    /// The issue title is the module and the first line of the message, and the formatted output of the log event is
+    /// in the body.  Other log events are not sent.  The `repo` is the owner and name of the repository, e.g.
+    /// `a1ecbr0wn/call_logger`, and the `token` needs permission to write issues.  The requests are made with the built
+    /// in HTTP client, its DNS settings, the URL policy and the limit on requests in flight, so this can't be used with
+    /// [`CallLoggerBuilder::with_http_transport`], which doesn't return the responses that are needed.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .github_issues("my-org/my-app", "my-token")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn github_issues<R, T>(self, repo: R, token: T) -> CallLoggerBuilder
+    where
+        R: Into<String>,
+        T: AsRef<str>,
+    {
+        self.github_issues_with_api(API_URL, repo, token)
+    }
+
+    /// As [`CallLoggerBuilder::github_issues`], but for the REST API at `api_url`, e.g.
+    /// `https://github.example.com/api/v3` for GitHub Enterprise Server.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .github_issues_with_api("https://github.example.com/api/v3", "my-org/my-app", "my-token")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn github_issues_with_api<U, R, T>(
+        mut self,
+        api_url: U,
+        repo: R,
+        token: T,
+    ) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+        R: Into<String>,
+        T: AsRef<str>,
+    {
+        self.dispatcher.target = Some(Box::new(GitHubIssues {
+            api_url: api_url.into().trim_end_matches('/').to_string(),
+            repo: repo.into(),
+            authorization: format!("Bearer {}", token.as_ref()),
+            issues: Mutex::new(HashMap::new()),
+            fingerprint: self.dispatcher.fingerprint.clone(),
+            owner: self.dispatcher.owner.clone(),
+        }));
+        self
+    }
+}
+
+struct GitHubIssues {
+    api_url: String,
+    repo: String,
+    authorization: String,
+    /// The number of the issue that has been found or opened for each fingerprint, each behind a lock of its own so
+    /// that errors with the same fingerprint are reported one at a time without holding up the others
+    issues: Mutex<HashMap<String, Arc<Mutex<Option<u64>>>>>,
+    fingerprint: FingerprintSlot,
+    owner: DispatcherSlot,
+}
+
+impl GitHubIssues {
+    fn request(&self, method: &str, path: &str, body: Option<&str>) -> Result<String, CallError> {
+        let mut headers = vec![
+            ("Authorization", self.authorization.as_str()),
+            ("Accept", "application/vnd.github+json"),
+        ];
+        if body.is_some() {
+            headers.push(("Content-Type", "application/json"));
+        }
+        self.owner
+            .request(method, &format!("{}/{path}", self.api_url), &headers, body)
+    }
+
+    /// Whether the issue is still open
+    fn is_open(&self, number: u64) -> Result<bool, CallError> {
+        let response =
+            self.request("GET", &format!("repos/{}/issues/{number}", self.repo), None)?;
+        Ok(json_member(&response, "state") == Some("\"open\""))
+    }
+
+    /// The number of the open issue with the fingerprint in its body, if there is one
+    fn find_issue(&self, fingerprint: &str) -> Result<Option<u64>, CallError> {
+        let query = format!("repo:{} is:issue is:open in:body {fingerprint}", self.repo);
+        let response = self.request(
+            "GET",
+            &format!("search/issues?q={}", encode_query(&query)),
+            None,
+        )?;
+        Ok(json_member(&response, "items")
+            .and_then(json_first_element)
+            .and_then(issue_number))
+    }
+}
+
+impl CallTarget for GitHubIssues {
+    fn call(&self, payload: &str, record: &Record) -> Result<(), BoxError> {
+        if record.level() != Level::Error {
+            return Ok(());
+        }
+        let message = record.args().to_string();
+        let module = record.module_path().unwrap_or(record.target());
+        let fingerprint = self.fingerprint.of(record);
+        let issue = self
+            .issues
+            .lock()
+            .unwrap_or_else(|x| x.into_inner())
+            .entry(fingerprint.clone())
+            .or_default()
+            .clone();
+        // the lock is held while the issue is looked up and opened, so that the same error isn't opened twice
+        let mut issue = issue.lock().unwrap_or_else(|x| x.into_inner());
+        let number = match *issue {
+            Some(number) if self.is_open(number)? => Some(number),
+            _ => self.find_issue(&fingerprint)?,
+        };
+        match number {
+            Some(number) => {
+                let body = format!("Logged again:\n\n```\n{payload}\n```");
+                self.request(
+                    "POST",
+                    &format!("repos/{}/issues/{number}/comments", self.repo),
+                    Some(&format!("{{\"body\":\"{}\"}}", escape_json(&body))),
+                )?;
+                *issue = Some(number);
+            }
+            None => {
+                let title = format!("{module}: {}", message.lines().next().unwrap_or_default());
+                let title = title.chars().take(MAX_TITLE).collect::<String>();
+                let body = format!("```\n{payload}\n```\n\nFingerprint: {fingerprint}");
+                let response = self.request(
+                    "POST",
+                    &format!("repos/{}/issues", self.repo),
+                    Some(&format!(
+                        "{{\"title\":\"{}\",\"body\":\"{}\"}}",
+                        escape_json(&title),
+                        escape_json(&body)
+                    )),
+                )?;
+                *issue = issue_number(&response);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The number of an issue from its JSON
+fn issue_number(issue: &str) -> Option<u64> {
+    json_member(issue, "number")?.parse().ok()
+}
@@ -0,0 +1,278 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use log::{Level, Record};
+
+use crate::{CallError, CallErrorKind, CallLogger};
+
+thread_local! {
+    /// The shared state of the logger that a test event is being sent to from this thread, or `0`
+    static TEST_EVENT_FOR: Cell<usize> = const { Cell::new(0) };
+
+    /// The outcome of delivering the test event, set by the logger
+    static TEST_OUTCOME: RefCell<Option<Result<(), CallError>>> = const { RefCell::new(None) };
+}
+
+impl CallLogger {
+    /// Returns a handle that can pause and resume the delivery of log events after the logger has been set up, e.g.
+    /// to stop calling a struggling webhook during an incident without restarting the application.
+    ///
+    /// # Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// let logger = CallLogger::new().build().unwrap();
+    /// let handle = logger.handle();
+    /// logger.init();
+    /// handle.pause();
+    /// log::error!("not delivered");
+    /// handle.resume();
+    /// ```
+    pub fn handle(&self) -> CallLoggerHandle {
+        CallLoggerHandle {
+            state: self.pause.clone(),
+            expired: self
+                .dispatcher
+                .event_ttl
+                .as_ref()
+                .map(|event_ttl| event_ttl.expired.clone()),
+        }
+    }
+
+    /// Sends a test event through the formatters and middleware to the call target and every other target, and returns
+    /// the first failure, so that a deploy script can check that a webhook is set up correctly before the application
+    /// handles traffic.  The test event is an error from the `call_logger` target with a `test=true` key-value pair, so
+    /// that targets which only accept errors are sent it too.  It is delivered on this thread, even if the logger is
+    /// paused or delivers in the background, and is not written to the dead-letter file if it fails.  A batched target
+    /// only adds it to the batch.
+    ///
+    /// # Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// let logger = CallLogger::new()
+    ///     .with_call_target("echo")
+    ///     .build()
+    ///     .unwrap();
+    /// if let Err(x) = logger.send_test_event() {
+    ///     eprintln!("the call target can't be called, {x}");
+    /// }
+    /// ```
+    pub fn send_test_event(&self) -> Result<(), CallError> {
+        with_test_record(|record| self.deliver_test_event(record))
+    }
+}
+
+/// A handle to a [`CallLogger`] that can pause, resume and silence the delivery of log events at runtime, returned by
+/// [`CallLogger::handle`].  While the logger is paused, log events are counted and written to the dead-letter file if
+/// there is one, so that they can be re-sent with [`CallLogger::replay_dead_letters`], but they are not delivered.
+/// They are written after the middleware, so that they are scrubbed or encrypted in the file as they would have been
+/// when delivered.
+#[derive(Clone, Debug)]
+pub struct CallLoggerHandle {
+    state: Arc<PauseState>,
+    /// The number of log events that were dropped for being too old, if they are
+    expired: Option<Arc<AtomicU64>>,
+}
+
+impl CallLoggerHandle {
+    /// Stops log events from being delivered until [`CallLoggerHandle::resume`] is called
+    pub fn pause(&self) {
+        self.state.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Delivers log events again after [`CallLoggerHandle::pause`]
+    pub fn resume(&self) {
+        self.state.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether the delivery of log events is paused
+    pub fn is_paused(&self) -> bool {
+        self.state.is_paused()
+    }
+
+    /// The number of log events that have not been delivered because the logger was paused or silenced
+    pub fn paused_count(&self) -> u64 {
+        self.state.count.load(Ordering::Relaxed)
+    }
+
+    /// The number of log events that were dropped because they were older than
+    /// [`CallLoggerBuilder::with_event_ttl`](crate::CallLoggerBuilder::with_event_ttl) by the time they would have been
+    /// delivered
+    pub fn expired_count(&self) -> u64 {
+        self.expired
+            .as_ref()
+            .map_or(0, |expired| expired.load(Ordering::Relaxed))
+    }
+
+    /// Stops log events from being delivered for `duration`, e.g. while a deploy restarts the services that would
+    /// otherwise set off a webhook.  Silenced log events are counted and written to the dead-letter file in the same
+    /// way as when the logger is paused, so that they can be reviewed later.
+    ///
+    /// # Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// # use std::time::Duration;
+    /// let logger = CallLogger::new().build().unwrap();
+    /// let handle = logger.handle();
+    /// logger.init();
+    /// handle.silence_for(Duration::from_secs(15 * 60));
+    /// log::error!("not delivered until the silence ends");
+    /// ```
+    pub fn silence_for(&self, duration: Duration) {
+        self.silence_matching(|_| true, Instant::now() + duration);
+    }
+
+    /// Stops the log events that the closure returns `true` for from being delivered until `until`, e.g. to silence
+    /// the errors of one module during its planned maintenance while the others are still delivered.  Silenced log
+    /// events are handled in the same way as with [`CallLoggerHandle::silence_for`].
+    ///
+    /// # Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// # use std::time::{Duration, Instant};
+    /// let logger = CallLogger::new().build().unwrap();
+    /// let handle = logger.handle();
+    /// logger.init();
+    /// handle.silence_matching(
+    ///     |record| record.target().starts_with("my_app::db"),
+    ///     Instant::now() + Duration::from_secs(30 * 60),
+    /// );
+    /// ```
+    pub fn silence_matching<F>(&self, predicate: F, until: Instant)
+    where
+        F: Fn(&Record) -> bool + Sync + Send + 'static,
+    {
+        self.state
+            .silences
+            .write()
+            .unwrap_or_else(|x| x.into_inner())
+            .push(Silence {
+                predicate: Box::new(predicate),
+                until,
+            });
+    }
+
+    /// Ends every silence set with [`CallLoggerHandle::silence_for`] or [`CallLoggerHandle::silence_matching`]
+    /// before its time
+    pub fn end_silences(&self) {
+        self.state
+            .silences
+            .write()
+            .unwrap_or_else(|x| x.into_inner())
+            .clear();
+    }
+
+    /// Sends a test event to the logger once it has been set up with [`CallLogger::init`] and returns the first
+    /// failure, in the same way as [`CallLogger::send_test_event`].  The test event is sent through [`log::logger`],
+    /// so this also works when the logger is wrapped by another logger that passes it log events.
+    ///
+    /// # Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// let logger = CallLogger::new().build().unwrap();
+    /// let handle = logger.handle();
+    /// logger.init();
+    /// if let Err(x) = handle.send_test_event() {
+    ///     eprintln!("the call target can't be called, {x}");
+    /// }
+    /// ```
+    pub fn send_test_event(&self) -> Result<(), CallError> {
+        TEST_EVENT_FOR.with(|test_event_for| test_event_for.set(Arc::as_ptr(&self.state) as usize));
+        with_test_record(|record| log::logger().log(record));
+        TEST_EVENT_FOR.with(|test_event_for| test_event_for.set(0));
+        TEST_OUTCOME
+            .with(|outcome| outcome.borrow_mut().take())
+            .unwrap_or_else(|| {
+                Err(CallError::new(
+                    CallErrorKind::Unsupported,
+                    "the logger has not been set up with init()",
+                ))
+            })
+    }
+}
+
+/// Calls the closure with the record of a test event
+fn with_test_record<R>(f: impl FnOnce(&Record) -> R) -> R {
+    let kv = ("test", true);
+    f(&Record::builder()
+        .args(format_args!("call_logger test event"))
+        .level(Level::Error)
+        .target("call_logger")
+        .key_values(&kv)
+        .build())
+}
+
+/// Whether the record being logged on this thread is a test event for the logger with this shared state
+pub(crate) fn is_test_event_for(state: &Arc<PauseState>) -> bool {
+    TEST_EVENT_FOR.with(|test_event_for| test_event_for.get() == Arc::as_ptr(state) as usize)
+}
+
+/// Passes the outcome of delivering a test event back to the handle that sent it
+pub(crate) fn set_test_outcome(outcome: Result<(), CallError>) {
+    TEST_OUTCOME.with(|test_outcome| *test_outcome.borrow_mut() = Some(outcome));
+}
+
+/// Whether the delivery of log events is paused or silenced, shared between the logger and its handles
+#[derive(Default, Debug)]
+pub(crate) struct PauseState {
+    paused: AtomicBool,
+    count: AtomicU64,
+    silences: RwLock<Vec<Silence>>,
+}
+
+impl PauseState {
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Why the log event isn't delivered, if the logger is paused or the log event is silenced
+    pub(crate) fn holds(&self, record: &Record) -> Option<&'static str> {
+        if self.is_paused() {
+            return Some("the logger is paused");
+        }
+        let now = Instant::now();
+        let silences = self.silences.read().unwrap_or_else(|x| x.into_inner());
+        if silences.is_empty() {
+            return None;
+        }
+        if silences.iter().all(|silence| silence.until > now) {
+            return silences
+                .iter()
+                .any(|silence| (silence.predicate)(record))
+                .then_some("the log event is silenced");
+        }
+        // the silences that have ended are removed
+        drop(silences);
+        let mut silences = self.silences.write().unwrap_or_else(|x| x.into_inner());
+        silences.retain(|silence| silence.until > now);
+        silences
+            .iter()
+            .any(|silence| (silence.predicate)(record))
+            .then_some("the log event is silenced")
+    }
+
+    /// Counts a log event that was not delivered because the logger was paused
+    pub(crate) fn count(&self) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// The log events that are not delivered until a time, set with [`CallLoggerHandle::silence_matching`]
+struct Silence {
+    predicate: Box<dyn Fn(&Record) -> bool + Sync + Send>,
+    until: Instant,
+}
+
+impl fmt::Debug for Silence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Silence")
+            .field("until", &self.until)
+            .finish_non_exhaustive()
+    }
+}
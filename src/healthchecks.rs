@@ -0,0 +1,54 @@
+use log::Level;
+
+use crate::CallLoggerBuilder;
+
+impl CallLoggerBuilder {
+    /// Pings a [Healthchecks.io][healthchecks] check with the info log events, which are taken to be heartbeats, and
+    /// signals a failure of the check with the error log events, so that the check raises an alert when the heartbeats
+    /// stop or something goes wrong.  `ping_url` is the ping URL of the check, e.g.
+    /// `https://hc-ping.com/<uuid>`, and errors are sent to its `/fail` endpoint.  The message of the log event is
+    /// the body of the ping, which Healthchecks.io shows in the log of the check.  Other log events are not sent and
+    /// the formatter is not used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .healthchecks("https://hc-ping.com/eb095278-f28d-448d-87fb-7b75c171a6aa")
+    ///     .init();
+    /// log::info!("heartbeat");
+    /// ```
+    ///
+    /// [healthchecks]: https://healthchecks.io/docs/http_api/
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn healthchecks<U>(mut self, ping_url: U) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+    {
+        let ping_url = ping_url.into().trim_end_matches('/').to_string();
+        let (success_url, fail_url) = (ping_url.clone(), format!("{ping_url}/fail"));
+        self.dispatcher.http_headers.push((
+            "Content-Type".to_string(),
+            "text/plain; charset=utf-8".to_string(),
+        ));
+        self.dispatcher.accepts = Some(Box::new(|_, record| {
+            matches!(record.level(), Level::Error | Level::Info)
+        }));
+        self.dispatcher.route = Some(Box::new(move |_, record| {
+            if record.level() == Level::Error {
+                fail_url.clone()
+            } else {
+                success_url.clone()
+            }
+        }));
+        self.formatter = Box::new(|buffer, context| {
+            let message = context.message();
+            match message.as_str() {
+                Some(message) => buffer.push_str(message),
+                None => buffer.push_str(&message.to_string()),
+            }
+        });
+        self.with_call_target(ping_url)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{webhooks::flat_json_formatter, CallLoggerBuilder};
+
+/// The type of the events that are fired on the event bus of Home Assistant
+const EVENT_TYPE: &str = "call_logger";
+
+impl CallLoggerBuilder {
+    /// Fires a `call_logger` event on the event bus of [Home Assistant][events] for each log event, so that an
+    /// automation can react to it, e.g. by turning a lamp red when a daemon logs an error.  The data of the event is
+    /// the `level`, `target` and `message` of the log event and its key-value pairs, which can be used in the trigger
+    /// of the automation, e.g. `event_data: {level: ERROR}`.  The `base_url` is the URL of Home Assistant, e.g.
+    /// `http://homeassistant.local:8123`, and the `token` is a long-lived access token.  The formatter is not used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .home_assistant("http://homeassistant.local:8123", "my-long-lived-token")
+    ///     .init();
+    /// ```
+    ///
+    /// [events]: https://developers.home-assistant.io/docs/api/rest/#post-apieventsevent_type
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn home_assistant<U, T>(mut self, base_url: U, token: T) -> CallLoggerBuilder
+    where
+        U: AsRef<str>,
+        T: AsRef<str>,
+    {
+        self.dispatcher.http_headers.push((
+            "Authorization".to_string(),
+            format!("Bearer {}", token.as_ref()),
+        ));
+        self.formatter = Box::new(flat_json_formatter);
+        self.with_call_target(format!(
+            "{}/api/events/{EVENT_TYPE}",
+            base_url.as_ref().trim_end_matches('/')
+        ))
+    }
+
+    /// Triggers the Home Assistant automation with the [webhook trigger][webhook] `webhook_id` for each log event,
+    /// with the same data as [`CallLoggerBuilder::home_assistant`] as the JSON body, which the automation can use as
+    /// `trigger.json`.  Webhooks don't need an access token.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .home_assistant_webhook("http://homeassistant.local:8123", "my_app_errors")
+    ///     .init();
+    /// ```
+    ///
+    /// [webhook]: https://www.home-assistant.io/docs/automation/trigger/#webhook-trigger
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn home_assistant_webhook<U, W>(mut self, base_url: U, webhook_id: W) -> CallLoggerBuilder
+    where
+        U: AsRef<str>,
+        W: AsRef<str>,
+    {
+        self.formatter = Box::new(flat_json_formatter);
+        self.with_call_target(format!(
+            "{}/api/webhook/{}",
+            base_url.as_ref().trim_end_matches('/'),
+            webhook_id.as_ref()
+        ))
+    }
+}
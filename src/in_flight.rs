@@ -0,0 +1,74 @@
+use std::sync::{Condvar, Mutex};
+
+use crate::CallLoggerBuilder;
+
+impl CallLoggerBuilder {
+    /// Limits the number of HTTP requests that are sent to a URL call target at the same time, so that a burst of log
+    /// events delivered by the workers doesn't open hundreds of connections.  Requests over the limit wait until one
+    /// of the others has finished.  The limit applies to each target separately, and at least one request is always
+    /// allowed.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_workers(16)
+    ///     .with_max_in_flight(4)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_max_in_flight(mut self, max: usize) -> CallLoggerBuilder {
+        self.dispatcher.in_flight = Some(InFlight::new(max));
+        self
+    }
+}
+
+/// Counts the HTTP requests of a target that have been sent and not yet answered
+pub(crate) struct InFlight {
+    max: usize,
+    count: Mutex<usize>,
+    finished: Condvar,
+}
+
+impl InFlight {
+    pub(crate) fn new(max: usize) -> InFlight {
+        InFlight {
+            max: max.max(1),
+            count: Mutex::new(0),
+            finished: Condvar::new(),
+        }
+    }
+
+    /// A limit with the same maximum and no requests
+    pub(crate) fn fresh(&self) -> InFlight {
+        InFlight::new(self.max)
+    }
+
+    /// Waits until there is room for another request, which is counted until the returned permit is dropped
+    pub(crate) fn acquire(&self) -> Permit<'_> {
+        let mut count = self.count.lock().unwrap_or_else(|x| x.into_inner());
+        while *count >= self.max {
+            count = self.finished.wait(count).unwrap_or_else(|x| x.into_inner());
+        }
+        *count += 1;
+        Permit { in_flight: self }
+    }
+}
+
+/// A request that has been counted by [`InFlight`]
+pub(crate) struct Permit<'a> {
+    in_flight: &'a InFlight,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        *self
+            .in_flight
+            .count
+            .lock()
+            .unwrap_or_else(|x| x.into_inner()) -= 1;
+        self.in_flight.finished.notify_one();
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    fmt::Arguments,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{
+    kv::{Error, Key, Value, VisitSource},
+    Record,
+};
+
+use crate::{encode_query, CallLoggerBuilder};
+
+impl CallLoggerBuilder {
+    /// Formats each log event as an [InfluxDB line protocol][line-protocol] point in the `log` measurement.  The level
+    /// and module path are written as tags, the message and any key-value pairs are written as fields and the point
+    /// is timestamped in nanoseconds since the epoch.  Float values that are NaN or infinite can't be written in the line
+    /// protocol, so those key-value pairs are left out.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .influxdb_format()
+    ///     .init();
+    /// log::info!("msg");
+    /// ```
+    ///
+    /// [line-protocol]: https://docs.influxdata.com/influxdb/v2/reference/syntax/line-protocol/
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn influxdb_format(self) -> CallLoggerBuilder {
+        self.format(|_, message, record| influxdb_formatter(message, record))
+    }
+
+    /// Writes each log event to an InfluxDB v2 bucket using the line protocol formatter of
+    /// [`CallLoggerBuilder::influxdb_format`].  The `url` is the base URL of the InfluxDB server and the `token` is an
+    /// API token with write access to the bucket.  If the server also needs the organization, it can be added with
+    /// [`CallLoggerBuilder::customize_request`], e.g. `request.query("org", "my-org")`.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .influxdb("http://localhost:8086", "logs", "my-token")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn influxdb<U, B, T>(mut self, url: U, bucket: B, token: T) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+        B: AsRef<str>,
+        T: AsRef<str>,
+    {
+        self.dispatcher.http_headers.push((
+            "Authorization".to_string(),
+            format!("Token {}", token.as_ref()),
+        ));
+        self.dispatcher.http_headers.push((
+            "Content-Type".to_string(),
+            "text/plain; charset=utf-8".to_string(),
+        ));
+        let url = format!(
+            "{}/api/v2/write?bucket={}&precision=ns",
+            url.into().trim_end_matches('/'),
+            encode_query(bucket.as_ref())
+        );
+        self.with_call_target(url).influxdb_format()
+    }
+}
+
+fn influxdb_formatter(message: &Arguments, record: &Record) -> String {
+    let mut line = format!("log,level={}", record.level());
+    if let Some(module_path) = record.module_path() {
+        line.push_str(&format!(",module={}", escape_tag(module_path)));
+    }
+    line.push_str(&format!(" msg=\"{}\"", escape_field(&message.to_string())));
+    let mut visitor = FieldVisitor { fields: Vec::new() };
+    if let Ok(()) = record.key_values().visit(&mut visitor) {
+        for (key, value) in visitor.fields {
+            line.push_str(&format!(",{key}={value}"));
+        }
+    }
+    if let Ok(since_epoch) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        line.push_str(&format!(" {}", since_epoch.as_nanos()));
+    }
+    line
+}
+
+/// Escapes tag keys, tag values and field keys
+fn escape_tag(tag: &str) -> String {
+    tag.replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
+
+/// Escapes string field values, which are double quoted and can't contain a newline, as it ends the point
+fn escape_field(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// Visitor that converts the kv pairs in a log record to line protocol fields, keeping numbers and booleans typed.
+struct FieldVisitor {
+    fields: Vec<(String, String)>,
+}
+
+impl<'kvs> VisitSource<'kvs> for FieldVisitor {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        let value = if let Some(value) = value.to_bool() {
+            value.to_string()
+        } else if let Some(value) = value.to_i64() {
+            format!("{value}i")
+        } else if let Some(value) = value.to_f64() {
+            if !value.is_finite() {
+                return Ok(());
+            }
+            value.to_string()
+        } else {
+            format!("\"{}\"", escape_field(&value.to_string()))
+        };
+        self.fields.push((escape_tag(key.as_str()), value));
+        Ok(())
+    }
+}
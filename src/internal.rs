@@ -0,0 +1,127 @@
+use std::{
+    fmt::Arguments,
+    fs::OpenOptions,
+    io::{stderr, stdout, Write},
+    path::PathBuf,
+    sync::RwLock,
+};
+
+#[cfg(all(
+    feature = "process",
+    not(feature = "no-exec"),
+    not(target_arch = "wasm32")
+))]
+use std::process::{Command, Stdio};
+
+use crate::CallLoggerBuilder;
+
+/// Where the logger reports its own errors, e.g. that the call target could not be called, set with
+/// [`CallLoggerBuilder::with_internal_log`].
+pub enum InternalSink {
+    /// Print to stdout, this is the default
+    Stdout,
+    /// Print to stderr
+    Stderr,
+    /// Append a line to a file
+    File(PathBuf),
+    /// Write to the system log, with `logger` on Unix or as an event in the Windows application event log
+    #[cfg(all(
+        feature = "process",
+        not(feature = "no-exec"),
+        not(target_arch = "wasm32")
+    ))]
+    SystemLog,
+    /// Pass the error to a closure
+    Custom(Box<dyn Fn(&str) + Sync + Send + 'static>),
+    /// Don't report errors
+    Silent,
+}
+
+/// Where errors are reported, shared by every logger because some errors are found outside of the logger, e.g. by the
+/// threads of the targets
+static INTERNAL_SINK: RwLock<InternalSink> = RwLock::new(InternalSink::Stdout);
+
+impl CallLoggerBuilder {
+    /// Sets where the logger reports its own errors, e.g. that a webhook has been failing, which are printed to stdout
+    /// by default.  Headless services and daemons, where stdout is not seen, can report them to a file or the system
+    /// log instead.  This applies to every logger in the process and is set when the logger is built.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::{CallLogger, InternalSink};
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_internal_log(InternalSink::File("call_logger_errors.log".into()))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_internal_log(mut self, sink: InternalSink) -> CallLoggerBuilder {
+        self.internal_sink = Some(sink);
+        self
+    }
+}
+
+/// Sets where errors are reported
+pub(crate) fn set_sink(sink: InternalSink) {
+    *INTERNAL_SINK.write().unwrap_or_else(|x| x.into_inner()) = sink;
+}
+
+/// Reports an error of the logger to the internal sink
+pub(crate) fn report(message: Arguments) {
+    let sink = INTERNAL_SINK.read().unwrap_or_else(|x| x.into_inner());
+    let _ = match &*sink {
+        InternalSink::Stdout => writeln!(stdout(), "{message}"),
+        InternalSink::Stderr => writeln!(stderr(), "{message}"),
+        InternalSink::File(path) => OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{message}")),
+        #[cfg(all(
+            feature = "process",
+            not(feature = "no-exec"),
+            not(target_arch = "wasm32")
+        ))]
+        InternalSink::SystemLog => system_log(&message.to_string()),
+        InternalSink::Custom(report) => {
+            report(&message.to_string());
+            Ok(())
+        }
+        InternalSink::Silent => Ok(()),
+    };
+}
+
+#[cfg(all(
+    feature = "process",
+    not(feature = "no-exec"),
+    not(target_arch = "wasm32")
+))]
+fn system_log(message: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("eventcreate");
+        command.args([
+            "/T",
+            "ERROR",
+            "/ID",
+            "1",
+            "/L",
+            "APPLICATION",
+            "/SO",
+            "call_logger",
+            "/D",
+        ]);
+        command
+    } else {
+        let mut command = Command::new("logger");
+        command.args(["-t", "call_logger", "--"]);
+        command
+    };
+    command
+        .arg(message)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|_| ())
+}
@@ -0,0 +1,265 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use log::{Level, Record};
+
+use crate::{
+    base64, dispatch::DispatcherSlot, encode_query, escape_json, fingerprint::FingerprintSlot,
+    json_first_element, json_member, BoxError, CallError, CallLoggerBuilder, CallTarget,
+};
+
+/// The longest summary of a ticket that is made from the message of a log event
+const MAX_SUMMARY: usize = 255;
+
+/// How the logger signs in to Jira, passed to [`CallLoggerBuilder::jira`]
+#[derive(Debug, Clone)]
+pub enum JiraCredentials {
+    /// The email address of the account and an API token, for Jira Cloud
+    Basic {
+        /// The email address or user name of the account
+        user: String,
+        /// The API token or password of the account
+        token: String,
+    },
+    /// A personal access token, for Jira Data Center and Server
+    Bearer(String),
+}
+
+impl JiraCredentials {
+    fn authorization(&self) -> String {
+        match self {
+            JiraCredentials::Basic { user, token } => {
+                format!("Basic {}", base64(format!("{user}:{token}").as_bytes()))
+            }
+            JiraCredentials::Bearer(token) => format!("Bearer {token}"),
+        }
+    }
+}
+
+impl CallLoggerBuilder {
+    /// Opens a Jira ticket in `project` for each error log event, e.g. so that production errors are triaged with the
+    /// rest of the work of a team.  The summary of the ticket is the module and the first line of the message, and the
+    /// description is the formatted output of the log event.  Each error is fingerprinted by its module and message,
+    /// and the fingerprint is added to the ticket as a label, so that an error with the same fingerprint as an
+    /// unresolved ticket is added to it as a comment instead of opening another ticket.  Once that ticket is resolved,
+    /// the next error opens another one.  A label is the default on purpose, as it works in any project without an
+    /// administrator adding a field, and [`CallLoggerBuilder::jira_with_fingerprint_field`] keeps the fingerprint in a
+    /// custom field instead, out of the labels that the team sorts tickets with.  Other log events are not sent.
+    /// Tickets are opened as a `Bug` with version 2 of the REST API.  The requests are made with the built in HTTP
+    /// client, its DNS settings, the URL policy and the limit on requests in flight, so this can't be used with
+    /// [`CallLoggerBuilder::with_http_transport`], which doesn't return the responses that are needed.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::{CallLogger, JiraCredentials};
+    /// CallLogger::new()
+    ///     .jira(
+    ///         "https://my-org.atlassian.net",
+    ///         JiraCredentials::Basic { user: "me@example.com".into(), token: "my-token".into() },
+    ///         "APP",
+    ///     )
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn jira<U, P>(
+        self,
+        base_url: U,
+        credentials: JiraCredentials,
+        project: P,
+    ) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        self.jira_target(base_url.into(), credentials, project.into(), None)
+    }
+
+    /// As [`CallLoggerBuilder::jira`], but the fingerprint is kept in the text custom field `field`, e.g.
+    /// `customfield_10050`, instead of a label.  The field must be on the screen that tickets are created with.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::{CallLogger, JiraCredentials};
+    /// CallLogger::new()
+    ///     .jira_with_fingerprint_field(
+    ///         "https://jira.example.com",
+    ///         JiraCredentials::Bearer("my-token".into()),
+    ///         "APP",
+    ///         "customfield_10050",
+    ///     )
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn jira_with_fingerprint_field<U, P, F>(
+        self,
+        base_url: U,
+        credentials: JiraCredentials,
+        project: P,
+        field: F,
+    ) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+        P: Into<String>,
+        F: Into<String>,
+    {
+        self.jira_target(
+            base_url.into(),
+            credentials,
+            project.into(),
+            Some(field.into()),
+        )
+    }
+
+    fn jira_target(
+        mut self,
+        base_url: String,
+        credentials: JiraCredentials,
+        project: String,
+        field: Option<String>,
+    ) -> CallLoggerBuilder {
+        self.dispatcher.target = Some(Box::new(Jira {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            authorization: credentials.authorization(),
+            project,
+            field,
+            tickets: Mutex::new(HashMap::new()),
+            fingerprint: self.dispatcher.fingerprint.clone(),
+            owner: self.dispatcher.owner.clone(),
+        }));
+        self
+    }
+}
+
+struct Jira {
+    base_url: String,
+    authorization: String,
+    project: String,
+    /// The custom field that the fingerprint is kept in, or a label if there isn't one
+    field: Option<String>,
+    /// The key of the ticket that has been found or opened for each fingerprint, each behind a lock of its own so
+    /// that errors with the same fingerprint are reported one at a time without holding up the others
+    tickets: Mutex<HashMap<String, Arc<Mutex<Option<String>>>>>,
+    fingerprint: FingerprintSlot,
+    owner: DispatcherSlot,
+}
+
+impl Jira {
+    fn request(&self, method: &str, path: &str, body: Option<&str>) -> Result<String, CallError> {
+        let mut headers = vec![("Authorization", self.authorization.as_str())];
+        if body.is_some() {
+            headers.push(("Content-Type", "application/json"));
+        }
+        self.owner.request(
+            method,
+            &format!("{}/rest/api/2/{path}", self.base_url),
+            &headers,
+            body,
+        )
+    }
+
+    /// Whether the ticket hasn't been resolved
+    fn is_unresolved(&self, key: &str) -> Result<bool, CallError> {
+        let response = self.request("GET", &format!("issue/{key}?fields=resolution"), None)?;
+        Ok(json_member(&response, "fields")
+            .and_then(|fields| json_member(fields, "resolution"))
+            .map_or(true, |resolution| resolution == "null"))
+    }
+
+    /// The key of the unresolved ticket with the fingerprint, if there is one
+    fn find_ticket(&self, fingerprint: &str) -> Result<Option<String>, CallError> {
+        let condition = match &self.field {
+            Some(field) => {
+                let id = field.strip_prefix("customfield_").unwrap_or(field);
+                format!("cf[{id}] ~ {}", jql_string(fingerprint))
+            }
+            None => format!("labels = {}", jql_string(fingerprint)),
+        };
+        let jql = format!(
+            "project = {} AND resolution = Unresolved AND {condition}",
+            jql_string(&self.project)
+        );
+        let response = self.request(
+            "GET",
+            &format!("search?maxResults=1&fields=key&jql={}", encode_query(&jql)),
+            None,
+        )?;
+        Ok(json_member(&response, "issues")
+            .and_then(json_first_element)
+            .and_then(ticket_key))
+    }
+}
+
+impl CallTarget for Jira {
+    fn call(&self, payload: &str, record: &Record) -> Result<(), BoxError> {
+        if record.level() != Level::Error {
+            return Ok(());
+        }
+        let message = record.args().to_string();
+        let module = record.module_path().unwrap_or(record.target());
+        let fingerprint = self.fingerprint.of(record);
+        let ticket = self
+            .tickets
+            .lock()
+            .unwrap_or_else(|x| x.into_inner())
+            .entry(fingerprint.clone())
+            .or_default()
+            .clone();
+        // the lock is held while the ticket is looked up and opened, so that the same error isn't opened twice
+        let mut ticket = ticket.lock().unwrap_or_else(|x| x.into_inner());
+        let key = match ticket.take() {
+            Some(key) if self.is_unresolved(&key)? => Some(key),
+            _ => self.find_ticket(&fingerprint)?,
+        };
+        match key {
+            Some(key) => {
+                let body = format!("Logged again:\n{{code}}\n{payload}\n{{code}}");
+                self.request(
+                    "POST",
+                    &format!("issue/{key}/comment"),
+                    Some(&format!("{{\"body\":\"{}\"}}", escape_json(&body))),
+                )?;
+                *ticket = Some(key);
+            }
+            None => {
+                let summary = format!("{module}: {}", message.lines().next().unwrap_or_default());
+                let summary = summary.chars().take(MAX_SUMMARY).collect::<String>();
+                let description = format!("{{code}}\n{payload}\n{{code}}");
+                let fingerprint_field = match &self.field {
+                    Some(field) => format!(
+                        "\"{}\":\"{}\"",
+                        escape_json(field),
+                        escape_json(&fingerprint)
+                    ),
+                    None => format!("\"labels\":[\"{}\"]", escape_json(&fingerprint)),
+                };
+                let response = self.request(
+                    "POST",
+                    "issue",
+                    Some(&format!(
+                        "{{\"fields\":{{\"project\":{{\"key\":\"{}\"}},\"issuetype\":{{\"name\":\"Bug\"}},\"summary\":\"{}\",\"description\":\"{}\",{fingerprint_field}}}}}",
+                        escape_json(&self.project),
+                        escape_json(&summary),
+                        escape_json(&description)
+                    )),
+                )?;
+                *ticket = ticket_key(&response);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The key of a ticket from its JSON
+fn ticket_key(ticket: &str) -> Option<String> {
+    let key = json_member(ticket, "key")?;
+    Some(key.strip_prefix('"')?.strip_suffix('"')?.to_string())
+}
+
+/// A string in a JQL query, quoted and escaped so that it can't change the query
+fn jql_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
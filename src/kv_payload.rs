@@ -0,0 +1,43 @@
+use log::{kv::Key, Record};
+
+use crate::CallLoggerBuilder;
+
+impl CallLoggerBuilder {
+    /// Delivers the value of the key-value pair `key` as it is, instead of the formatted output, for the log events
+    /// that have it, so that a call site can write the exact body of a notification for a particular event.  The
+    /// value replaces the output of the formatter of the logger and of every target, though it still goes through the
+    /// middleware.  A string is delivered without quotes, and with the `kv_serde` feature a value captured with
+    /// `serde`, e.g. a `serde_json::Value`, is delivered as JSON.  Log events without the key are formatted as usual.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .payload_from_kv("webhook_payload")
+    ///     .init();
+    /// log::error!(webhook_payload = r#"{"text":"Disk full on db1","priority":2}"#; "disk full");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn payload_from_kv<T>(mut self, key: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        self.payload_key = Some(key.into());
+        self
+    }
+}
+
+/// The value of the key-value pair `key` of the record, written as the payload that is delivered
+pub(crate) fn kv_payload(record: &Record, key: &str) -> Option<String> {
+    let value = record.key_values().get(Key::from_str(key))?;
+    #[cfg(feature = "kv_serde")]
+    if let Ok(json) = serde_json::to_value(&value) {
+        return Some(match json {
+            serde_json::Value::String(payload) => payload,
+            json => json.to_string(),
+        });
+    }
+    Some(value.to_string())
+}
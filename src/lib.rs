@@ -17,6 +17,67 @@
 //! - `timestamps`
 //!   - add a timestamp to the output
 //!   - the timestamp can be set to one of a number of formats specified by a number of [`CallLogger`] builder functions
+//!   - adds the Bunyan formatter, see [`CallLoggerBuilder::bunyan_format`]
+//!   - adds quiet hours, which need the local time, see [`CallLoggerBuilder::quiet_hours`]
+//!
+//! - `http`
+//!   - sends log events to a URL call target with the built in HTTP client ([`ureq`](https://docs.rs/ureq))
+//!   - without it URL call targets need a transport, see [`CallLoggerBuilder::with_http_transport`]
+//!
+//! - `process`
+//!   - calls an application or script for each log event, and writes the output of the call to a file
+//!
+//! - `files`
+//!   - adds the dead-letter file, see [`CallLoggerBuilder::with_dead_letter_file`], audit mode, see
+//!     [`CallLoggerBuilder::audit_mode`], and the Avro file target
+//!
+//! - `sqlite`
+//!   - adds a target that inserts log events into a local SQLite database, see [`CallLoggerBuilder::sqlite`]
+//!
+//! - `websocket`
+//!   - adds a target that streams log events over a WebSocket connection, see [`CallLoggerBuilder::websocket`]
+//!
+//! - `schema`
+//!   - checks the formatted output against a JSON Schema, see [`CallLoggerBuilder::validate_against_schema`]
+//!
+//! - `snmp`
+//!   - adds a target that sends error log events as SNMPv2c traps, see [`CallLoggerBuilder::snmp_trap`]
+//!
+//! - `encryption`
+//!   - encrypts the formatted output to an age recipient before it is delivered, see
+//!     [`CallLoggerBuilder::encrypt_payload_with`]
+//!
+//! - `kv_serde`
+//!   - enables the `kv_serde` feature of the `log` crate, and the default formatter writes key-value pairs as JSON
+//!     values rather than strings, so that numbers, booleans, and maps and sequences captured with `serde` keep
+//!     their structure, see [`FormatContext::kv_json`]
+//!
+//! - `noop`
+//!   - adds a target that discards log events, for measuring the overhead of the logger, see
+//!     [`CallLoggerBuilder::with_noop_target`]
+//!
+//! - `no-exec`
+//!   - removes the code that starts processes at compile time, even when the `process` feature is enabled by another
+//!     crate, for deployments that must guarantee that the logger can never run a command
+//!   - a call target that is not a URL is then a [`ConfigError`] when the logger is built, except the
+//!     default `echo`, which is written to stdout instead, and the desktop notifications, the shell and the system
+//!     log are not available
+//!
+//! The `timestamps`, `http`, `process` and `files` features are enabled by default.  Without them only the [`Log`]
+//! implementation, the formatters and the targets that don't need them are built, e.g. for constrained environments
+//! that bring their own transport as a [`CallTarget`].
+//!
+//! # WebAssembly
+//!
+//! The crate can be built for `wasm32` targets, where there is no process to call and no built in HTTP client.  Log
+//! events can be sent to a URL with a closure set with [`CallLoggerBuilder::with_http_transport`], e.g. one that uses
+//! `fetch`, or delivered with [`CallLoggerBuilder::with_fn_target`].  The `sqlite`, `websocket`, `snmp` and
+//! `encryption` features are not available there.
+//!
+//! # Compile time filtering
+//!
+//! The `max_level_*` and `release_max_level_*` features of the [`log`] crate are respected, so that log events above
+//! those levels are removed at compile time and are never passed to the call target.
 //!
 //! # Example - Call default application (`echo`) for each log and default info level,
 //! `.new()` defaults to calling `echo` and therefore is analagous to `.with_call_target("echo")`
@@ -28,6 +89,7 @@
 //!
 //! # Example - Call an application for each log and write the result of the call to a file
 //! ```rust
+//! # #[cfg(all(feature = "process", not(feature = "no-exec")))] {
 //! let _ = call_logger::CallLogger::new()
 //!     .with_call_target("echo")
 //!     .to_file("test.log")
@@ -36,12 +98,12 @@
 //! log::info!("msg");
 //! # use std::fs::remove_file;
 //! # remove_file("test.log").unwrap()
+//! # }
 //! ```
 //!
 //! # Example - Send all output to Discord via their API
 //! ```rust
 //! // Get the API endpoint from an environment variable, URL should start with `https://discord.com/api/webhooks/`
-//! #[cfg(feature = "timestamps")]
 //! if let Ok(endpoint) = std::env::var("DISCORD_API") {
 //!     let _ = call_logger::CallLogger::new()
 //!         .with_call_target(endpoint)
@@ -49,7 +111,7 @@
 //!         .format(|timestamp, message, record| {
 //!             format!(
 //!                 "{{ \"content\": \"{} [{}] {} - {}\" }}",
-//!                 timestamp,
+//!                 timestamp.unwrap_or_default(),
 //!                 record.level(),
 //!                 record.module_path().unwrap_or_default(),
 //!                 message
@@ -63,20 +125,29 @@
 //! [gh-repo-examples]: https://github.com/a1ecbr0wn/call_logger/tree/main/examples
 
 use std::{
-    collections::{HashMap, VecDeque},
-    fmt::{Arguments, Debug},
-    fs::write,
-    path::{Path, PathBuf},
-    process::Command,
+    backtrace::Backtrace,
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{Arguments, Debug, Write as _},
+    io::{stdout, Write},
+    net::Ipv6Addr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{channel, Sender},
+        Arc, Mutex, OnceLock, RwLock,
+    },
+    time::{Duration, SystemTime},
 };
 
-use log::kv::{Error, Key, Value, VisitSource};
-use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+#[cfg(feature = "process")]
+use std::path::{Path, PathBuf};
+
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
 
 #[cfg(feature = "timestamps")]
 use chrono::{DateTime, Local, Utc};
 #[cfg(feature = "timestamps")]
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::UNIX_EPOCH;
 
 /// The format to use when outputting the timestamp of the log.  Timestamps are only part
 /// of the log output if the `timestamps` feature is enabled for `call_logger`/
@@ -89,9 +160,30 @@ pub enum TimestampFormat {
     Local,
 }
 
-/// The `CallLogger` implements [`Log`] and provides some simple builder methods to help configure what and how to log.
-/// Some sensible defaults are provided to perform the simple case of calling the `echo` program for all error level
-/// logs with a JSON representation of the logged item.  The logger then needs to be initialized (`.init()`) before use.
+/// The crates in the dependency tree of this crate, whose log events are filtered by
+/// [`CallLoggerBuilder::with_internal_dependency_level`]
+const DEPENDENCY_CRATES: &[&str] = &[
+    "chrono",
+    "ring",
+    "rusqlite",
+    "rustls",
+    "serde_json",
+    "tungstenite",
+    "ureq",
+    "webpki",
+    "webpki_roots",
+];
+
+/// The number of targets that the level filters are remembered for
+const LEVEL_CACHE_SIZE: usize = 256;
+
+/// The capacity that each thread's format buffer is kept within between log events
+const BUFFER_CAPACITY: usize = 64 * 1024;
+
+/// The `CallLogger` implements [`Log`], it is configured with the builder methods of [`CallLoggerBuilder`] which is
+/// returned by [`CallLogger::new`].  Some sensible defaults are provided to perform the simple case of calling the
+/// `echo` program for all error level logs with a JSON representation of the logged item.  The logger then needs to be
+/// initialized (`.init()`) before use.  Once it is built the configuration of a `CallLogger` cannot be changed.
 ///
 /// # Example - The simple logger that calls `echo`
 /// ```rust
@@ -105,8 +197,24 @@ pub struct CallLogger {
     /// Custom level filters per module
     levels: Vec<(String, log::LevelFilter)>,
 
-    /// The target call to make every time a logging event occurs
-    call_target: String,
+    /// The level filter of the crates that this crate depends on
+    dependency_level: Option<LevelFilter>,
+
+    /// The patterns that the targets of log events must match to be logged
+    target_filter: TargetFilter,
+
+    /// The rules that promote log events to a more severe level
+    escalations: Vec<(Box<EscalationRule>, Level)>,
+
+    /// The hours that log events are lowered to a less severe level
+    #[cfg(feature = "timestamps")]
+    downgrades: Vec<(HourRange, Level)>,
+
+    /// The most verbose of the level filters, capped by the `log` crate's `max_level_*` features
+    max_level: LevelFilter,
+
+    /// The level filters already looked up for each target
+    level_cache: RwLock<HashMap<String, LevelFilter>>,
 
     /// The format to be used to output the timestamp
     #[cfg(feature = "timestamps")]
@@ -116,41 +224,269 @@ pub struct CallLogger {
     #[cfg(feature = "timestamps")]
     format_string: Option<String>,
 
-    /// The file to write the output of the call to
-    file: Option<PathBuf>,
+    /// A closure that defines how the output is displayed
+    formatter: Box<ContextFormatter>,
+
+    /// The fields that are added to every log event
+    static_fields: Vec<(String, String)>,
+
+    /// The labels that levels are written as instead of their names, indexed by level
+    level_labels: [Option<String>; 5],
+
+    /// Where the default formatter writes the fields of a log event
+    field_layout: FieldLayout,
+
+    /// The least severe level that a backtrace is captured for
+    backtrace_level: Option<Level>,
+
+    /// A closure that returns the ids of the current trace and span
+    trace_context_provider: Option<Box<TraceContextProvider>>,
+
+    /// The closures that add fields to each log event before it is formatted
+    enrichers: Vec<Box<Enricher>>,
+
+    /// How the source file and line of a log event are rendered as a link
+    source_links: Option<SourceLinker>,
+
+    /// The key of the key-value pair whose value is delivered instead of the formatted output
+    payload_key: Option<String>,
+
+    /// The minimum time between payloads that are re-sent from a file
+    #[cfg(feature = "files")]
+    replay_interval: Option<Duration>,
+
+    /// The stages that the formatted output passes through before it is delivered
+    middleware: Arc<[Box<Middleware>]>,
+
+    /// Where and how the formatted output is delivered
+    dispatcher: Arc<Dispatcher>,
+
+    /// The other targets that the formatted output is delivered to
+    targets: Vec<BuiltTarget>,
+
+    /// The longest time that logging waits for a delivery before leaving it to finish in the background
+    latency_budget: Option<Duration>,
+
+    /// The number of threads that deliver in the background, or `0` to deliver while logging
+    worker_count: usize,
+
+    /// The order that log events are delivered in by the background threads
+    ordering: DeliveryOrder,
+
+    /// The threads that deliver in the background, started when they are first needed
+    workers: OnceLock<Workers>,
+
+    /// The number of log events that have been formatted
+    sequence: AtomicU64,
+
+    /// The name of the host that is logging
+    hostname: String,
+
+    /// Whether delivery has been paused with a [`CallLoggerHandle`]
+    pause: Arc<PauseState>,
+}
+
+/// The builder of a [`CallLogger`], returned by [`CallLogger::new`].  The builder methods configure what and how to
+/// log, then [`build`](CallLoggerBuilder::build) checks the configuration and returns the logger, or
+/// [`init`](CallLoggerBuilder::init) builds the logger and sets it up as the logger for the `log` crate.
+///
+/// # Example - Check the configuration before setting up the logger
+/// ```rust
+/// # use call_logger::CallLogger;
+/// let logger = CallLogger::new()
+///     .with_call_target("https://postman-echo.com/post")
+///     .build()
+///     .expect("the logger is misconfigured");
+/// logger.init();
+/// ```
+pub struct CallLoggerBuilder {
+    /// The default logging level filter
+    level: LevelFilter,
+
+    /// Custom level filters per module
+    levels: Vec<(String, log::LevelFilter)>,
+
+    /// The level filter of the crates that this crate depends on
+    dependency_level: Option<LevelFilter>,
+
+    /// The patterns that the targets of log events must match to be logged
+    target_filter: TargetFilter,
+
+    /// The rules that promote log events to a more severe level
+    escalations: Vec<(Box<EscalationRule>, Level)>,
+
+    /// The hours that log events are handled differently, and how, or why the hours couldn't be parsed
+    #[cfg(feature = "timestamps")]
+    quiet_hours: Vec<(Result<HourRange, String>, QuietHoursAction)>,
+
+    /// The format to be used to output the timestamp
+    #[cfg(feature = "timestamps")]
+    timestamp: TimestampFormat,
+
+    /// The format string if using custom timestamps
+    #[cfg(feature = "timestamps")]
+    format_string: Option<String>,
 
     /// A closure that defines how the output is displayed
-    formatter: Box<Formatter>,
+    formatter: Box<ContextFormatter>,
+
+    /// The fields that are added to every log event
+    static_fields: Vec<(String, String)>,
+
+    /// The labels that levels are written as instead of their names, indexed by level
+    level_labels: [Option<String>; 5],
+
+    /// Where the default formatter writes the fields of a log event
+    field_layout: FieldLayout,
+
+    /// The least severe level that a backtrace is captured for
+    backtrace_level: Option<Level>,
+
+    /// A closure that returns the ids of the current trace and span
+    trace_context_provider: Option<Box<TraceContextProvider>>,
+
+    /// The closures that add fields to each log event before it is formatted
+    enrichers: Vec<Box<Enricher>>,
+
+    /// How the source file and line of a log event are rendered as a link
+    source_links: Option<SourceLinker>,
+
+    /// The key of the key-value pair whose value is delivered instead of the formatted output
+    payload_key: Option<String>,
+
+    /// The closure that makes the key of the fingerprint of a log event
+    fingerprinter: Option<Arc<Fingerprinter>>,
+
+    /// The minimum time between payloads that are re-sent from a file
+    #[cfg(feature = "files")]
+    replay_interval: Option<Duration>,
 
-    /// Echo everything to console just before making the call, to aid debugging.
-    echo: bool,
+    /// The stages that the formatted output passes through before it is delivered
+    middleware: Vec<Box<Middleware>>,
+
+    /// The recipient that the formatted output is encrypted to after the middleware, or why it couldn't be parsed
+    #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+    encryption: Option<Result<encrypt::Recipient, String>>,
+
+    /// Where and how the formatted output is delivered
+    dispatcher: Dispatcher,
+
+    /// The other targets that the formatted output is delivered to
+    targets: Vec<Target>,
+
+    /// The longest time that logging waits for a delivery before leaving it to finish in the background
+    latency_budget: Option<Duration>,
+
+    /// The number of threads that deliver in the background, or `0` to deliver while logging
+    worker_count: usize,
+
+    /// The order that log events are delivered in by the background threads
+    ordering: DeliveryOrder,
+
+    /// Every log event must be delivered while logging, see [`CallLoggerBuilder::audit_mode`]
+    audit: bool,
+
+    /// The formatter only keeps the allowed fields, see [`CallLoggerBuilder::minimal_payload`]
+    minimal_payload: bool,
+
+    /// Where the logger reports its own errors
+    internal_sink: Option<InternalSink>,
+
+    /// The server that the server-sent events are served from, which is started when the logger is built
+    sse_server: Option<sse::SseServer>,
 }
 
 impl CallLogger {
-    /// Creates a new `CallLogger`, use this along with the builder methods and then call `init` to set up the logger.  
-    /// The default timestamp format is utc epoch (if the `timestamps` feature is enabled), and the default call app
-    /// that is called is `echo`.
+    /// Creates a new [`CallLoggerBuilder`], use this along with the builder methods and then call `init` to set up the
+    /// logger.  The default timestamp format is utc epoch (if the `timestamps` feature is enabled), and the default
+    /// call app that is called is `echo`.
     ///
     /// # Example
     /// ```rust
     /// # use call_logger::CallLogger;
     /// CallLogger::new().init();
     /// ```
-    pub fn new() -> CallLogger {
-        CallLogger {
-            level: LevelFilter::Trace,
-            levels: Vec::new(),
+    // `new` is kept as the way to start configuring a logger, as it was before the builder was split out
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> CallLoggerBuilder {
+        CallLoggerBuilder::new()
+    }
+
+    /// Creates a new [`CallLoggerBuilder`], the same as [`CallLogger::new`].
+    ///
+    /// # Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// let logger = CallLogger::builder().build();
+    /// ```
+    pub fn builder() -> CallLoggerBuilder {
+        CallLoggerBuilder::new()
+    }
 
-            // default to calling echo which will output the log event to console
-            call_target: "echo".into(),
+    /// Sets this logger up as the logger for the `log` crate.  It also sets the `log` crate's maximum level to the most
+    /// verbose of the level filters, so that log events that would be filtered out are discarded by the logging macros
+    /// without calling the logger.
+    ///
+    /// # Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// if let Ok(logger) = CallLogger::new().build() {
+    ///     logger.init();
+    /// }
+    /// ```
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        let max_level = self.max_level;
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
 
+impl CallLoggerBuilder {
+    /// Creates a new `CallLoggerBuilder` with the default configuration, the same as [`CallLogger::new`].
+    ///
+    /// # Example
+    /// ```rust
+    /// # use call_logger::CallLoggerBuilder;
+    /// CallLoggerBuilder::new().init();
+    /// ```
+    pub fn new() -> CallLoggerBuilder {
+        CallLoggerBuilder {
+            level: LevelFilter::Trace,
+            levels: Vec::new(),
+            dependency_level: None,
+            target_filter: TargetFilter::default(),
+            escalations: Vec::new(),
+            #[cfg(feature = "timestamps")]
+            quiet_hours: Vec::new(),
             #[cfg(feature = "timestamps")]
             timestamp: TimestampFormat::Utc,
             #[cfg(feature = "timestamps")]
             format_string: None,
-            file: None,
-            echo: false,
-            formatter: Box::new(Self::json_formatter),
+            formatter: Box::new(CallLogger::json_formatter),
+            static_fields: Vec::new(),
+            level_labels: Default::default(),
+            field_layout: FieldLayout::default(),
+            backtrace_level: None,
+            trace_context_provider: None,
+            enrichers: Vec::new(),
+            source_links: None,
+            payload_key: None,
+            fingerprinter: None,
+            #[cfg(feature = "files")]
+            replay_interval: None,
+            middleware: Vec::new(),
+            #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+            encryption: None,
+            dispatcher: Dispatcher::new(),
+            targets: Vec::new(),
+            latency_budget: None,
+            worker_count: 0,
+            ordering: DeliveryOrder::Concurrent,
+            audit: false,
+            minimal_payload: false,
+            internal_sink: None,
+            sse_server: None,
         }
     }
 
@@ -166,14 +502,34 @@ impl CallLogger {
     /// ```
     #[inline]
     #[must_use = "You must call init() before logging"]
-    pub fn with_level(mut self, level: LevelFilter) -> CallLogger {
+    pub fn with_level(mut self, level: LevelFilter) -> CallLoggerBuilder {
         self.level = level;
-        log::set_max_level(self.level);
+        self
+    }
+
+    /// The maximum log level that would be logged for the crates that this crate depends on, e.g. `ureq` and `rustls`,
+    /// whatever their target string, so that calling the call target does not log more events to call it with, and
+    /// their noise can be turned off without a level for each of them.  A level set for a module with
+    /// [`CallLoggerBuilder::with_level_for`] takes precedence over this.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// # use log::LevelFilter;
+    /// CallLogger::new()
+    ///     .with_internal_dependency_level(LevelFilter::Off)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_internal_dependency_level(mut self, level: LevelFilter) -> CallLoggerBuilder {
+        self.dependency_level = Some(level);
         self
     }
 
     /// The maximum log level that would be logged for a module where the target string is found in the log item's
-    /// target or module path.
+    /// target or module path.  When more than one of these target strings is found, the level for the longest one is
+    /// used.
     ///
     /// # Example matching a module name
     /// ```rust
@@ -198,11 +554,55 @@ impl CallLogger {
     #[must_use = "You must call init() before logging"]
     pub fn with_level_for<T: Into<String>>(mut self, target: T, level: log::LevelFilter) -> Self {
         self.levels.push((target.into(), level));
+        // the longest (most specific) target is checked first, a stable sort keeps the order of equal lengths
+        self.levels
+            .sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        self
+    }
+
+    /// Promotes a log event to `level` when the closure returns `true` for it, e.g. based on its key-value pairs, so
+    /// that it is filtered, formatted and routed as if it had been logged at that level.  Log events are only ever
+    /// made more severe, and when more than one rule matches the most severe level is used.  The rules are only
+    /// checked for log events that reach the logger, so the `log` crate's maximum level should allow the levels that
+    /// are escalated from, see [`CallLogger::init`].
+    ///
+    /// Example - Escalate to an error after 3 attempts
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// # use log::Level;
+    /// CallLogger::new()
+    ///     .escalate_if(
+    ///         |record| {
+    ///             record
+    ///                 .key_values()
+    ///                 .get("attempts".into())
+    ///                 .and_then(|attempts| attempts.to_u64())
+    ///                 .is_some_and(|attempts| attempts > 3)
+    ///         },
+    ///         Level::Error,
+    ///     )
+    ///     .init();
+    /// log::warn!(attempts = 4; "retrying");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn escalate_if<F>(mut self, rule: F, level: Level) -> CallLoggerBuilder
+    where
+        F: Fn(&Record) -> bool + Sync + Send + 'static,
+    {
+        self.escalations.push((Box::new(rule), level));
         self
     }
 
     /// Sets the command line application, script or URL that is called and passed the log details.
     ///
+    /// The arguments of an application are separated by whitespace.  The formatted output of the log event is passed
+    /// to the application as a single argument, in place of an argument that is exactly `{}` or after the other
+    /// arguments if there isn't one.  The output is not escaped and is never parsed by a shell, but it is whatever was
+    /// logged, so it could start with `-` and be taken as an option.  Use
+    /// [`CallLoggerBuilder::with_options_terminator`] or [`CallLoggerBuilder::payload_via_stdin`] if the application
+    /// could be confused by this.
+    ///
     /// Example - Call an application with parameters
     /// ```rust
     /// # use call_logger::CallLogger;
@@ -223,19 +623,151 @@ impl CallLogger {
     /// ```
     #[inline]
     #[must_use = "You must call init() before logging"]
-    pub fn with_call_target<T>(mut self, call_target: T) -> CallLogger
+    pub fn with_call_target<T>(mut self, call_target: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        self.dispatcher.call_target = call_target.into();
+        #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+        {
+            self.dispatcher.exec_path = None;
+        }
+        self
+    }
+
+    /// Names the call target, so that formatters can say where a log event is going with
+    /// [`FormatContext::channel`] and [`FormatContext::channels`] instead of the call target itself, which can hold
+    /// a URL with a key in it.  Targets are named with [`Target::with_channel_name`].
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://events.pagerduty.com/v2/enqueue")
+    ///     .with_channel_name("pager")
+    ///     .format_with(|context| {
+    ///         format!("{{\"channel\":\"{}\",\"msg\":\"{}\"}}", context.channel(), context.message())
+    ///     })
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_channel_name<T>(mut self, name: T) -> CallLoggerBuilder
     where
         T: Into<String>,
     {
-        self.call_target = call_target.into();
+        self.dispatcher.channel_name = Some(name.into());
+        self
+    }
+
+    /// Sends the formatted output of each log event down a channel instead of calling an application or URL, so that
+    /// another part of the application can consume the log events, e.g. to build a custom shipper.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// let (sender, receiver) = std::sync::mpsc::channel();
+    /// CallLogger::new()
+    ///     .with_channel_target(sender)
+    ///     .init();
+    /// log::info!("msg");
+    /// # let _ = receiver.try_recv();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_channel_target(self, sender: Sender<String>) -> CallLoggerBuilder {
+        let sender = Mutex::new(sender);
+        self.with_fn_target(move |payload, _| {
+            sender
+                .lock()
+                .map_err(|x| x.to_string())?
+                .send(payload.to_string())?;
+            Ok(())
+        })
+    }
+
+    /// Passes the formatted output of each log event, along with the record itself, to a closure instead of calling an
+    /// application or URL.  This allows any Rust delivery logic to be plugged in, e.g. an SDK client the application
+    /// already holds, while still using the level filtering and formatting of the logger.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_fn_target(|payload, record| {
+    ///         println!("{} {payload}", record.level());
+    ///         Ok(())
+    ///     })
+    ///     .init();
+    /// log::info!("msg");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_fn_target<F>(mut self, target: F) -> CallLoggerBuilder
+    where
+        F: Fn(&str, &Record) -> Result<(), BoxError> + Sync + Send + 'static,
+    {
+        self.dispatcher.target = Some(Box::new(target));
+        self
+    }
+
+    /// Delivers the formatted output of each log event, along with the record itself, to a [`CallTarget`] instead of
+    /// calling an application or URL.  This is how a transport of your own is plugged in, e.g. when the crate is built
+    /// without the `http` and `process` features.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::{BoxError, CallLogger, CallTarget};
+    /// # use log::Record;
+    /// struct Discard;
+    ///
+    /// impl CallTarget for Discard {
+    ///     fn call(&self, _payload: &str, _record: &Record) -> Result<(), BoxError> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// CallLogger::new()
+    ///     .with_custom_target(Discard)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_custom_target<T>(mut self, target: T) -> CallLoggerBuilder
+    where
+        T: CallTarget + 'static,
+    {
+        self.dispatcher.target = Some(Box::new(target));
         self
     }
 
+    /// Writes the formatted output of each log event as a line directly to stdout rather than calling `echo`, which is
+    /// far cheaper than spawning a process per log event and is what container log collectors expect.  Each line is
+    /// written to the locked, line buffered stdout so that lines from different threads are not interleaved.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .json_stdout()
+    ///     .init();
+    /// log::info!("msg");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn json_stdout(self) -> CallLoggerBuilder {
+        self.with_fn_target(|payload, _| {
+            let mut stdout = stdout().lock();
+            writeln!(stdout, "{payload}")?;
+            Ok(())
+        })
+    }
+
     /// Sets the timestamp to the number of milliseconds since the epoch.
     #[inline]
     #[must_use = "You must call init() before logging"]
     #[cfg(feature = "timestamps")]
-    pub fn with_epoch_ms_timestamp(mut self) -> CallLogger {
+    pub fn with_epoch_ms_timestamp(mut self) -> CallLoggerBuilder {
         self.timestamp = TimestampFormat::UtcEpochMs;
         self
     }
@@ -244,7 +776,7 @@ impl CallLogger {
     #[inline]
     #[must_use = "You must call init() before logging"]
     #[cfg(feature = "timestamps")]
-    pub fn with_epoch_us_timestamp(mut self) -> CallLogger {
+    pub fn with_epoch_us_timestamp(mut self) -> CallLoggerBuilder {
         self.timestamp = TimestampFormat::UtcEpochUs;
         self
     }
@@ -253,7 +785,7 @@ impl CallLogger {
     #[inline]
     #[must_use = "You must call init() before logging"]
     #[cfg(feature = "timestamps")]
-    pub fn with_utc_timestamp(mut self) -> CallLogger {
+    pub fn with_utc_timestamp(mut self) -> CallLoggerBuilder {
         self.timestamp = TimestampFormat::Utc;
         self
     }
@@ -262,7 +794,7 @@ impl CallLogger {
     #[inline]
     #[must_use = "You must call init() before logging"]
     #[cfg(feature = "timestamps")]
-    pub fn with_local_timestamp(mut self) -> CallLogger {
+    pub fn with_local_timestamp(mut self) -> CallLoggerBuilder {
         self.timestamp = TimestampFormat::Local;
         self
     }
@@ -276,7 +808,7 @@ impl CallLogger {
     /// # use std::time::SystemTime;
     /// # use chrono::DateTime;
     /// # use call_logger::{CallLogger, TimestampFormat};
-    /// let logger = CallLogger::default()
+    /// let logger = CallLogger::new()
     ///     .with_formatted_timestamp(TimestampFormat::Utc,"%H:%M:%S %d/%m/%Y %z");
     /// ```
     #[inline]
@@ -286,7 +818,7 @@ impl CallLogger {
         mut self,
         timestamp_format: TimestampFormat,
         format_string: T,
-    ) -> CallLogger
+    ) -> CallLoggerBuilder
     where
         T: Into<String>,
     {
@@ -295,7 +827,10 @@ impl CallLogger {
         self
     }
 
-    /// Writes each call to console before making the call, use for debugging.
+    /// Writes each call to console before making the call, use for debugging.  After the call, the size of the
+    /// payload, how long the call took and its outcome are written, e.g. `Called: 57 bytes in 12.3ms, HTTP status 200`,
+    /// and an application is waited for so that its exit code can be written.  See [`CallLoggerBuilder::echo_sampled`]
+    /// and [`CallLoggerBuilder::echo_errors_only`] to write fewer of them.
     ///
     /// Example
     /// ```rust
@@ -306,12 +841,13 @@ impl CallLogger {
     /// ```
     #[inline]
     #[must_use = "You must call init() before logging"]
-    pub fn echo(mut self) -> CallLogger {
-        self.echo = true;
+    pub fn echo(mut self) -> CallLoggerBuilder {
+        self.dispatcher.echo.get_or_insert_with(Echo::default);
         self
     }
 
-    /// Write the output of the call to a file
+    /// Write the output of the call to a file.  The output of each call is appended to the file, see
+    /// [`CallLoggerBuilder::with_file_flush_interval`] to buffer the writes.
     ///
     /// Example
     /// ```
@@ -322,89 +858,997 @@ impl CallLogger {
     /// ```
     #[inline]
     #[must_use = "You must call init() before logging"]
-    pub fn to_file<P>(mut self, file: P) -> CallLogger
+    #[cfg(feature = "process")]
+    pub fn to_file<P>(mut self, file: P) -> CallLoggerBuilder
     where
         P: AsRef<Path>,
     {
-        self.file = Some(PathBuf::from(file.as_ref()));
+        self.dispatcher.file = Some(PathBuf::from(file.as_ref()));
         self
     }
 
-    /// Sets the formatter of this logger. The closure should accept a formatted
-    /// value for a timestamp, a message and a log record, and return a `String`
-    /// representation of the message that has been formatted.
-    ///
-    /// [`fmt::Arguments`]: https://doc.rust-lang.org/std/fmt/struct.Arguments.html
-    ///
-    /// Example usage:
+    /// Sets a closure that turns the output of a call to an application into what is written to the file set by
+    /// [`CallLoggerBuilder::to_file`], instead of the application's stdout.  The closure is passed the stdout, the
+    /// stderr and the exit status of the call, e.g. to add a timestamp or keep only the last line.
     ///
+    /// Example - Write the exit code and the last line of the output
     /// ```
-    /// let _ = call_logger::CallLogger::new()
-    ///     .format(|timestamp, message, record| {
-    ///         format!(
-    ///             "{{ \"content\": \"{} [{}] {} - {}\" }}",
-    ///             timestamp,
-    ///             record.level(),
-    ///             record.module_path().unwrap_or_default(),
-    ///             message
-    ///         )
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("echo")
+    ///     .to_file("my_app.log")
+    ///     .map_output(|stdout, _stderr, status| {
+    ///         format!("{} {}\n", status.code().unwrap_or(-1), stdout.lines().last().unwrap_or_default())
     ///     })
     ///     .init();
-    /// log::info!("msg");
     /// ```
     #[inline]
-    #[cfg(feature = "timestamps")]
-    pub fn format<F>(mut self, formatter: F) -> Self
+    #[must_use = "You must call init() before logging"]
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    pub fn map_output<F>(mut self, output_mapper: F) -> CallLoggerBuilder
     where
-        F: Fn(String, &Arguments, &log::Record) -> String + Sync + Send + 'static,
+        F: Fn(&str, &str, std::process::ExitStatus) -> String + Sync + Send + 'static,
     {
-        self.formatter = Box::new(formatter);
+        self.dispatcher.output_mapper = Some(Box::new(output_mapper));
         self
     }
 
-    /// Sets the formatter of this logger. The closure should accept a message
-    /// and a log record, and return a `String` representation of the message
-    /// that has been formatted.
-    ///
-    /// [`fmt::Arguments`]: https://doc.rust-lang.org/std/fmt/struct.Arguments.html
-    ///
-    /// Example usage:
+    /// Calls the application `command` for each log event, as per [`CallLoggerBuilder::with_call_target`], and posts
+    /// its output to `url`, so that a script or a tool such as `jq` can enrich or filter log events before they are
+    /// sent to a webhook.  Nothing is posted if the application outputs nothing, and the call fails if the application
+    /// exits with an error.
     ///
+    /// Example - Wrap the message in the body that Discord expects
     /// ```
-    /// let _ = call_logger::CallLogger::new()
-    ///     .format(|message, record| {
-    ///         format!(
-    ///             "{{ \"content\": \"[{}] {} - {}\" }}",
-    ///             record.level(),
-    ///             record.module_path().unwrap_or_default(),
-    ///             message
-    ///         )
-    ///     })
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .pipe_process_to_http(
+    ///         "jq -nc --argjson event {} $event|{content:.msg}",
+    ///         "https://postman-echo.com/post",
+    ///     )
     ///     .init();
-    /// log::info!("msg");
     /// ```
     #[inline]
-    #[cfg(not(feature = "timestamps"))]
-    pub fn format<F>(mut self, formatter: F) -> Self
+    #[must_use = "You must call init() before logging"]
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    pub fn pipe_process_to_http<C, U>(mut self, command: C, url: U) -> CallLoggerBuilder
     where
-        F: Fn(&Arguments, &log::Record) -> String + Sync + Send + 'static,
+        C: Into<String>,
+        U: Into<String>,
     {
-        self.formatter = Box::new(formatter);
-        self
+        self.dispatcher.pipe_url = Some(url.into());
+        self.with_call_target(command)
     }
 
-    /// This needs to be called after the builder has set up the logger.
+    /// Passes `--` before the formatted output of the log event when calling an application, so that output that
+    /// starts with `-` is never taken as an option by applications that follow this convention.
     ///
-    /// # Example
+    /// Example
     /// ```
     /// # use call_logger::CallLogger;
-    /// CallLogger::new().init();
+    /// CallLogger::new()
+    ///     .with_call_target("logger -t my_app")
+    ///     .with_options_terminator()
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    #[cfg(feature = "process")]
+    pub fn with_options_terminator(mut self) -> CallLoggerBuilder {
+        self.dispatcher.options_terminator = true;
+        self
+    }
+
+    /// Writes the formatted output of the log event to the stdin of the application instead of passing it as an
+    /// argument, so that it can't be confused with the arguments and isn't limited by the maximum length of a command
+    /// line.  A `{}` argument in the call target is removed.
+    ///
+    /// Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("logger -t my_app")
+    ///     .payload_via_stdin()
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    #[cfg(feature = "process")]
+    pub fn payload_via_stdin(mut self) -> CallLoggerBuilder {
+        self.dispatcher.payload_delivery = dispatch::PayloadDelivery::Stdin;
+        self
+    }
+
+    /// Passes the formatted output of the log event to the application in the environment variable `name` instead of
+    /// as an argument, for hook scripts that read their input from the environment.  A `{}` argument in the call
+    /// target is removed.  The payload can't contain a nul character and is limited by the size of the environment
+    /// that the system allows.
+    ///
+    /// Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("printenv LOG_EVENT")
+    ///     .payload_via_env("LOG_EVENT")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    #[cfg(feature = "process")]
+    pub fn payload_via_env<T>(mut self, name: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        self.dispatcher.payload_delivery = dispatch::PayloadDelivery::Env(name.into());
+        self
+    }
+
+    /// Buffers the output of calls that is written to the file set by [`CallLoggerBuilder::to_file`], writing it to the
+    /// file at most once per `interval`, when the buffer is full, or when the logger is flushed.  This greatly reduces
+    /// the number of writes made when logging frequently.  By default the output of each call is written straight away.
+    ///
+    /// Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// # use std::time::Duration;
+    /// CallLogger::new()
+    ///     .to_file("my_app.log")
+    ///     .with_file_flush_interval(Duration::from_secs(1))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    #[cfg(feature = "process")]
+    pub fn with_file_flush_interval(mut self, interval: Duration) -> CallLoggerBuilder {
+        self.dispatcher.file_flush_interval = interval;
+        self
+    }
+
+    /// Sets the formatter of this logger. The closure should accept the formatted
+    /// value of the timestamp, a message and a log record, and return a `String`
+    /// representation of the message that has been formatted.  The timestamp is
+    /// `None` when the `timestamps` feature is disabled, so the closure has the
+    /// same signature whichever features are enabled.
+    ///
+    /// [`fmt::Arguments`]: https://doc.rust-lang.org/std/fmt/struct.Arguments.html
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// let _ = call_logger::CallLogger::new()
+    ///     .format(|timestamp, message, record| {
+    ///         format!(
+    ///             "{{ \"content\": \"{} [{}] {} - {}\" }}",
+    ///             timestamp.unwrap_or_default(),
+    ///             record.level(),
+    ///             record.module_path().unwrap_or_default(),
+    ///             message
+    ///         )
+    ///     })
+    ///     .init();
+    /// log::info!("msg");
+    /// ```
+    #[inline]
+    pub fn format<F>(mut self, formatter: F) -> Self
+    where
+        F: Fn(Option<String>, &Arguments, &log::Record) -> String + Sync + Send + 'static,
+    {
+        self.formatter = Box::new(move |buffer, context| {
+            let timestamp = context.timestamp().map(str::to_string);
+            buffer.push_str(&formatter(timestamp, context.message(), context.record()))
+        });
+        self
+    }
+
+    /// Sets a formatter of this logger that writes into a buffer instead of returning a new `String`.  The closure
+    /// should accept the buffer, the formatted value of the timestamp, a message and a log record, and append the
+    /// formatted message to the buffer.  Each thread reuses its own buffer, so this avoids an allocation for every
+    /// log event when logging at a high rate.  As with [`CallLoggerBuilder::format`], the timestamp is `None` when
+    /// the `timestamps` feature is disabled.
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// use std::fmt::Write;
+    /// let _ = call_logger::CallLogger::new()
+    ///     .format_into(|buffer, timestamp, message, record| {
+    ///         let _ = write!(buffer, "{} [{}] {}", timestamp.unwrap_or_default(), record.level(), message);
+    ///     })
+    ///     .init();
+    /// log::info!("msg");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn format_into<F>(mut self, formatter: F) -> Self
+    where
+        F: Fn(&mut String, Option<String>, &Arguments, &log::Record) + Sync + Send + 'static,
+    {
+        self.formatter = Box::new(move |buffer, context| {
+            let timestamp = context.timestamp().map(str::to_string);
+            formatter(buffer, timestamp, context.message(), context.record())
+        });
+        self
+    }
+
+    /// Sets the formatter of this logger to a closure that is passed a [`FormatContext`] with the details of the log
+    /// event, e.g. the timestamp, message, record, key-value pairs, static fields, sequence number and hostname, and
+    /// returns the formatted `String`.  The closure has the same signature whichever features are enabled, and more
+    /// details can be added to the context without changing it.
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// let _ = call_logger::CallLogger::new()
+    ///     .with_static_field("service", "billing")
+    ///     .format_with(|context| {
+    ///         format!(
+    ///             "{} #{} [{}] {}",
+    ///             context.hostname(),
+    ///             context.sequence(),
+    ///             context.record().level(),
+    ///             context.message()
+    ///         )
+    ///     })
+    ///     .init();
+    /// log::info!("msg");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn format_with<F>(mut self, formatter: F) -> CallLoggerBuilder
+    where
+        F: Fn(&FormatContext) -> String + Sync + Send + 'static,
+    {
+        self.formatter = Box::new(move |buffer, context| buffer.push_str(&formatter(context)));
+        self
+    }
+
+    /// Adds a field that is part of every log event, e.g. the name of the service or the environment.  The default
+    /// formatter adds static fields to the JSON object after the key-value pairs of the log event, and they are
+    /// passed to a formatter set with [`CallLoggerBuilder::format_with`].
+    ///
+    /// Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_static_field("service", "billing")
+    ///     .with_static_field("env", "production")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_static_field<K, V>(mut self, key: K, value: V) -> CallLoggerBuilder
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.static_fields.push((key.into(), value.into()));
+        self
+    }
+
+    /// Writes a level as `label` instead of its name, e.g. to match the severities of syslog or the level names of
+    /// another language.  The label is used by the default formatter and is passed to a formatter set with
+    /// [`CallLoggerBuilder::format_with`].
+    ///
+    /// Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// # use log::Level;
+    /// CallLogger::new()
+    ///     .map_level(Level::Warn, "notice")
+    ///     .map_level(Level::Trace, "debug")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn map_level<T>(mut self, level: Level, label: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        self.level_labels[level as usize - 1] = Some(label.into());
+        self
+    }
+
+    /// Writes every level as the label returned by the closure instead of its name.  The closure is called once for
+    /// each level when this is set, not for each log event.
+    ///
+    /// Example - Bunyan numeric levels
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// # use log::Level;
+    /// CallLogger::new()
+    ///     .map_levels(|level| match level {
+    ///         Level::Error => "50",
+    ///         Level::Warn => "40",
+    ///         Level::Info => "30",
+    ///         Level::Debug => "20",
+    ///         Level::Trace => "10",
+    ///     })
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn map_levels<F, T>(mut self, mapper: F) -> CallLoggerBuilder
+    where
+        F: Fn(Level) -> T,
+        T: Into<String>,
+    {
+        for level in Level::iter() {
+            self = self.map_level(level, mapper(level));
+        }
+        self
+    }
+
+    /// Writes the levels as `labels` instead of their names, in the order error, warn, info, debug and trace, e.g. to
+    /// match the conventions of the service that log events are sent to.  The `log` crate has no fatal level, so an
+    /// error is the most severe.  This is the same as calling [`CallLoggerBuilder::map_level`] for each level.
+    ///
+    /// Example - Localized labels
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_level_labels(["FEHLER", "WARNUNG", "INFO", "DEBUG", "TRACE"])
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_level_labels<T>(mut self, labels: [T; 5]) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        for (level, label) in Level::iter().zip(labels) {
+            self = self.map_level(level, label);
+        }
+        self
+    }
+
+    /// Captures a backtrace of where a log event at `level` or a more severe level was logged, and includes it in the
+    /// formatted output as `backtrace`, so that alerts have enough context to debug the error.  The backtrace is
+    /// captured whether or not `RUST_BACKTRACE` is set, which is slow, so it should only be used for levels that are
+    /// logged rarely.  It is passed to a formatter set with [`CallLoggerBuilder::format_with`].
+    ///
+    /// Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// # use log::Level;
+    /// CallLogger::new()
+    ///     .with_backtraces(Level::Error)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_backtraces(mut self, level: Level) -> CallLoggerBuilder {
+        self.backtrace_level = Some(level);
+        self
+    }
+
+    /// Sets a closure that returns the ids of the current trace and span, e.g. from OpenTelemetry, so that log events
+    /// can be correlated with traces.  The closure is called for each log event and returns `None` when there is no
+    /// current span.  The default formatter adds the ids as `trace_id` and `span_id`, and they are passed to a
+    /// formatter set with [`CallLoggerBuilder::format_with`].
+    ///
+    /// Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_trace_context_provider(|| {
+    ///         Some(("4bf92f3577b34da6a3ce929d0e0e4736".to_string(), "00f067aa0ba902b7".to_string()))
+    ///     })
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_trace_context_provider<F>(mut self, provider: F) -> CallLoggerBuilder
+    where
+        F: Fn() -> Option<(String, String)> + Sync + Send + 'static,
+    {
+        self.trace_context_provider = Some(Box::new(provider));
+        self
+    }
+
+    /// Adds a closure that is called with each log event before it is formatted, and adds fields to it, e.g. the id
+    /// of the current user or the path of the current request from a task-local, so that every payload has them
+    /// without a custom formatter.  The fields are added after the fields of [`push_context`], so every formatter that
+    /// writes those writes these too, and they are passed to a formatter set with [`CallLoggerBuilder::format_with`]
+    /// as [`FormatContext::scoped_fields`].  The closures are called in the order they are added.
+    ///
+    /// Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .enrich_with(|record, fields| {
+    ///         fields.push(("pid".to_string(), std::process::id().to_string()));
+    ///         if record.level() == log::Level::Error {
+    ///             fields.push(("alert".to_string(), "true".to_string()));
+    ///         }
+    ///     })
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn enrich_with<F>(mut self, enricher: F) -> CallLoggerBuilder
+    where
+        F: Fn(&Record, &mut Vec<(String, String)>) + Sync + Send + 'static,
+    {
+        self.enrichers.push(Box::new(enricher));
+        self
+    }
+
+    /// Sets a closure that is called when a log event can't be delivered, instead of printing the failure.  The
+    /// closure is passed a [`CallError`] with the call target, the kind of failure and the start of the formatted
+    /// output of the log event.  It is also used by the targets added with [`CallLoggerBuilder::with_target`].
+    ///
+    /// Example
+    /// ```
+    /// # use call_logger::{CallErrorKind, CallLogger};
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .on_call_error(|error| match error.kind {
+    ///         CallErrorKind::Http(429) => eprintln!("{} is rate limiting", error.target),
+    ///         _ => eprintln!("could not log {}: {error}", error.payload_snippet),
+    ///     })
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn on_call_error<F>(mut self, error_handler: F) -> CallLoggerBuilder
+    where
+        F: Fn(&CallError) + Sync + Send + 'static,
+    {
+        self.dispatcher.error_handler = Some(Arc::new(error_handler));
+        self
+    }
+
+    /// Sets a closure that can customize the HTTP request made when the call target is a URL.  The closure is
+    /// passed the level of the record being logged and the request that is about to be sent, and returns the request
+    /// that should be sent instead, e.g. with extra headers or query parameters.  A `Content-Type` of
+    /// `application/json` is added if the closure does not set one.  The request should be changed rather than
+    /// replaced, as a new request, e.g. one made with `ureq::post`, is not made with the agent of the logger, so it
+    /// loses the settings of [`CallLoggerBuilder::with_dns_ttl`] and [`CallLoggerBuilder::prefer_ip`].
+    ///
+    /// Example - Add a priority header for warnings and above and flag errors for escalation
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// # use log::Level;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .customize_request(|level, request| match level {
+    ///         Level::Error => request.set("X-Priority", "high").query("escalate", "true"),
+    ///         Level::Warn => request.set("X-Priority", "high"),
+    ///         _ => request,
+    ///     })
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub fn customize_request<F>(mut self, customizer: F) -> CallLoggerBuilder
+    where
+        F: Fn(Level, ureq::Request) -> ureq::Request + Sync + Send + 'static,
+    {
+        self.dispatcher.request_customizer = Some(Box::new(customizer));
+        self
+    }
+
+    /// Sets a closure that sends the HTTP request when the call target is a URL, instead of the built in HTTP client.
+    /// The closure is passed the URL, the headers and the body of the request.  A `Content-Type` of
+    /// `application/json` is in the headers unless another one has been set.  The closure set with
+    /// [`customize_request`](CallLoggerBuilder::customize_request) is not used when there is a transport.
+    ///
+    /// There is no built in HTTP client on `wasm32` targets, so this is how log events are sent to a URL from a
+    /// browser or a worker, e.g. with a closure that starts a `fetch` using `web-sys`.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_http_transport(|url, headers, body| {
+    ///         println!("POST {url} {headers:?} {body}");
+    ///         Ok(())
+    ///     })
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_http_transport<F>(mut self, transport: F) -> CallLoggerBuilder
+    where
+        F: Fn(&str, &[(&str, &str)], &str) -> Result<(), BoxError> + Sync + Send + 'static,
+    {
+        self.dispatcher.http_transport = Some(Box::new(transport));
+        self
+    }
+
+    /// Adds a stage that the formatted output of each log event passes through before it is delivered, e.g. to redact,
+    /// enrich or compress it.  The closure is passed the output of the formatter or of the previous stage, and returns
+    /// the output that should be delivered, or `None` to drop the log event.  Stages are run in the order they are
+    /// added.
+    ///
+    /// Example - Redact a password and drop health checks
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_middleware(|payload| Some(payload.replace("hunter2", "********")))
+    ///     .with_middleware(|payload| (!payload.contains("/health")).then_some(payload))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_middleware<F>(mut self, middleware: F) -> CallLoggerBuilder
+    where
+        F: Fn(String) -> Option<String> + Sync + Send + 'static,
+    {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Sets the longest time that logging a log event waits for it to be delivered.  If the delivery takes longer than
+    /// the budget, e.g. because of a slow DNS lookup, it is left to finish on a background thread and logging carries
+    /// on, so that latency sensitive applications are not held up by a slow call target.  Deliveries that finish in
+    /// the background are made with a copy of the record that does not have its key-value pairs, these are still in
+    /// the formatted output.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// # use std::time::Duration;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_latency_budget(Duration::from_millis(50))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_latency_budget(mut self, budget: Duration) -> CallLoggerBuilder {
+        self.latency_budget = Some(budget);
+        self
+    }
+
+    /// Delivers log events on a pool of `workers` background threads instead of while logging, so that slow calls,
+    /// e.g. to a process target with [`CallLoggerBuilder::to_file`], are made concurrently rather than one after
+    /// another and do not hold up the application.  Log events may be delivered out of order unless
+    /// [`CallLoggerBuilder::with_module_ordering`] is also used.  Deliveries are made with a copy of the record that
+    /// does not have its key-value pairs, these are still in the formatted output.  Call `log::logger().flush()` to
+    /// wait for the queued log events to be delivered.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("echo")
+    ///     .with_workers(4)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_workers(mut self, workers: usize) -> CallLoggerBuilder {
+        self.worker_count = workers;
+        self
+    }
+
+    /// Delivers all of the log events from the same module on the same background thread, so that they are delivered
+    /// in the order they were logged, while log events from different modules are still delivered concurrently.  This
+    /// only has an effect when [`CallLoggerBuilder::with_workers`] is used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_workers(4)
+    ///     .with_module_ordering()
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_module_ordering(mut self) -> CallLoggerBuilder {
+        self.ordering = DeliveryOrder::Module;
+        self
+    }
+
+    /// Guarantees that log events are delivered in the order they were logged when they are delivered in the
+    /// background, by delivering all of them on a single background thread, instead of concurrently as per
+    /// [`CallLoggerBuilder::with_workers`].  Use this when the call target cannot cope with log events that are out of
+    /// order.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .with_workers(4)
+    ///     .preserve_order()
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn preserve_order(mut self) -> CallLoggerBuilder {
+        self.ordering = DeliveryOrder::Preserved;
+        self
+    }
+
+    /// Checks the configuration and builds the logger.  An error is returned if the call target is empty or is not a
+    /// valid URL when it looks like one, or if options have been set that can't be used together or with the call
+    /// target.
+    ///
+    /// # Example
     /// ```
-    pub fn init(self) -> Result<(), SetLoggerError> {
-        log::set_boxed_logger(Box::new(self))?;
+    /// # use call_logger::{CallLogger, ConfigError};
+    /// let result = CallLogger::new().with_call_target("https://").build();
+    /// assert!(matches!(result, Err(ConfigError::InvalidUrl(_))));
+    /// ```
+    pub fn build(mut self) -> Result<CallLogger, ConfigError> {
+        // the targets that quiet hours are routed to are set up and checked in the same way as the other targets
+        #[cfg(feature = "timestamps")]
+        let downgrades = self
+            .set_up_quiet_hours()
+            .map_err(ConfigError::InvalidQuietHours)?;
+        // the other targets are restricted to the same applications and URLs, which is checked with the rest of the
+        // options
+        for target in &mut self.targets {
+            #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+            if target.dispatcher.allowed_programs.is_none() {
+                target.dispatcher.allowed_programs = self.dispatcher.allowed_programs.clone();
+            }
+            if target.dispatcher.allowed_schemes.is_none() {
+                target.dispatcher.allowed_schemes = self.dispatcher.allowed_schemes.clone();
+            }
+            if target.dispatcher.allowed_hosts.is_none() {
+                target.dispatcher.allowed_hosts = self.dispatcher.allowed_hosts.clone();
+            }
+        }
+        for dispatcher in std::iter::once(&mut self.dispatcher)
+            .chain(self.targets.iter_mut().map(|target| &mut target.dispatcher))
+        {
+            dispatcher.restrict_fallback();
+        }
+        self.validate()?;
+        if let Some(internal_sink) = self.internal_sink.take() {
+            internal::set_sink(internal_sink);
+        }
+        if let Some(sse_server) = self.sse_server.take() {
+            sse_server.start()?;
+        }
+        for target in &mut self.targets {
+            if target.dispatcher.error_handler.is_none() {
+                target.dispatcher.error_handler = self.dispatcher.error_handler.clone();
+            }
+            if target.dispatcher.suppressed_modules.is_empty() {
+                target.dispatcher.suppressed_modules = self.dispatcher.suppressed_modules.clone();
+            }
+            if target.dispatcher.in_flight.is_none() {
+                target.dispatcher.in_flight =
+                    self.dispatcher.in_flight.as_ref().map(InFlight::fresh);
+            }
+            if target.dispatcher.event_ttl.is_none() {
+                target.dispatcher.event_ttl = self.dispatcher.event_ttl.clone();
+            }
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            if target.dispatcher.dns.is_none() {
+                target.dispatcher.dns = self.dispatcher.dns.clone();
+            }
+            #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+            if target.dispatcher.prefer_ip.is_none() {
+                target.dispatcher.prefer_ip = self.dispatcher.prefer_ip;
+            }
+        }
+        #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+        for dispatcher in std::iter::once(&mut self.dispatcher)
+            .chain(self.targets.iter_mut().map(|target| &mut target.dispatcher))
+        {
+            if let Some(dns) = &dispatcher.dns {
+                if dispatcher.is_url() {
+                    dns.prefetch(&dispatcher.call_target);
+                }
+            }
+            if dispatcher.dns.is_some() || dispatcher.prefer_ip.is_some() {
+                dispatcher.agent = Some(dns::agent(dispatcher.dns.clone(), dispatcher.prefer_ip));
+            }
+        }
+        for dispatcher in std::iter::once(&mut self.dispatcher)
+            .chain(self.targets.iter_mut().map(|target| &mut target.dispatcher))
+        {
+            dispatcher.set_up_fallback();
+        }
+        if let Some(fingerprinter) = &self.fingerprinter {
+            for dispatcher in std::iter::once(&self.dispatcher)
+                .chain(self.targets.iter().map(|target| &target.dispatcher))
+            {
+                dispatcher.fingerprint.set(fingerprinter);
+            }
+        }
+        let max_level = self
+            .levels
+            .iter()
+            .map(|(_, level)| *level)
+            .chain(self.dependency_level)
+            .fold(self.level, Ord::max)
+            .min(log::STATIC_MAX_LEVEL);
+        // the payload is encrypted after every other stage, so that they all see the plain text
+        #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+        if let Some(Ok(recipient)) = self.encryption {
+            self.middleware
+                .push(Box::new(move |params| recipient.encrypt(&params)));
+        }
+        #[cfg(feature = "files")]
+        for dispatcher in std::iter::once(&mut self.dispatcher)
+            .chain(self.targets.iter_mut().map(|target| &mut target.dispatcher))
+        {
+            dispatcher.dead_letter_redact = !self.middleware.is_empty();
+        }
+        #[cfg(all(
+            feature = "process",
+            not(feature = "no-exec"),
+            not(target_arch = "wasm32")
+        ))]
+        for dispatcher in std::iter::once(&mut self.dispatcher)
+            .chain(self.targets.iter_mut().map(|target| &mut target.dispatcher))
+        {
+            dispatcher.wait_for_exit = self.audit;
+        }
+        let middleware: Arc<[Box<Middleware>]> = self.middleware.into();
+        for dispatcher in std::iter::once(&mut self.dispatcher)
+            .chain(self.targets.iter_mut().map(|target| &mut target.dispatcher))
+        {
+            dispatcher.share_middleware(&middleware);
+        }
+        let logger = CallLogger {
+            level: self.level,
+            levels: self.levels,
+            dependency_level: self.dependency_level,
+            target_filter: self.target_filter,
+            escalations: self.escalations,
+            #[cfg(feature = "timestamps")]
+            downgrades,
+            max_level,
+            level_cache: RwLock::new(HashMap::new()),
+            #[cfg(feature = "timestamps")]
+            timestamp: self.timestamp,
+            #[cfg(feature = "timestamps")]
+            format_string: self.format_string,
+            formatter: self.formatter,
+            static_fields: self.static_fields,
+            level_labels: self.level_labels,
+            field_layout: self.field_layout,
+            backtrace_level: self.backtrace_level,
+            trace_context_provider: self.trace_context_provider,
+            enrichers: self.enrichers,
+            source_links: self.source_links,
+            payload_key: self.payload_key,
+            #[cfg(feature = "files")]
+            replay_interval: self.replay_interval,
+            middleware,
+            dispatcher: Arc::new(self.dispatcher),
+            targets: self.targets.into_iter().map(BuiltTarget::from).collect(),
+            latency_budget: self.latency_budget,
+            worker_count: self.worker_count,
+            ordering: self.ordering,
+            workers: OnceLock::new(),
+            sequence: AtomicU64::new(0),
+            hostname: context::hostname(),
+            pause: Arc::new(PauseState::default()),
+        };
+        for dispatcher in std::iter::once(&logger.dispatcher)
+            .chain(logger.targets.iter().map(|target| &target.dispatcher))
+        {
+            dispatcher.owner.set(dispatcher);
+            for fallback in dispatcher.fallbacks() {
+                fallback.owner.set(dispatcher);
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        dispatch::start_flusher(
+            std::iter::once(&logger.dispatcher)
+                .chain(logger.targets.iter().map(|target| &target.dispatcher)),
+        );
+        Ok(logger)
+    }
+
+    /// Builds the logger and sets it up as the logger for the `log` crate, see [`CallLogger::init`].  This needs to be
+    /// called after the builder has set up the logger.
+    ///
+    /// # Example
+    /// ```
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new().init();
+    /// ```
+    pub fn init(self) -> Result<(), InitError> {
+        self.build()?.init()?;
         Ok(())
     }
 
+    /// Checks that the call target and the options can be used together, and the same for each of the other targets
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.audit
+            && (self.worker_count > 0
+                || self.latency_budget.is_some()
+                || std::iter::once(&self.dispatcher)
+                    .chain(self.targets.iter().map(|target| &target.dispatcher))
+                    .flat_map(|dispatcher| {
+                        std::iter::once(dispatcher).chain(dispatcher.fallbacks())
+                    })
+                    .any(|dispatcher| dispatcher.batch.is_some()))
+        {
+            return Err(ConfigError::Conflict(
+                "audit mode delivers while logging, so it can't be used with workers, a latency budget or batching",
+            ));
+        }
+        if self.minimal_payload
+            && std::iter::once(&self.dispatcher)
+                .chain(self.targets.iter().map(|target| &target.dispatcher))
+                .any(|dispatcher| dispatcher.digests())
+        {
+            return Err(ConfigError::Conflict(
+                "a digest counts the log events by module, so it can't be used with a minimal payload",
+            ));
+        }
+        #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+        if let Some(Err(reason)) = &self.encryption {
+            return Err(ConfigError::InvalidRecipient(reason.clone()));
+        }
+        std::iter::once(&self.dispatcher)
+            .chain(self.targets.iter().map(|target| &target.dispatcher))
+            .try_for_each(validate_dispatcher)
+    }
+}
+
+/// Checks that the call target of a dispatcher and its options can be used together
+fn validate_dispatcher(dispatcher: &Dispatcher) -> Result<(), ConfigError> {
+    if let Some(fallback) = &dispatcher.over_limit_fallback {
+        validate_dispatcher(fallback)?;
+    }
+    #[cfg(feature = "schema")]
+    if let Some(Err(reason)) = &dispatcher.schema {
+        return Err(ConfigError::InvalidSchema(reason.clone()));
+    }
+    if dispatcher.target.is_some() {
+        return Ok(());
+    }
+    let call_target = dispatcher.call_target.trim();
+    if call_target.is_empty() {
+        return Err(ConfigError::EmptyTarget);
+    }
+    let is_url = dispatcher.is_url();
+    // the default call target is written to stdout instead of being called
+    #[cfg(feature = "no-exec")]
+    if !is_url && call_target != "echo" {
+        return Err(ConfigError::ExecDisabled(call_target.to_string()));
+    }
+    if dispatcher.url_key.is_some() {
+        if !is_url {
+            return Err(ConfigError::Conflict(
+                "a URL from a key-value pair can only be used with a URL call target",
+            ));
+        }
+        if dispatcher.allowed_hosts.is_none() {
+            return Err(ConfigError::Conflict(
+                "a URL from a key-value pair can only be used with the hosts that are allowed",
+            ));
+        }
+        if dispatcher.batch.is_some() {
+            return Err(ConfigError::Conflict(
+                "a URL from a key-value pair can't be used with batching",
+            ));
+        }
+    }
+    if is_url {
+        validate_url(call_target)?;
+        validate_url_policy(dispatcher, call_target)?;
+    } else if dispatcher.batch.is_some() {
+        return Err(ConfigError::Conflict(
+            "batching can only be used with a URL call target",
+        ));
+    }
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    for (_, policy) in &dispatcher.status_policies {
+        if let StatusPolicy::Fallback(url) = policy {
+            validate_url(url.trim())?;
+            validate_url_policy(dispatcher, url.trim())?;
+        }
+    }
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    if let Some(url) = &dispatcher.pipe_url {
+        if is_url {
+            return Err(ConfigError::Conflict(
+                "the output of a URL call target can't be posted to another URL",
+            ));
+        }
+        validate_url(url.trim())?;
+        validate_url_policy(dispatcher, url.trim())?;
+    }
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    if let Some(path) = &dispatcher.exec_path {
+        if dispatcher.via_shell {
+            return Err(ConfigError::Conflict(
+                "an executable set by its path is run without the shell",
+            ));
+        }
+        exec::validate_executable(path)?;
+    }
+    #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+    if !is_url {
+        dispatcher.validate_program()?;
+    }
+    #[cfg(feature = "process")]
+    {
+        if is_url && dispatcher.file.is_some() {
+            return Err(ConfigError::Conflict(
+                "the output of a URL call target can't be written to a file",
+            ));
+        }
+        if dispatcher.options_terminator
+            && dispatcher.payload_delivery != dispatch::PayloadDelivery::Argument
+        {
+            return Err(ConfigError::Conflict(
+                "the options terminator can't be used when the payload is passed on stdin or in an environment variable",
+            ));
+        }
+        if let dispatch::PayloadDelivery::Env(name) = &dispatcher.payload_delivery {
+            if name.is_empty() || name.contains(['=', '\0']) {
+                return Err(ConfigError::Conflict(
+                    "the name of the environment variable for the payload must not be empty or contain `=`",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a URL is allowed by the schemes and hosts that log events can be sent to
+fn validate_url_policy(dispatcher: &Dispatcher, url: &str) -> Result<(), ConfigError> {
+    dispatcher
+        .check_url_policy(url)
+        .map_err(|reason| ConfigError::UrlNotAllowed(url.to_string(), reason))
+}
+
+/// Checks that a URL has a host and doesn't contain whitespace, and that an IPv6 address and the port are valid
+fn validate_url(url: &str) -> Result<(), ConfigError> {
+    let authority = url
+        .split_once("://")
+        .map(|(_, rest)| rest.split(['/', '?', '#']).next().unwrap_or_default())
+        .unwrap_or_default();
+    let host_port = authority.rsplit('@').next().unwrap_or_default();
+    if host_port.is_empty() || url.contains(char::is_whitespace) {
+        return Err(ConfigError::InvalidUrl(url.to_string()));
+    }
+    let invalid = |reason| Err(ConfigError::InvalidHost(url.to_string(), reason));
+    let port = match host_port.strip_prefix('[') {
+        Some(rest) => {
+            let Some((address, port)) = rest.split_once(']') else {
+                return invalid("the IPv6 address is missing the closing `]`");
+            };
+            if address.contains('%') {
+                return invalid(
+                    "zone ids, e.g. `%eth0` on a link-local address, are not supported",
+                );
+            }
+            if address.parse::<Ipv6Addr>().is_err() {
+                return invalid("the address in square brackets is not an IPv6 address");
+            }
+            match port {
+                "" => None,
+                port => match port.strip_prefix(':') {
+                    Some(port) => Some(port),
+                    None => return invalid("only a port can follow the IPv6 address"),
+                },
+            }
+        }
+        None => match host_port.split_once(':') {
+            Some((_, port)) if port.contains(':') => {
+                return invalid(
+                    "an IPv6 address must be in square brackets, e.g. `http://[::1]:9000`",
+                )
+            }
+            Some(("", _)) => return invalid("the host is empty"),
+            Some((_, port)) => Some(port),
+            None => None,
+        },
+    };
+    match port.filter(|port| !port.is_empty()).map(str::parse::<u16>) {
+        Some(Err(_)) => invalid("the port is not a number from 0 to 65535"),
+        _ => Ok(()),
+    }
+}
+
+impl CallLogger {
     #[cfg(feature = "timestamps")]
     fn format_timestamp(&self, time: SystemTime) -> String {
         if let Some(format_string) = &self.format_string {
@@ -436,67 +1880,278 @@ impl CallLogger {
         }
     }
 
-    #[cfg(not(feature = "timestamps"))]
-    fn json_formatter(message: &Arguments, record: &log::Record) -> String {
-        Self::json_formatter_inner(String::new(), message, record)
+    fn json_formatter(buffer: &mut String, context: &FormatContext) {
+        let record = context.record();
+        // writing to a String cannot fail
+        let _ = write!(
+            buffer,
+            "{{\"ts\":\"{}\",\"level\":\"{}\",\"target\":\"",
+            context.timestamp().unwrap_or_default(),
+            context.level()
+        );
+        push_json_escaped(buffer, record.target());
+        buffer.push_str("\",");
+        if let Some(file) = record.file() {
+            let _ = write!(buffer, "\"file\":\"{file}\",");
+        }
+        if let Some(line) = record.line() {
+            let _ = write!(buffer, "\"line\":\"{line}\",");
+        }
+        if let Some(module_path) = record.module_path() {
+            let _ = write!(buffer, "\"module_path\":\"{module_path}\",");
+        }
+        if let (Some(trace_id), Some(span_id)) = (context.trace_id(), context.span_id()) {
+            buffer.push_str("\"trace_id\":\"");
+            push_json_escaped(buffer, trace_id);
+            buffer.push_str("\",\"span_id\":\"");
+            push_json_escaped(buffer, span_id);
+            buffer.push_str("\",");
+        }
+        push_json_fields(buffer, context, &RESERVED_KEYS);
+        if let Some(backtrace) = context.backtrace() {
+            buffer.push_str("\"backtrace\":\"");
+            push_json_escaped(buffer, backtrace);
+            buffer.push_str("\",");
+        }
+        buffer.push_str("\"msg\":\"");
+        let message = context.message();
+        match message.as_str() {
+            Some(message) => push_json_escaped(buffer, message),
+            None => push_json_escaped(buffer, &message.to_string()),
+        }
+        buffer.push('"');
+        if context.field_layout().sequence {
+            let _ = write!(buffer, ",\"seq\":{}", context.sequence());
+        }
+        buffer.push('}');
+    }
+
+    /// Formats the record into the buffer with the formatter, then the same for each of the other targets with their
+    /// own formatter if they have one, and delivers the formatted output to each of them
+    fn format_and_deliver(&self, buffer: &mut String, record: &Record) {
+        let held = self.pause.holds(record);
+        if held.is_some() {
+            self.pause.count();
+        }
+        self.format_for_each(buffer, record, |dispatcher, params| {
+            self.process(dispatcher, params, record, held)
+        });
     }
 
-    #[cfg(feature = "timestamps")]
-    fn json_formatter(timestamp: String, message: &Arguments, record: &log::Record) -> String {
-        Self::json_formatter_inner(timestamp.to_string(), message, record)
-    }
-
-    fn json_formatter_inner(
-        timestamp: String,
-        message: &Arguments,
-        record: &log::Record,
-    ) -> String {
-        let timestamp = format!("\"ts\":\"{timestamp}\",");
-        let level = format!("\"level\":\"{}\",", record.level());
-        let file = match record.file() {
-            Some(file) => format!("\"file\":\"{file}\","),
-            None => "".to_string(),
+    /// Formats a record and delivers it to each target on this thread, without the background threads or the pause,
+    /// returning the first failure
+    fn deliver_test_event(&self, record: &Record) -> Result<(), CallError> {
+        let mut outcome = Ok(());
+        with_buffer(|buffer| {
+            self.format_for_each(buffer, record, |dispatcher, params| {
+                let Some(params) = self
+                    .middleware
+                    .iter()
+                    .try_fold(params.to_string(), |params, middleware| middleware(params))
+                else {
+                    return;
+                };
+                if let Err(x) = dispatcher.deliver(&params, record) {
+                    if outcome.is_ok() {
+                        outcome = Err(x.with_call(dispatcher.target_name(), &params));
+                    }
+                }
+            })
+        });
+        outcome
+    }
+
+    /// Formats the record into the buffer with the formatter, then the same for each of the other targets with their
+    /// own formatter if they have one, and passes the formatted output for each target to `deliver`.  The payload set
+    /// in the key-value pairs of the record is passed to every target instead, if there is one.
+    fn format_for_each<F>(&self, buffer: &mut String, record: &Record, mut deliver: F)
+    where
+        F: FnMut(&Arc<Dispatcher>, &str),
+    {
+        if let Some(payload) = self
+            .payload_key
+            .as_ref()
+            .and_then(|key| kv_payload::kv_payload(record, key))
+        {
+            buffer.push_str(&payload);
+            deliver(&self.dispatcher, buffer);
+            for target in &self.targets {
+                deliver(&target.dispatcher, buffer);
+            }
+            return;
+        }
+        #[cfg(feature = "timestamps")]
+        let timestamp = Some(self.format_timestamp(SystemTime::now()));
+        #[cfg(not(feature = "timestamps"))]
+        let timestamp: Option<String> = None;
+        let level = self.level_labels[record.level() as usize - 1]
+            .as_deref()
+            .unwrap_or(record.level().as_str());
+        let backtrace = self
+            .backtrace_level
+            .filter(|backtrace_level| record.level() <= *backtrace_level)
+            .map(|_| Backtrace::force_capture().to_string());
+        let trace_context = self
+            .trace_context_provider
+            .as_ref()
+            .and_then(|provider| provider());
+        let mut scoped_fields = scope::scoped_fields();
+        for enricher in &self.enrichers {
+            enricher(record, &mut scoped_fields);
+        }
+        let routed_channels = || {
+            std::iter::once(&self.dispatcher)
+                .chain(self.targets.iter().map(|target| &target.dispatcher))
+                .filter(|dispatcher| dispatcher.routes(record))
+                .map(|dispatcher| dispatcher.channel_name())
+                .collect()
         };
-        let line = match record.line() {
-            Some(line) => format!("\"line\":\"{line}\","),
-            None => "".to_string(),
+        let mut context = FormatContext::new(
+            timestamp.as_deref(),
+            level,
+            record,
+            backtrace.as_deref(),
+            trace_context.as_ref(),
+            &scoped_fields,
+            &self.static_fields,
+            self.sequence.fetch_add(1, Ordering::Relaxed),
+            &self.hostname,
+            &self.field_layout,
+            self.get_level_for_module(record.target()),
+            self.dispatcher.channel_name(),
+            &routed_channels,
+            self.source_links.as_ref(),
+        );
+        (self.formatter)(buffer, &context);
+        deliver(&self.dispatcher, buffer);
+        for target in &self.targets {
+            match &target.formatter {
+                Some(formatter) => {
+                    let mut params = String::new();
+                    context.set_channel(target.dispatcher.channel_name());
+                    formatter(&mut params, &context);
+                    deliver(&target.dispatcher, &params);
+                }
+                None => deliver(&target.dispatcher, buffer),
+            }
+        }
+    }
+
+    /// Passes the formatted output of a record through the middleware, then delivers it with the dispatcher, unless
+    /// it is `held` because the logger is paused or the record is silenced, in which case it is written to the
+    /// dead-letter file after the middleware so that it is scrubbed and encrypted there too
+    fn process(
+        &self,
+        dispatcher: &Arc<Dispatcher>,
+        params: &str,
+        record: &Record,
+        held: Option<&'static str>,
+    ) {
+        let in_background = self.worker_count > 0 || self.latency_budget.is_some();
+        if held.is_none() && self.middleware.is_empty() && !in_background {
+            // the formatted output is delivered straight from the buffer without being copied
+            self.deliver(dispatcher, params, record);
+            return;
+        }
+        let Some(params) = self
+            .middleware
+            .iter()
+            .try_fold(params.to_string(), |params, middleware| middleware(params))
+        else {
+            return;
         };
-        let module_path = match record.module_path() {
-            Some(module_path) => format!("\"module_path\":\"{module_path}\","),
-            None => "".to_string(),
+        if let Some(reason) = held {
+            dispatcher.write_dead_letter(&params, &Event::from(record), reason);
+        } else if in_background {
+            self.deliver_in_background(dispatcher, params, record);
+        } else {
+            self.deliver(dispatcher, &params, record);
+        }
+    }
+
+    /// The background delivery threads, which are started the first time that they are needed
+    fn workers(&self) -> &Workers {
+        self.workers
+            .get_or_init(|| Workers::start(self.worker_count))
+    }
+
+    /// Delivers the formatted output of a record on this thread
+    fn deliver(&self, dispatcher: &Dispatcher, params: &str, record: &Record) {
+        if let Err(x) = dispatcher.deliver(params, record) {
+            dispatcher.call_failed(params, &Event::from(record), x);
+        }
+    }
+
+    /// Hands the formatted output of a record to the background threads, waiting for up to the latency budget
+    fn deliver_in_background(&self, dispatcher: &Arc<Dispatcher>, params: String, record: &Record) {
+        let (done, delivered) = match self.latency_budget {
+            Some(budget) => {
+                let (done, delivered) = channel();
+                (Some(done), Some((delivered, budget)))
+            }
+            None => (None, None),
         };
-        let mut visitor = LogVisitor {
-            map: HashMap::new(),
+        let order_key = match self.ordering {
+            DeliveryOrder::Concurrent => None,
+            DeliveryOrder::Module => Some(record.module_path().unwrap_or(record.target())),
+            DeliveryOrder::Preserved => Some(""),
         };
-        let kv_str = if let Ok(()) = record.key_values().visit(&mut visitor) {
-            let mut msg = String::new();
-            for (key, value) in visitor.map {
-                msg.push_str(&format!("\"{key}\":\"{value}\","));
-            }
-            msg
-        } else {
-            "".to_string()
+        let job = Job::Deliver {
+            dispatcher: dispatcher.clone(),
+            params,
+            event: Event::from(record),
+            done,
         };
-        let msg = format!(
-            "\"msg\":\"{}\"",
-            message
-                .to_string()
-                .replace('\\', "\\\\")
-                .replace('\"', "\\\"")
-        );
-        format!("{{{timestamp}{level}{file}{line}{module_path}{kv_str}{msg}}}")
+        self.workers().send(job, order_key);
+        if let Some((delivered, budget)) = delivered {
+            let _ = delivered.recv_timeout(budget);
+        }
     }
 
-    fn get_level_for_module(&self, target: String) -> &LevelFilter {
-        self.levels
+    fn get_level_for_module(&self, target: &str) -> LevelFilter {
+        if self.levels.is_empty() && self.dependency_level.is_none() {
+            return self.level;
+        }
+        if let Some(level) = self
+            .level_cache
+            .read()
+            .ok()
+            .and_then(|cache| cache.get(target).copied())
+        {
+            return level;
+        }
+        let level = self
+            .levels
             .iter()
-            .find(|(module, _)| target.contains(module))
-            .map(|(_, level)| level)
-            .unwrap_or(&self.level)
+            .find(|(module, _)| target.contains(module.as_str()))
+            .map(|(_, level)| *level)
+            .or_else(|| {
+                let krate = target.split("::").next().unwrap_or(target);
+                self.dependency_level
+                    .filter(|_| DEPENDENCY_CRATES.contains(&krate))
+            })
+            .unwrap_or(self.level);
+        if let Ok(mut cache) = self.level_cache.write() {
+            // targets are usually module paths so there are only a few of them, but if there are many the cache is
+            // started again rather than growing without limit
+            if cache.len() >= LEVEL_CACHE_SIZE {
+                cache.clear();
+            }
+            cache.insert(target.to_string(), level);
+        }
+        level
     }
 }
 
 impl Default for CallLogger {
+    fn default() -> Self {
+        CallLoggerBuilder::new()
+            .build()
+            .expect("the default configuration is valid")
+    }
+}
+
+impl Default for CallLoggerBuilder {
     fn default() -> Self {
         Self::new()
     }
@@ -504,79 +2159,72 @@ impl Default for CallLogger {
 
 impl Log for CallLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= *self.get_level_for_module(metadata.target().to_string())
+        // the quick check avoids looking up the level for the module when nothing could be logged at this level
+        metadata.level() <= self.max_level
+            && metadata.level() <= self.get_level_for_module(metadata.target())
+            && self.target_filter.allows(metadata.target())
     }
 
     fn log(&self, record: &Record) {
-        if self.enabled(record.metadata()) {
-            let formatter = &self.formatter;
-            #[cfg(feature = "timestamps")]
-            let params = formatter(
-                self.format_timestamp(SystemTime::now()),
-                record.args(),
-                record,
-            );
-            #[cfg(not(feature = "timestamps"))]
-            let params = formatter(record.args(), record);
-            if self.call_target.starts_with("http://") || self.call_target.starts_with("https://") {
-                if self.echo {
-                    println!("Calling: `{}\n\t{params}`", self.call_target);
-                }
-                let avoid_overflow = match record.module_path() {
-                    Some(module_path) => {
-                        module_path.starts_with("ureq::") || module_path.starts_with("rustls::")
-                    }
-                    None => false,
-                };
-                if !avoid_overflow {
-                    if let Err(x) = ureq::post(&self.call_target)
-                        .set("Content-Type", "application/json")
-                        .send_string(params.as_str())
-                    {
-                        println!("logging call to {} failed {x}", self.call_target);
-                    }
-                }
-            } else {
-                let mut args = if let Some((header, trailer)) = self.call_target.split_once("{}") {
-                    let mut args = header.split(' ').collect::<VecDeque<&str>>();
-                    args.push_back(params.as_str());
-                    for arg in trailer.split(' ') {
-                        args.push_back(arg);
-                    }
-                    args
-                } else {
-                    let mut args = self.call_target.split(' ').collect::<VecDeque<&str>>();
-                    args.push_back(params.as_str());
-                    args
-                };
-                if self.echo {
-                    println!("Calling: `{}`", Vec::from(args.clone()).join(" "));
-                }
-                let call_target = args.pop_front().unwrap();
-                match self.file {
-                    Some(_) => match Command::new(call_target).args(args).output() {
-                        Ok(output) => {
-                            if let Some(file) = &self.file {
-                                let _ = write(file, &output.stdout);
-                            }
-                        }
-                        Err(x) => {
-                            println!("logging call to {} failed {x}", self.call_target);
-                        }
-                    },
-                    None => match Command::new(call_target).args(args).spawn() {
-                        Ok(_) => {}
-                        Err(x) => {
-                            println!("logging call to {} failed {x}", self.call_target);
-                        }
-                    },
-                }
+        if handle::is_test_event_for(&self.pause) {
+            handle::set_test_outcome(self.deliver_test_event(record));
+            return;
+        }
+        if structured::take_sending_event() {
+            self.deliver_event(record);
+            return;
+        }
+        let escalated = self
+            .escalations
+            .iter()
+            .filter(|(rule, level)| *level < record.level() && rule(record))
+            .map(|(_, level)| *level)
+            .min();
+        #[cfg(feature = "timestamps")]
+        let escalated = {
+            let level = escalated.unwrap_or(record.level());
+            self.downgrades
+                .iter()
+                .filter(|(hours, downgrade)| *downgrade > level && hours.is_now())
+                .map(|(_, downgrade)| *downgrade)
+                .max()
+                .or(escalated)
+        };
+        let escalated_record;
+        let record = match escalated {
+            Some(level) => {
+                escalated_record = Record::builder()
+                    .args(*record.args())
+                    .level(level)
+                    .target(record.target())
+                    .module_path(record.module_path())
+                    .file(record.file())
+                    .line(record.line())
+                    .key_values(record.key_values())
+                    .build();
+                &escalated_record
             }
+            None => record,
+        };
+        if self.enabled(record.metadata()) {
+            with_buffer(|buffer| self.format_and_deliver(buffer, record));
         }
     }
 
     fn flush(&self) {
-        log::logger().flush()
+        if let Some(workers) = self.workers.get() {
+            workers.flush();
+        }
+        self.dispatcher.flush();
+        for target in &self.targets {
+            target.dispatcher.flush();
+        }
+    }
+}
+
+impl Drop for CallLogger {
+    fn drop(&mut self) {
+        self.flush();
     }
 }
 
@@ -593,12 +2241,51 @@ impl Debug for CallLogger {
         let mut f = f.debug_struct("CallLogger");
 
         let f = f
-            .field("call-target", &self.call_target)
+            .field("call-target", &self.dispatcher.call_target)
+            .field(
+                "target",
+                &self
+                    .dispatcher
+                    .target
+                    .as_ref()
+                    .map(|_| "Box<dyn CallTarget>"),
+            )
+            .field("targets", &self.targets.len())
             .field("level", &self.level)
             .field("levels", &LevelsDebug(&self.levels))
-            .field("echo", &self.echo)
-            .field("file", &self.file)
-            .field("formatter", &"Box<Formatter>");
+            .field("dependency_level", &self.dependency_level)
+            .field("target_filter", &self.target_filter)
+            .field("echo", &self.dispatcher.echo.is_some())
+            .field("batch", &self.dispatcher.batch.is_some())
+            .field("middleware", &self.middleware.len())
+            .field("latency_budget", &self.latency_budget)
+            .field("workers", &self.worker_count)
+            .field("ordering", &self.ordering)
+            .field("formatter", &"Box<FormatterInto>")
+            .field(
+                "http_transport",
+                &self
+                    .dispatcher
+                    .http_transport
+                    .as_ref()
+                    .map(|_| "Box<HttpTransport>"),
+            );
+
+        #[cfg(feature = "process")]
+        let f = f.field("file", &self.dispatcher.file);
+
+        #[cfg(feature = "files")]
+        let f = f.field("dead_letter_file", &self.dispatcher.dead_letter_file);
+
+        #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+        let f = f.field(
+            "request_customizer",
+            &self
+                .dispatcher
+                .request_customizer
+                .as_ref()
+                .map(|_| "Box<RequestCustomizer>"),
+        );
 
         #[cfg(feature = "timestamps")]
         let f = f.field("timestamp", &self.timestamp);
@@ -607,24 +2294,432 @@ impl Debug for CallLogger {
     }
 }
 
-// Visitor for querying the kv pairs in a log record.
-struct LogVisitor {
-    map: HashMap<String, String>,
+/// The type alias for a closure that returns the ids of the current trace and span.
+pub type TraceContextProvider = dyn Fn() -> Option<(String, String)> + Sync + Send + 'static;
+
+/// The type alias for a closure that adds fields to a log event before it is formatted.
+pub type Enricher = dyn Fn(&Record, &mut Vec<(String, String)>) + Sync + Send + 'static;
+
+/// The type alias for a closure that makes the key of the fingerprint of a log event.
+pub type Fingerprinter = dyn Fn(&Record) -> String + Sync + Send + 'static;
+
+/// The type alias for a rule that decides whether a log event is promoted to a more severe level.
+pub type EscalationRule = dyn Fn(&Record) -> bool + Sync + Send + 'static;
+
+/// The type alias for a closure that is passed the reason when a log event can't be delivered.
+pub type ErrorHandler = dyn Fn(&CallError) + Sync + Send + 'static;
+
+/// The type alias for a closure that customizes the HTTP request sent to a URL call target.
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub type RequestCustomizer = dyn Fn(Level, ureq::Request) -> ureq::Request + Sync + Send + 'static;
+
+/// The type alias for a closure that turns the stdout, stderr and exit status of a call into what is written to the
+/// file.
+#[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+pub type OutputMapper =
+    dyn Fn(&str, &str, std::process::ExitStatus) -> String + Sync + Send + 'static;
+
+/// The order that log events are delivered in by the background threads
+#[derive(Debug)]
+enum DeliveryOrder {
+    /// Log events are shared between the threads in turn and may be delivered out of order
+    Concurrent,
+    /// Log events from the same module are delivered in order
+    Module,
+    /// All log events are delivered in order
+    Preserved,
+}
+
+/// The details of a log event that are kept when the record itself is no longer available, e.g. while the
+/// formatted output is batched or when it is written to the dead-letter file.
+pub(crate) struct Event {
+    pub(crate) level: Level,
+    pub(crate) target: String,
+    pub(crate) msg: String,
+    pub(crate) module_path: Option<String>,
+    pub(crate) file: Option<String>,
+    pub(crate) line: Option<u32>,
+    /// When the log event was logged, which is how old it is for [`CallLoggerBuilder::with_event_ttl`]
+    pub(crate) logged: SystemTime,
+}
+
+impl Event {
+    /// Calls a closure with a record rebuilt from the event, without any key-value pairs.  The events made from the
+    /// record while the closure runs keep the time that this event was logged.
+    pub(crate) fn with_record<R>(&self, f: impl FnOnce(&Record) -> R) -> R {
+        event_ttl::with_logged_at(self.logged, || {
+            f(&Record::builder()
+                .args(format_args!("{}", self.msg))
+                .level(self.level)
+                .target(&self.target)
+                .module_path(self.module_path.as_deref())
+                .file(self.file.as_deref())
+                .line(self.line)
+                .build())
+        })
+    }
+}
+
+impl From<&Record<'_>> for Event {
+    fn from(record: &Record) -> Self {
+        Event {
+            level: record.level(),
+            target: record.target().to_string(),
+            msg: record.args().to_string(),
+            module_path: record.module_path().map(|x| x.to_string()),
+            file: record.file().map(|x| x.to_string()),
+            line: record.line(),
+            logged: event_ttl::logged_at(),
+        }
+    }
 }
 
-impl<'kvs> VisitSource<'kvs> for LogVisitor {
-    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
-        self.map.insert(key.to_string(), value.to_string());
-        Ok(())
+/// The JSON of the value of the member `key` of a JSON object, without looking inside the other members, so that a
+/// nested member with the same key isn't found instead
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub(crate) fn json_member<'a>(object: &'a str, key: &str) -> Option<&'a str> {
+    let mut rest = object.trim_start().strip_prefix('{')?;
+    loop {
+        rest = rest.trim_start();
+        let name_end = json_value_end(rest)?;
+        let name = rest[..name_end].strip_prefix('"')?.strip_suffix('"')?;
+        let value = rest[name_end..]
+            .trim_start()
+            .strip_prefix(':')?
+            .trim_start();
+        let value_end = json_value_end(value)?;
+        if name == key {
+            return Some(&value[..value_end]);
+        }
+        rest = value[value_end..].trim_start().strip_prefix(',')?;
+    }
+}
+
+/// The JSON of the first element of a JSON array, if it has one
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub(crate) fn json_first_element(array: &str) -> Option<&str> {
+    let rest = array.trim_start().strip_prefix('[')?.trim_start();
+    if rest.starts_with(']') {
+        return None;
+    }
+    json_value_end(rest).map(|end| &rest[..end])
+}
+
+/// The length of the JSON value at the start of `json`, skipping over the strings and nested values in it
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+fn json_value_end(json: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in json.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    in_string = false;
+                    if depth == 0 {
+                        return Some(i + 1);
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' if depth == 0 => return Some(i),
+            '}' | ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            ',' if depth == 0 => return Some(i),
+            c if depth == 0 && c.is_whitespace() => return Some(i),
+            _ => {}
+        }
+    }
+    (depth == 0 && !in_string).then_some(json.len())
+}
+
+/// Escapes a string so that it can be written between the quotes of a JSON string
+pub(crate) fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    push_json_escaped(&mut escaped, value);
+    escaped
+}
+
+/// Appends a string to the buffer, escaped so that it can be used as the value of a JSON string
+pub(crate) fn push_json_escaped(buffer: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '\"' => buffer.push_str("\\\""),
+            '\\' => buffer.push_str("\\\\"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\t' => buffer.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(buffer, "\\u{:04x}", u32::from(c));
+            }
+            c => buffer.push(c),
+        }
+    }
+}
+
+/// Percent encodes a query parameter value or a segment of the path of a URL
+pub(crate) fn encode_query(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Encodes bytes as standard base64 with padding
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | u32::from(*byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+thread_local! {
+    /// The buffer that each thread formats log events into, so that a new `String` isn't needed for every event
+    static BUFFER: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Runs the closure with this thread's empty format buffer.  A new buffer is used instead if the closure logs again
+/// while the buffer is in use, e.g. from a call target.
+fn with_buffer<R>(f: impl FnOnce(&mut String) -> R) -> R {
+    BUFFER.with(|buffer| match buffer.try_borrow_mut() {
+        Ok(mut buffer) => {
+            buffer.clear();
+            let result = f(&mut buffer);
+            // a very large log event shouldn't keep its memory for the life of the thread
+            buffer.shrink_to(BUFFER_CAPACITY);
+            result
+        }
+        Err(_) => f(&mut String::new()),
+    })
+}
+
+/// A target that the formatted output of each log event is delivered to, along with the record itself.  Implement this
+/// to bring your own transport and set it with [`CallLoggerBuilder::with_custom_target`].  Closures that accept the
+/// payload and the record implement this trait already.
+///
+/// Example
+/// ```rust
+/// # use call_logger::{BoxError, CallLogger, CallTarget};
+/// # use log::Record;
+/// struct Stderr;
+///
+/// impl CallTarget for Stderr {
+///     fn call(&self, payload: &str, _record: &Record) -> Result<(), BoxError> {
+///         eprintln!("{payload}");
+///         Ok(())
+///     }
+/// }
+///
+/// CallLogger::new()
+///     .with_custom_target(Stderr)
+///     .init();
+/// ```
+pub trait CallTarget: Sync + Send {
+    /// Delivers the formatted output of a log event
+    fn call(&self, payload: &str, record: &Record) -> Result<(), BoxError>;
+
+    /// Delivers anything that the target has buffered
+    fn flush(&self) {}
+
+    /// Delivers what the target has buffered for as long as it holds log events, and returns how long until it should
+    /// be called again, or `None` if it doesn't buffer them.  The logger calls this from a background thread, so that
+    /// buffered log events are delivered when no more are logged.
+    fn flush_due(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl<F> CallTarget for F
+where
+    F: Fn(&str, &Record) -> Result<(), BoxError> + Sync + Send,
+{
+    fn call(&self, payload: &str, record: &Record) -> Result<(), BoxError> {
+        self(payload, record)
     }
 }
 
+/// The error type returned by call targets.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The type alias for a stage that the formatted output passes through before it is delivered.
+pub type Middleware = dyn Fn(String) -> Option<String> + Sync + Send + 'static;
+
+/// The type alias for a closure that sends the HTTP request to a URL call target, passed the URL, headers and body.
+pub type HttpTransport =
+    dyn Fn(&str, &[(&str, &str)], &str) -> Result<(), BoxError> + Sync + Send + 'static;
+
+/// The type alias for a closure that the formatted output is delivered to instead of the call target.
+pub type FnTarget = dyn Fn(&str, &Record) -> Result<(), BoxError> + Sync + Send + 'static;
+
 /// The type alias for a log formatter.
+pub type Formatter =
+    dyn Fn(Option<String>, &Arguments, &log::Record) -> String + Sync + Send + 'static;
+
+/// A rule that decides whether a target is sent a log event, passed the formatted output and the record
+type DeliveryFilter = dyn Fn(&str, &Record) -> bool + Sync + Send + 'static;
+
+/// A closure that picks the URL that the formatted output of a log event is sent to, for services that have a URL for
+/// each action
+type Route = dyn Fn(&str, &Record) -> String + Sync + Send + 'static;
+
+/// A closure that returns the headers that sign the body of an HTTP request, for services that authenticate each
+/// request
+type RequestSigner = dyn Fn(&str) -> Vec<(String, String)> + Sync + Send + 'static;
+
+/// The formatter that the logger calls, which all of the kinds of formatter are adapted to
+type ContextFormatter = dyn Fn(&mut String, &FormatContext) + Sync + Send + 'static;
+
+/// The type alias for a log formatter that writes into a buffer.
+pub type FormatterInto =
+    dyn Fn(&mut String, Option<String>, &Arguments, &log::Record) + Sync + Send + 'static;
+
+#[cfg(feature = "files")]
+mod audit;
+#[cfg(feature = "files")]
+mod avro;
+mod azure;
+mod batch;
+mod build_info;
 #[cfg(feature = "timestamps")]
-pub type Formatter = dyn Fn(String, &Arguments, &log::Record) -> String + Sync + Send + 'static;
-/// The type alias for a log formatter.
-#[cfg(not(feature = "timestamps"))]
-pub type Formatter = dyn Fn(&Arguments, &log::Record) -> String + Sync + Send + 'static;
+mod bunyan;
+mod clickhouse;
+mod console;
+mod context;
+mod csv;
+#[cfg(feature = "files")]
+mod dead_letter;
+#[cfg(all(
+    feature = "process",
+    not(feature = "no-exec"),
+    not(target_arch = "wasm32")
+))]
+mod desktop;
+mod digest;
+mod dispatch;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+mod dns;
+mod echo;
+#[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+mod encrypt;
+mod error;
+mod event_ttl;
+#[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+mod exec;
+mod fields;
+mod filter;
+mod fingerprint;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+mod github;
+mod handle;
+mod healthchecks;
+mod home_assistant;
+mod in_flight;
+mod influxdb;
+mod internal;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+mod jira;
+mod kv_payload;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+mod matrix;
+mod minimal;
+mod nagios;
+#[cfg(feature = "noop")]
+mod noop;
+mod ntfy;
+mod opsgenie;
+mod pushgateway;
+#[cfg(feature = "timestamps")]
+mod quiet_hours;
+mod rate_limit;
+#[cfg(feature = "schema")]
+mod schema;
+mod scope;
+mod scrub;
+#[cfg(all(
+    feature = "process",
+    not(feature = "no-exec"),
+    not(target_arch = "wasm32")
+))]
+mod shell;
+#[cfg(feature = "snmp")]
+mod snmp;
+mod source_links;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod sse;
+mod statsd;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+mod status;
+mod structured;
+mod target;
+mod threshold;
+mod twilio;
+mod url_policy;
+mod vector;
+mod victorops;
+mod webhooks;
+#[cfg(feature = "websocket")]
+mod websocket;
+mod xml;
+mod zulip;
+
+pub use batch::BatchFormat;
+pub use context::FormatContext;
+use dispatch::{Dispatcher, Job, Workers};
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub use dns::IpFamily;
+use echo::Echo;
+pub use error::{CallError, CallErrorKind, ConfigError, InitError};
+pub use fields::KeyCollision;
+use fields::{push_json_fields, FieldLayout, RESERVED_KEYS};
+use filter::TargetFilter;
+pub use handle::CallLoggerHandle;
+use handle::PauseState;
+use in_flight::InFlight;
+pub use internal::InternalSink;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub use jira::JiraCredentials;
+#[cfg(feature = "timestamps")]
+use quiet_hours::HourRange;
+#[cfg(feature = "timestamps")]
+pub use quiet_hours::QuietHoursAction;
+pub use scope::{push_context, ContextGuard};
+use source_links::SourceLinker;
+pub use source_links::SourceLinks;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub use status::StatusPolicy;
+pub use structured::{event, StructuredEvent};
+use target::BuiltTarget;
+pub use target::Target;
 
 #[cfg(test)]
 mod test;
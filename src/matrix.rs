@@ -0,0 +1,127 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::Record;
+
+use crate::{
+    encode_query, push_json_escaped, webhooks::level_color, BoxError, CallLoggerBuilder,
+    CallTarget, FormatContext,
+};
+
+impl CallLoggerBuilder {
+    /// Posts each log event as an `m.room.message` event to a [Matrix][matrix] room, for teams that chat on Matrix,
+    /// e.g. with Element.  The message is the level, target and message of the log event, with the level in bold and
+    /// colored by severity for clients that show HTML, followed by a link to the source once
+    /// [`CallLoggerBuilder::with_source_links`] is set.  The `homeserver` is the base URL of the homeserver of the
+    /// account, e.g. `https://matrix.org`, the `access_token` is the token of an account that has joined the room, and
+    /// the `room_id` is the internal id of the room, e.g. `!abc123:matrix.org`.  This is delivered with the built in
+    /// HTTP client, not the closure set with [`CallLoggerBuilder::with_http_transport`].
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .matrix("https://matrix.org", "my-token", "!abc123:matrix.org")
+    ///     .init();
+    /// ```
+    ///
+    /// [matrix]: https://spec.matrix.org/latest/client-server-api/#mroommessage
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn matrix<H, T, R>(
+        mut self,
+        homeserver: H,
+        access_token: T,
+        room_id: R,
+    ) -> CallLoggerBuilder
+    where
+        H: AsRef<str>,
+        T: AsRef<str>,
+        R: AsRef<str>,
+    {
+        self.formatter = Box::new(matrix_formatter);
+        self.dispatcher.target = Some(Box::new(MatrixRoom {
+            send_url: format!(
+                "{}/_matrix/client/v3/rooms/{}/send/m.room.message",
+                homeserver.as_ref().trim_end_matches('/'),
+                encode_query(room_id.as_ref())
+            ),
+            authorization: format!("Bearer {}", access_token.as_ref()),
+            // transaction ids must not be reused by the same access token, even after a restart
+            txn_prefix: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis(),
+            txn_count: AtomicU64::new(0),
+        }));
+        self
+    }
+}
+
+struct MatrixRoom {
+    send_url: String,
+    authorization: String,
+    txn_prefix: u128,
+    txn_count: AtomicU64,
+}
+
+impl CallTarget for MatrixRoom {
+    fn call(&self, payload: &str, _record: &Record) -> Result<(), BoxError> {
+        let txn_id = format!(
+            "{}-{}",
+            self.txn_prefix,
+            self.txn_count.fetch_add(1, Ordering::Relaxed)
+        );
+        ureq::put(&format!("{}/{txn_id}", self.send_url))
+            .set("Authorization", &self.authorization)
+            .set("Content-Type", "application/json")
+            .send_string(payload)?;
+        Ok(())
+    }
+}
+
+fn matrix_formatter(buffer: &mut String, context: &FormatContext) {
+    let record = context.record();
+    let message = context.message().to_string();
+    buffer.push_str("{\"msgtype\":\"m.text\",\"body\":\"[");
+    push_json_escaped(buffer, context.level());
+    buffer.push_str("] ");
+    push_json_escaped(buffer, record.target());
+    buffer.push_str(": ");
+    push_json_escaped(buffer, &message);
+    let source = context.source_url().zip(record.file().zip(record.line()));
+    if let Some((_, (file, line))) = source {
+        push_json_escaped(buffer, &format!(" ({file}:{line})"));
+    }
+    buffer.push_str(
+        "\",\"format\":\"org.matrix.custom.html\",\"formatted_body\":\"<font data-mx-color=\\\"",
+    );
+    buffer.push_str(level_color(record.level()));
+    buffer.push_str("\\\"><b>");
+    push_json_escaped(buffer, &escape_html(context.level()));
+    buffer.push_str("</b></font> ");
+    push_json_escaped(buffer, &escape_html(record.target()));
+    buffer.push_str(": ");
+    push_json_escaped(buffer, &escape_html(&message).replace('\n', "<br>"));
+    if let Some((url, (file, line))) = source {
+        push_json_escaped(
+            buffer,
+            &format!(
+                " (<a href=\"{}\">{}:{line}</a>)",
+                escape_html(&url),
+                escape_html(file)
+            ),
+        );
+    }
+    buffer.push_str("\"}");
+}
+
+/// Escapes text so that it is shown as is in HTML
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,73 @@
+use std::fmt::Write;
+
+use crate::{push_json_escaped, CallLoggerBuilder, FormatContext};
+
+impl CallLoggerBuilder {
+    /// Formats each log event as a JSON object with only the timestamp, the level, the message and the fields whose
+    /// keys are in `allowed_keys`, so that logs that are sent outside of the organisation carry as little personal data
+    /// as possible.  The target, module path, source file and line, hostname, trace context and backtrace are left
+    /// out, as are the key-value pairs, scoped fields and static fields that aren't allowed, e.g.
+    /// `{"ts":"...","level":"INFO","order_id":"17","msg":"order placed"}`.  The message itself is written as it was
+    /// logged, so it can still be masked with [`CallLoggerBuilder::with_middleware`].  A digest would send the modules
+    /// of the log events, so it can't be used with a minimal payload.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .minimal_payload(&["order_id"])
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .init();
+    /// log::info!(order_id = 17, email = "someone@example.com"; "order placed");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn minimal_payload<T>(mut self, allowed_keys: &[T]) -> CallLoggerBuilder
+    where
+        T: AsRef<str>,
+    {
+        let allowed_keys = allowed_keys
+            .iter()
+            .map(|key| key.as_ref().to_string())
+            .collect::<Vec<_>>();
+        self.formatter =
+            Box::new(move |buffer, context| minimal_formatter(buffer, &allowed_keys, context));
+        self.minimal_payload = true;
+        self
+    }
+}
+
+fn minimal_formatter(buffer: &mut String, allowed_keys: &[String], context: &FormatContext) {
+    // writing to a String cannot fail
+    let _ = write!(
+        buffer,
+        "{{\"ts\":\"{}\",\"level\":\"",
+        context.timestamp().unwrap_or_default()
+    );
+    push_json_escaped(buffer, context.level());
+    buffer.push_str("\",");
+    // the key-value pairs come first, then the scoped and static fields, and only the first of each key is written
+    let mut written: Vec<&str> = Vec::new();
+    for (key, value) in context
+        .kv()
+        .iter()
+        .chain(context.scoped_fields().iter().map(|(k, v)| (k, v)))
+        .chain(context.static_fields().iter().map(|(k, v)| (k, v)))
+    {
+        if allowed_keys.contains(key) && !written.contains(&key.as_str()) {
+            buffer.push('"');
+            push_json_escaped(buffer, key);
+            buffer.push_str("\":\"");
+            push_json_escaped(buffer, value);
+            buffer.push_str("\",");
+            written.push(key);
+        }
+    }
+    buffer.push_str("\"msg\":\"");
+    let message = context.message();
+    match message.as_str() {
+        Some(message) => push_json_escaped(buffer, message),
+        None => push_json_escaped(buffer, &message.to_string()),
+    }
+    buffer.push_str("\"}");
+}
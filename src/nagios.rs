@@ -0,0 +1,103 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{Level, Record};
+
+use crate::{escape_json, BoxError, CallLoggerBuilder, CallTarget};
+
+impl CallLoggerBuilder {
+    /// Submits error, warning and info log events as passive check results of a Nagios or Icinga service, so that
+    /// legacy monitoring can raise alerts from the log events of an application.  Errors are `CRITICAL`, warnings are
+    /// `WARNING` and info log events are `OK`, and debug and trace log events are not submitted.  The message of the
+    /// log event is the output of the check, the formatter is not used.
+    ///
+    /// `command_file_or_url` is either the external command file of Nagios, e.g. `/var/lib/nagios/rw/nagios.cmd`,
+    /// that a `PROCESS_SERVICE_CHECK_RESULT` command is written to, or the base URL of the Icinga 2 API, e.g.
+    /// `https://icinga:5665`, that the check result is posted to.  The API needs credentials, which can be added with
+    /// [`CallLoggerBuilder::customize_request`].
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .nagios_passive("/var/lib/nagios/rw/nagios.cmd", "web01", "my_app")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn nagios_passive<T, H, S>(
+        mut self,
+        command_file_or_url: T,
+        host: H,
+        service: S,
+    ) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+        H: Into<String>,
+        S: Into<String>,
+    {
+        let command_file_or_url = command_file_or_url.into();
+        let (host, service) = (host.into(), service.into());
+        self.dispatcher.accepts = Some(Box::new(|_, record| record.level() <= Level::Info));
+        if command_file_or_url.starts_with("http://") || command_file_or_url.starts_with("https://")
+        {
+            let filter = format!("host.name==\"{host}\" && service.name==\"{service}\"");
+            self.dispatcher
+                .http_headers
+                .push(("Accept".to_string(), "application/json".to_string()));
+            self.with_call_target(format!(
+                "{}/v1/actions/process-check-result",
+                command_file_or_url.trim_end_matches('/')
+            ))
+            .format(move |_, message, record| {
+                format!(
+                    "{{\"type\":\"Service\",\"filter\":\"{}\",\"exit_status\":{},\"plugin_output\":\"{}\"}}",
+                    escape_json(&filter),
+                    exit_status(record),
+                    escape_json(&message.to_string())
+                )
+            })
+        } else {
+            self.dispatcher.target = Some(Box::new(CommandFile {
+                path: command_file_or_url.into(),
+            }));
+            self.format(move |_, message, record| {
+                let since_epoch = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                format!(
+                    "[{}] PROCESS_SERVICE_CHECK_RESULT;{host};{service};{};{}",
+                    since_epoch.as_secs(),
+                    exit_status(record),
+                    message.to_string().replace('\n', "\\n")
+                )
+            })
+        }
+    }
+}
+
+/// The return code of a check for the level of a log event
+fn exit_status(record: &Record) -> u8 {
+    match record.level() {
+        Level::Error => 2,
+        Level::Warn => 1,
+        _ => 0,
+    }
+}
+
+/// Writes each external command as a line of the command file, which is usually a named pipe
+struct CommandFile {
+    path: PathBuf,
+}
+
+impl CallTarget for CommandFile {
+    fn call(&self, payload: &str, _record: &Record) -> Result<(), BoxError> {
+        let mut file = OpenOptions::new().append(true).open(&self.path)?;
+        file.write_all(format!("{payload}\n").as_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use log::Record;
+
+use crate::{BoxError, CallLoggerBuilder, CallTarget};
+
+impl CallLoggerBuilder {
+    /// Discards the formatted output of each log event instead of calling the call target.  The level filtering,
+    /// formatting and middleware of the logger still run, so this is useful for measuring the overhead of the logger
+    /// itself, e.g. in benchmarks.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_noop_target()
+    ///     .init();
+    /// log::info!("msg");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_noop_target(mut self) -> CallLoggerBuilder {
+        self.dispatcher.target = Some(Box::new(NoopTarget));
+        self
+    }
+}
+
+/// A call target that does nothing with the log events
+pub(crate) struct NoopTarget;
+
+impl CallTarget for NoopTarget {
+    fn call(&self, _payload: &str, _record: &Record) -> Result<(), BoxError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use log::Level;
+
+use crate::{push_json_escaped, CallLoggerBuilder, FormatContext};
+
+impl CallLoggerBuilder {
+    /// Publishes each log event to an [ntfy][ntfy] topic as a push notification.  The `topic_url` is the URL of the
+    /// topic, e.g. `https://ntfy.sh/my_app_alerts` or a topic on a self-hosted server.  The title is the level and
+    /// target of the log event, the priority is `high` for errors, `default` for warnings, `low` for info and `min`
+    /// for debug and trace, and errors and warnings are tagged with an emoji.  The key-value pairs `title`, `priority`
+    /// (1 to 5) and `tags` (separated by commas) of the log event are used instead when they are set.  The
+    /// notification is published as JSON and the formatter is not used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .ntfy("https://ntfy.sh/my_app_alerts")
+    ///     .init();
+    /// ```
+    ///
+    /// [ntfy]: https://docs.ntfy.sh/publish/#publish-as-json
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn ntfy<U>(mut self, topic_url: U) -> CallLoggerBuilder
+    where
+        U: AsRef<str>,
+    {
+        let topic_url = topic_url.as_ref().trim_end_matches('/');
+        let (server, topic) = topic_url.rsplit_once('/').unwrap_or((topic_url, ""));
+        let topic = topic.to_string();
+        self.formatter = Box::new(move |buffer, context| ntfy_formatter(buffer, &topic, context));
+        self.with_call_target(server)
+    }
+}
+
+fn ntfy_formatter(buffer: &mut String, topic: &str, context: &FormatContext) {
+    let record = context.record();
+    let kv = context.kv();
+    buffer.push_str("{\"topic\":\"");
+    push_json_escaped(buffer, topic);
+    buffer.push_str("\",\"title\":\"");
+    match kv.get("title") {
+        Some(title) => push_json_escaped(buffer, title),
+        None => {
+            push_json_escaped(buffer, context.level());
+            buffer.push(' ');
+            push_json_escaped(buffer, record.target());
+        }
+    }
+    buffer.push_str("\",\"message\":\"");
+    push_json_escaped(buffer, &context.message().to_string());
+    buffer.push_str("\",\"priority\":");
+    let priority = kv
+        .get("priority")
+        .and_then(|priority| priority.parse::<u8>().ok())
+        .filter(|priority| (1..=5).contains(priority))
+        .unwrap_or(match record.level() {
+            Level::Error => 4,
+            Level::Warn => 3,
+            Level::Info => 2,
+            Level::Debug | Level::Trace => 1,
+        });
+    buffer.push_str(&priority.to_string());
+    buffer.push_str(",\"tags\":[");
+    let level_tag = match record.level() {
+        Level::Error => Some("rotating_light"),
+        Level::Warn => Some("warning"),
+        _ => None,
+    };
+    let tags = level_tag.into_iter().chain(
+        kv.get("tags")
+            .map(|tags| tags.split(',').map(str::trim).filter(|tag| !tag.is_empty()))
+            .into_iter()
+            .flatten(),
+    );
+    for (i, tag) in tags.enumerate() {
+        if i > 0 {
+            buffer.push(',');
+        }
+        buffer.push('"');
+        push_json_escaped(buffer, tag);
+        buffer.push('"');
+    }
+    buffer.push_str("]}");
+}
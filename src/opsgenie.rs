@@ -0,0 +1,121 @@
+use log::{kv::Key, Level, Record};
+
+use crate::{encode_query, push_json_escaped, CallLoggerBuilder, FormatContext};
+
+/// The alerts API of Opsgenie
+const ALERTS_URL: &str = "https://api.opsgenie.com/v2/alerts";
+
+/// The longest message of an alert, longer messages are in the description
+const MAX_MESSAGE: usize = 130;
+
+impl CallLoggerBuilder {
+    /// Creates [Opsgenie][opsgenie] alerts for error and warning log events, with the priority `P1` for errors and
+    /// `P3` for warnings.  The alias of the alert is the target of the log event, so repeated log events from the same
+    /// target are counted against one open alert instead of each raising a new one, and the target can be set to
+    /// group them differently, e.g. `log::error!(target: "database", ...)`.  A log event with the key-value pair
+    /// `resolved = true` closes the alert of its target, see [`CallLoggerBuilder::opsgenie_resolved_when`] to decide
+    /// this differently.  Other log events are not sent and the formatter is not used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .opsgenie("my-api-key")
+    ///     .init();
+    /// log::error!(target: "database", "connection lost");
+    /// log::info!(target: "database", resolved = true; "connection restored");
+    /// ```
+    ///
+    /// [opsgenie]: https://docs.opsgenie.com/docs/alert-api
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn opsgenie<K>(self, api_key: K) -> CallLoggerBuilder
+    where
+        K: AsRef<str>,
+    {
+        self.opsgenie_resolved_when(api_key, has_resolved)
+    }
+
+    /// As [`CallLoggerBuilder::opsgenie`], but the alert of the target of a log event is closed when `resolved` returns
+    /// `true` for it, whatever its level.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .opsgenie_resolved_when("my-api-key", |record| record.args().to_string().starts_with("Recovered"))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn opsgenie_resolved_when<K, F>(mut self, api_key: K, resolved: F) -> CallLoggerBuilder
+    where
+        K: AsRef<str>,
+        F: Fn(&Record) -> bool + Sync + Send + 'static,
+    {
+        self.dispatcher.http_headers.push((
+            "Authorization".to_string(),
+            format!("GenieKey {}", api_key.as_ref()),
+        ));
+        self.dispatcher.accepts = Some(Box::new(|payload, _| !payload.is_empty()));
+        self.dispatcher.route = Some(Box::new(|payload, record| {
+            if payload.starts_with("{\"message\":") {
+                ALERTS_URL.to_string()
+            } else {
+                format!(
+                    "{ALERTS_URL}/{}/close?identifierType=alias",
+                    encode_query(record.target())
+                )
+            }
+        }));
+        self.formatter = Box::new(move |buffer, context| {
+            if resolved(context.record()) {
+                close_formatter(buffer, context);
+            } else if context.record().level() <= Level::Warn {
+                alert_formatter(buffer, context);
+            }
+        });
+        self.with_call_target(ALERTS_URL)
+    }
+}
+
+/// Whether a log event has the key-value pair `resolved = true`
+pub(crate) fn has_resolved(record: &Record) -> bool {
+    record
+        .key_values()
+        .get(Key::from_str("resolved"))
+        .and_then(|value| value.to_bool())
+        .unwrap_or(false)
+}
+
+/// Writes the body of the request that creates an alert
+fn alert_formatter(buffer: &mut String, context: &FormatContext) {
+    let record = context.record();
+    let message = context.message().to_string();
+    buffer.push_str("{\"message\":\"");
+    push_json_escaped(
+        buffer,
+        &message.chars().take(MAX_MESSAGE).collect::<String>(),
+    );
+    buffer.push_str("\",\"alias\":\"");
+    push_json_escaped(buffer, record.target());
+    buffer.push_str("\",\"description\":\"");
+    push_json_escaped(buffer, &message);
+    buffer.push_str("\",\"priority\":\"");
+    buffer.push_str(match record.level() {
+        Level::Error => "P1",
+        _ => "P3",
+    });
+    buffer.push_str("\",\"source\":\"");
+    push_json_escaped(buffer, context.hostname());
+    buffer.push_str("\"}");
+}
+
+/// Writes the body of the request that closes an alert
+fn close_formatter(buffer: &mut String, context: &FormatContext) {
+    buffer.push_str("{\"source\":\"");
+    push_json_escaped(buffer, context.hostname());
+    buffer.push_str("\",\"note\":\"");
+    push_json_escaped(buffer, &context.message().to_string());
+    buffer.push_str("\"}");
+}
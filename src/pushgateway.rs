@@ -0,0 +1,134 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use log::Level;
+
+use crate::{base64, encode_query, CallLoggerBuilder, FormatContext, Target};
+
+impl CallLoggerBuilder {
+    /// Pushes a counter of the log events at each level to a [Prometheus Pushgateway][pushgateway] as well as
+    /// delivering them to the call target, so that alerts on the rate of errors can be raised even when the log events
+    /// themselves go elsewhere.  The counter is `log_records_total` with a `level` label, and all of the counters of
+    /// the `job` are pushed to `url` after each log event.  The counts start from zero when the logger is built.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .json_stdout()
+    ///     .prometheus_pushgateway("http://pushgateway:9091", "my_app")
+    ///     .init();
+    /// ```
+    ///
+    /// [pushgateway]: https://github.com/prometheus/pushgateway#api
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn prometheus_pushgateway<U, J>(self, url: U, job: J) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+        J: Into<String>,
+    {
+        self.with_target(pushgateway_target(&url.into(), &job.into(), false))
+    }
+
+    /// Pushes counters of the log events to a Prometheus Pushgateway in the same way as
+    /// [`CallLoggerBuilder::prometheus_pushgateway`], and also counts them per module as
+    /// `log_records_by_module_total` with a `module` and a `level` label, so that the module whose errors set off an
+    /// alert can be seen.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .json_stdout()
+    ///     .prometheus_pushgateway_by_module("http://pushgateway:9091", "my_app")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn prometheus_pushgateway_by_module<U, J>(self, url: U, job: J) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+        J: Into<String>,
+    {
+        self.with_target(pushgateway_target(&url.into(), &job.into(), true))
+    }
+}
+
+/// A target that pushes the counters of the job, which are counted as the log events are formatted for it
+fn pushgateway_target(url: &str, job: &str, by_module: bool) -> Target {
+    // a job with a slash can only be in the path in base64
+    let job = if job.contains('/') {
+        let encoded = base64(job.as_bytes()).replace('+', "-").replace('/', "_");
+        format!("job@base64/{encoded}")
+    } else {
+        format!("job/{}", encode_query(job))
+    };
+    let mut target = Target::http(format!("{}/metrics/{job}", url.trim_end_matches('/')));
+    target.dispatcher.http_headers.push((
+        "Content-Type".to_string(),
+        "text/plain; version=0.0.4".to_string(),
+    ));
+    let counters = LogCounters {
+        levels: Default::default(),
+        modules: by_module.then(Default::default),
+    };
+    target.formatter = Some(Box::new(move |buffer, context| {
+        counters.count_and_write(buffer, context)
+    }));
+    target
+}
+
+/// The number of log events at each level, and per module and level if they are counted
+struct LogCounters {
+    levels: [AtomicU64; 5],
+    modules: Option<Mutex<BTreeMap<(String, Level), u64>>>,
+}
+
+impl LogCounters {
+    /// Counts the log event, then writes all of the counters in the Prometheus text format
+    fn count_and_write(&self, buffer: &mut String, context: &FormatContext) {
+        let record = context.record();
+        self.levels[record.level() as usize - 1].fetch_add(1, Ordering::Relaxed);
+        // writing to a String cannot fail
+        buffer.push_str("# TYPE log_records_total counter\n");
+        for level in Level::iter() {
+            let _ = writeln!(
+                buffer,
+                "log_records_total{{level=\"{}\"}} {}",
+                level.as_str().to_lowercase(),
+                self.levels[level as usize - 1].load(Ordering::Relaxed)
+            );
+        }
+        if let Some(modules) = &self.modules {
+            let module = record.module_path().unwrap_or(record.target());
+            let mut modules = modules.lock().unwrap_or_else(|x| x.into_inner());
+            *modules
+                .entry((module.to_string(), record.level()))
+                .or_default() += 1;
+            buffer.push_str("# TYPE log_records_by_module_total counter\n");
+            for ((module, level), count) in modules.iter() {
+                let _ = writeln!(
+                    buffer,
+                    "log_records_by_module_total{{module=\"{}\",level=\"{}\"}} {count}",
+                    escape_label(module),
+                    level.as_str().to_lowercase()
+                );
+            }
+        }
+    }
+}
+
+/// Escapes the value of a label in the Prometheus text format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
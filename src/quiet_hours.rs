@@ -0,0 +1,144 @@
+use std::ops::Range;
+
+use chrono::{Local, Timelike};
+use log::Level;
+
+use crate::{dispatch::Dispatcher, CallLoggerBuilder, Target};
+
+impl CallLoggerBuilder {
+    /// Changes how log events are handled between the local times `hours.start` and `hours.end`, written as `HH:MM`,
+    /// so that simple notification policies can be set without an alert manager, e.g. to stop paging at night.  The
+    /// hours can wrap past midnight, e.g. `"22:00".."07:00"`, and the end is not included.  When more than one window
+    /// covers the time of a log event, the least severe level is used, and it is routed to the last of the targets.
+    /// The logger can't be built if a time isn't valid, or the start and end are the same.
+    ///
+    /// Example - Page for errors during the day and email them at night
+    /// ```rust
+    /// # use call_logger::{CallLogger, QuietHoursAction, Target};
+    /// CallLogger::new()
+    ///     .with_call_target("https://events.pagerduty.com/v2/enqueue")
+    ///     .quiet_hours(
+    ///         "22:00".."07:00",
+    ///         QuietHoursAction::RouteTo(Target::http("https://postman-echo.com/post")),
+    ///     )
+    ///     .init();
+    /// ```
+    ///
+    /// Example - Treat errors as warnings during the hand-over between shifts
+    /// ```rust
+    /// # use call_logger::{CallLogger, QuietHoursAction};
+    /// # use log::Level;
+    /// CallLogger::new()
+    ///     .quiet_hours("17:30".."18:00", QuietHoursAction::DowngradeTo(Level::Warn))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn quiet_hours<T>(mut self, hours: Range<T>, action: QuietHoursAction) -> CallLoggerBuilder
+    where
+        T: AsRef<str>,
+    {
+        let hours = HourRange::parse(hours.start.as_ref(), hours.end.as_ref());
+        self.quiet_hours.push((hours, action));
+        self
+    }
+}
+
+/// What is done with log events during the hours set with [`CallLoggerBuilder::quiet_hours`].
+#[non_exhaustive]
+#[allow(clippy::large_enum_variant)]
+pub enum QuietHoursAction {
+    /// Log events that are more severe than this level are lowered to it, so that they are filtered, formatted and
+    /// routed as if they had been logged at that level
+    DowngradeTo(Level),
+    /// Log events are delivered to this target instead of the call target and the other targets
+    RouteTo(Target),
+}
+
+/// A window of the day in local time, from the minute of the start up to the minute of the end
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct HourRange {
+    start: u32,
+    end: u32,
+}
+
+impl HourRange {
+    /// Parses the start and end of the window, or returns why one of them can't be
+    fn parse(start: &str, end: &str) -> Result<HourRange, String> {
+        let minute = |time: &str| {
+            let (hours, minutes) = time.split_once(':')?;
+            let digits = |x: &str| x.bytes().all(|b| b.is_ascii_digit());
+            if !(1..=2).contains(&hours.len())
+                || minutes.len() != 2
+                || !digits(hours)
+                || !digits(minutes)
+            {
+                return None;
+            }
+            let hours = hours.parse::<u32>().ok().filter(|x| *x < 24)?;
+            let minutes = minutes.parse::<u32>().ok().filter(|x| *x < 60)?;
+            Some(hours * 60 + minutes)
+        };
+        let parsed = |time: &str| {
+            minute(time)
+                .ok_or_else(|| format!("the quiet hours time `{time}` is not a valid HH:MM time"))
+        };
+        let range = HourRange {
+            start: parsed(start)?,
+            end: parsed(end)?,
+        };
+        if range.start == range.end {
+            return Err(format!(
+                "the quiet hours `{start}` to `{end}` start and end at the same time"
+            ));
+        }
+        Ok(range)
+    }
+
+    /// Whether the window covers the current local time
+    pub(crate) fn is_now(&self) -> bool {
+        let now = Local::now();
+        self.covers(now.hour() * 60 + now.minute())
+    }
+
+    fn covers(&self, minute: u32) -> bool {
+        if self.start < self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+}
+
+impl CallLoggerBuilder {
+    /// Sets up the windows that route log events to another target, which then only delivers during its window while
+    /// the other targets don't, and returns the windows that lower the level of log events
+    pub(crate) fn set_up_quiet_hours(&mut self) -> Result<Vec<(HourRange, Level)>, String> {
+        let mut downgrades = Vec::new();
+        for (hours, action) in std::mem::take(&mut self.quiet_hours) {
+            let hours = hours?;
+            match action {
+                QuietHoursAction::DowngradeTo(level) => downgrades.push((hours, level)),
+                QuietHoursAction::RouteTo(mut target) => {
+                    for dispatcher in std::iter::once(&mut self.dispatcher)
+                        .chain(self.targets.iter_mut().map(|target| &mut target.dispatcher))
+                    {
+                        dispatcher.quiet_hours.push(hours);
+                    }
+                    target.dispatcher.only_during = Some(hours);
+                    self.targets.push(target);
+                }
+            }
+        }
+        Ok(downgrades)
+    }
+}
+
+impl Dispatcher {
+    /// Whether log events are delivered at the current time, which they aren't during its quiet hours or outside of
+    /// the hours of a target that quiet hours are routed to
+    pub(crate) fn is_on_duty(&self) -> bool {
+        self.only_during.map_or(true, |hours| hours.is_now())
+            && !self.quiet_hours.iter().any(HourRange::is_now)
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    sync::Mutex,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{dispatch::Dispatcher, in_flight::InFlight, CallLoggerBuilder, Target};
+
+impl CallLoggerBuilder {
+    /// Limits the number of log events that are delivered to the call target to `per_second`, with bursts of up to
+    /// `per_second` allowed after a quiet spell, e.g. for a paid API that throttles or charges for each call.  Log
+    /// events over the limit are delivered to the fallback set with [`CallLoggerBuilder::with_over_limit_fallback`],
+    /// or fail with [`CallErrorKind::OverLimit`](crate::CallErrorKind::OverLimit) so that they are reported and
+    /// written to the dead-letter file.  Each log event counts as a call, even when it is batched.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://api.pushover.net/1/messages.json")
+    ///     .with_rate_limit(5)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_rate_limit(mut self, per_second: u32) -> CallLoggerBuilder {
+        self.dispatcher.rate_limit = Some(RateLimit::new(per_second));
+        self
+    }
+
+    /// Limits the number of log events that are delivered to the call target to `calls` each day, which starts at
+    /// midnight UTC, e.g. for a paid API with a hard quota.  Log events over the budget are handled in the same way
+    /// as those over the limit set with [`CallLoggerBuilder::with_rate_limit`].  The budget is only counted by this
+    /// logger, so it starts again when the application restarts.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://api.pushover.net/1/messages.json")
+    ///     .with_daily_budget(300)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_daily_budget(mut self, calls: u32) -> CallLoggerBuilder {
+        self.dispatcher.daily_budget = Some(DailyBudget::new(calls));
+        self
+    }
+
+    /// Delivers the log events that are over the rate limit or daily budget of the call target to `fallback` instead,
+    /// e.g. to send them to email once the quota of a pager is used up.  The fallback is sent the payload that was
+    /// formatted for the call target, so its own formatter isn't used, and it is restricted to the same applications
+    /// and URLs.  A fallback that batches or sends a digest is flushed with the logger, and its failures are reported
+    /// in the same way as those of the call target.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::{CallLogger, Target};
+    /// CallLogger::new()
+    ///     .with_call_target("https://api.pushover.net/1/messages.json")
+    ///     .with_daily_budget(300)
+    ///     .with_over_limit_fallback(Target::http("https://postman-echo.com/post"))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_over_limit_fallback(mut self, fallback: Target) -> CallLoggerBuilder {
+        self.dispatcher.over_limit_fallback = Some(Box::new(fallback.dispatcher));
+        self
+    }
+}
+
+impl Target {
+    /// Limits the number of log events that are delivered to this target, in the same way as
+    /// [`CallLoggerBuilder::with_rate_limit`].
+    #[inline]
+    #[must_use]
+    pub fn with_rate_limit(mut self, per_second: u32) -> Target {
+        self.dispatcher.rate_limit = Some(RateLimit::new(per_second));
+        self
+    }
+
+    /// Limits the number of log events that are delivered to this target each day, in the same way as
+    /// [`CallLoggerBuilder::with_daily_budget`].
+    #[inline]
+    #[must_use]
+    pub fn with_daily_budget(mut self, calls: u32) -> Target {
+        self.dispatcher.daily_budget = Some(DailyBudget::new(calls));
+        self
+    }
+
+    /// Delivers the log events that are over the limits of this target to `fallback` instead, in the same way as
+    /// [`CallLoggerBuilder::with_over_limit_fallback`].
+    #[inline]
+    #[must_use]
+    pub fn with_over_limit_fallback(mut self, fallback: Target) -> Target {
+        self.dispatcher.over_limit_fallback = Some(Box::new(fallback.dispatcher));
+        self
+    }
+}
+
+/// A token bucket that holds up to a second of calls, and is refilled as time passes
+pub(crate) struct RateLimit {
+    per_second: f64,
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimit {
+    pub(crate) fn new(per_second: u32) -> RateLimit {
+        let per_second = f64::from(per_second);
+        RateLimit {
+            per_second,
+            bucket: Mutex::new((per_second, Instant::now())),
+        }
+    }
+
+    /// Takes a call from the bucket, or returns false if it is empty
+    fn try_take(&self) -> bool {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|x| x.into_inner());
+        let (tokens, refilled) = &mut *bucket;
+        let now = Instant::now();
+        *tokens = (*tokens + now.duration_since(*refilled).as_secs_f64() * self.per_second)
+            .min(self.per_second);
+        *refilled = now;
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The number of calls that can be made each day, and the number made on the current day
+pub(crate) struct DailyBudget {
+    calls: u32,
+    used: Mutex<(u64, u32)>,
+}
+
+impl DailyBudget {
+    pub(crate) fn new(calls: u32) -> DailyBudget {
+        DailyBudget {
+            calls,
+            used: Mutex::new((0, 0)),
+        }
+    }
+
+    /// Counts a call against the budget of today, or returns false if it has been used up
+    fn try_spend(&self) -> bool {
+        let today = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            / 86400;
+        let mut used = self.used.lock().unwrap_or_else(|x| x.into_inner());
+        if used.0 != today {
+            *used = (today, 0);
+        }
+        if used.1 < self.calls {
+            used.1 += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Dispatcher {
+    /// Counts a call against the rate limit and daily budget, or returns why it is over one of them
+    pub(crate) fn over_limit(&self) -> Option<String> {
+        if let Some(rate_limit) = &self.rate_limit {
+            if !rate_limit.try_take() {
+                return Some(format!(
+                    "the rate limit of {} calls a second was reached",
+                    rate_limit.per_second
+                ));
+            }
+        }
+        if let Some(budget) = &self.daily_budget {
+            if !budget.try_spend() {
+                return Some(format!(
+                    "the daily budget of {} calls is used up",
+                    budget.calls
+                ));
+            }
+        }
+        None
+    }
+
+    /// The fallback of this dispatcher, then the fallback of that, and so on
+    pub(crate) fn fallbacks(&self) -> impl Iterator<Item = &Dispatcher> {
+        std::iter::successors(self.over_limit_fallback.as_deref(), |fallback| {
+            fallback.over_limit_fallback.as_deref()
+        })
+    }
+
+    /// Restricts the fallback to the same applications and URLs as this dispatcher, before they are validated
+    pub(crate) fn restrict_fallback(&mut self) {
+        let Some(fallback) = &mut self.over_limit_fallback else {
+            return;
+        };
+        #[cfg(all(feature = "process", not(target_arch = "wasm32")))]
+        if fallback.allowed_programs.is_none() {
+            fallback.allowed_programs = self.allowed_programs.clone();
+        }
+        if fallback.allowed_schemes.is_none() {
+            fallback.allowed_schemes = self.allowed_schemes.clone();
+        }
+        if fallback.allowed_hosts.is_none() {
+            fallback.allowed_hosts = self.allowed_hosts.clone();
+        }
+        fallback.restrict_fallback();
+    }
+
+    /// Sets up the fallback to send requests in the same way as this dispatcher.  Its failures are reported by this
+    /// dispatcher, so they go to the same error handler and dead-letter file.
+    pub(crate) fn set_up_fallback(&mut self) {
+        let Some(fallback) = &mut self.over_limit_fallback else {
+            return;
+        };
+        fallback.fingerprint = self.fingerprint.clone();
+        if fallback.suppressed_modules.is_empty() {
+            fallback.suppressed_modules = self.suppressed_modules.clone();
+        }
+        if fallback.in_flight.is_none() {
+            fallback.in_flight = self.in_flight.as_ref().map(InFlight::fresh);
+        }
+        if fallback.event_ttl.is_none() {
+            fallback.event_ttl = self.event_ttl.clone();
+        }
+        #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+        {
+            if fallback.dns.is_none() {
+                fallback.dns = self.dns.clone();
+            }
+            if fallback.prefer_ip.is_none() {
+                fallback.prefer_ip = self.prefer_ip;
+            }
+            if fallback.dns.is_some() || fallback.prefer_ip.is_some() {
+                fallback.agent = Some(crate::dns::agent(fallback.dns.clone(), fallback.prefer_ip));
+            }
+        }
+        fallback.set_up_fallback();
+    }
+}
@@ -0,0 +1,209 @@
+use serde_json::{Map, Value};
+
+use crate::{CallError, CallErrorKind, CallLoggerBuilder};
+
+impl CallLoggerBuilder {
+    /// Checks that the formatted output of each log event, after the middleware, is JSON that matches the
+    /// [JSON Schema][schema] `schema_json` before it is delivered to the call target, so that a mistake in a
+    /// hand-written formatter doesn't reach the ingestion pipeline.  A log event that doesn't match is not delivered,
+    /// and is passed to the closure set with [`CallLoggerBuilder::on_call_error`] and written to the dead-letter file
+    /// if there is one.  Building the logger fails if the schema is not valid JSON.
+    ///
+    /// The keywords `type`, `enum`, `const`, `properties`, `required`, `additionalProperties`, `items`, `minItems`,
+    /// `maxItems`, `minLength`, `maxLength`, `minimum`, `maximum`, `allOf`, `anyOf`, `oneOf` and `not` are checked,
+    /// other keywords, e.g. `pattern` and `$ref`, are ignored.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .format(|_, message, record| format!("{{\"level\":\"{}\",\"msg\":\"{message}\"}}", record.level()))
+    ///     .validate_against_schema(r#"{"type": "object", "required": ["level", "msg"]}"#)
+    ///     .init();
+    /// ```
+    ///
+    /// [schema]: https://json-schema.org/understanding-json-schema/reference
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn validate_against_schema<S>(mut self, schema_json: S) -> CallLoggerBuilder
+    where
+        S: AsRef<str>,
+    {
+        self.dispatcher.schema =
+            Some(serde_json::from_str(schema_json.as_ref()).map_err(|x| x.to_string()));
+        self
+    }
+}
+
+/// Checks the formatted output of a log event against the schema
+pub(crate) fn check(schema: &Value, payload: &str) -> Result<(), CallError> {
+    let invalid = |reason: String| {
+        CallError::new(
+            CallErrorKind::Schema,
+            format!("the payload doesn't match the schema, {reason}"),
+        )
+    };
+    let instance = serde_json::from_str(payload).map_err(|x| invalid(format!("not JSON, {x}")))?;
+    violation(schema, &instance, "$").map_or(Ok(()), |reason| Err(invalid(reason)))
+}
+
+/// The first way that the value at `path` doesn't match the schema, or `None` if it matches
+fn violation(schema: &Value, instance: &Value, path: &str) -> Option<String> {
+    let schema = match schema {
+        Value::Bool(true) => return None,
+        Value::Bool(false) => return Some(format!("{path} is not allowed")),
+        Value::Object(schema) => schema,
+        _ => return None,
+    };
+    if let Some(types) = schema.get("type") {
+        let allowed = match types {
+            Value::Array(types) => types.iter().any(|x| has_type(instance, x)),
+            _ => has_type(instance, types),
+        };
+        if !allowed {
+            return Some(format!("{path} is not of type {types}"));
+        }
+    }
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        if !values.contains(instance) {
+            return Some(format!(
+                "{path} is not one of {}",
+                Value::Array(values.clone())
+            ));
+        }
+    }
+    if let Some(value) = schema.get("const") {
+        if value != instance {
+            return Some(format!("{path} is not {value}"));
+        }
+    }
+    match instance {
+        Value::Object(members) => {
+            if let Some(reason) = object_violation(schema, members, path) {
+                return Some(reason);
+            }
+        }
+        Value::Array(items) => {
+            if let Some(reason) = array_violation(schema, items, path) {
+                return Some(reason);
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count() as f64;
+            if limit(schema, "minLength").is_some_and(|min| length < min) {
+                return Some(format!("{path} is shorter than the minLength"));
+            }
+            if limit(schema, "maxLength").is_some_and(|max| length > max) {
+                return Some(format!("{path} is longer than the maxLength"));
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if limit(schema, "minimum").is_some_and(|min| number < min) {
+                return Some(format!("{path} is less than the minimum"));
+            }
+            if limit(schema, "maximum").is_some_and(|max| number > max) {
+                return Some(format!("{path} is more than the maximum"));
+            }
+        }
+        _ => {}
+    }
+    if let Some(Value::Array(schemas)) = schema.get("allOf") {
+        if let Some(reason) = schemas.iter().find_map(|x| violation(x, instance, path)) {
+            return Some(reason);
+        }
+    }
+    if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+        if schemas
+            .iter()
+            .all(|x| violation(x, instance, path).is_some())
+        {
+            return Some(format!("{path} doesn't match any of the anyOf schemas"));
+        }
+    }
+    if let Some(Value::Array(schemas)) = schema.get("oneOf") {
+        let matches = schemas
+            .iter()
+            .filter(|x| violation(x, instance, path).is_none())
+            .count();
+        if matches != 1 {
+            return Some(format!(
+                "{path} matches {matches} of the oneOf schemas instead of one"
+            ));
+        }
+    }
+    if let Some(not) = schema.get("not") {
+        if violation(not, instance, path).is_none() {
+            return Some(format!("{path} matches the not schema"));
+        }
+    }
+    None
+}
+
+fn object_violation(
+    schema: &Map<String, Value>,
+    members: &Map<String, Value>,
+    path: &str,
+) -> Option<String> {
+    if let Some(Value::Array(required)) = schema.get("required") {
+        if let Some(missing) = required
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|x| !members.contains_key(*x))
+        {
+            return Some(format!("{path} is missing the required `{missing}`"));
+        }
+    }
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (key, value) in members {
+        let member_path = format!("{path}.{key}");
+        let reason = match properties.and_then(|x| x.get(key)) {
+            Some(property) => violation(property, value, &member_path),
+            None => schema
+                .get("additionalProperties")
+                .and_then(|x| violation(x, value, &member_path)),
+        };
+        if reason.is_some() {
+            return reason;
+        }
+    }
+    None
+}
+
+fn array_violation(schema: &Map<String, Value>, items: &[Value], path: &str) -> Option<String> {
+    let length = items.len() as f64;
+    if limit(schema, "minItems").is_some_and(|min| length < min) {
+        return Some(format!("{path} has fewer than the minItems"));
+    }
+    if limit(schema, "maxItems").is_some_and(|max| length > max) {
+        return Some(format!("{path} has more than the maxItems"));
+    }
+    let item_schema = schema.get("items")?;
+    items
+        .iter()
+        .enumerate()
+        .find_map(|(index, item)| violation(item_schema, item, &format!("{path}[{index}]")))
+}
+
+/// A numeric keyword of the schema
+fn limit(schema: &Map<String, Value>, keyword: &str) -> Option<f64> {
+    schema.get(keyword).and_then(Value::as_f64)
+}
+
+/// Whether a value is of a JSON Schema type
+fn has_type(instance: &Value, schema_type: &Value) -> bool {
+    match schema_type.as_str() {
+        Some("null") => instance.is_null(),
+        Some("boolean") => instance.is_boolean(),
+        Some("object") => instance.is_object(),
+        Some("array") => instance.is_array(),
+        Some("string") => instance.is_string(),
+        Some("number") => instance.is_number(),
+        Some("integer") => {
+            instance.is_i64()
+                || instance.is_u64()
+                || instance.as_f64().is_some_and(|x| x.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+};
+
+thread_local! {
+    /// The fields that have been pushed on this thread and not yet removed, with the id of the guard of each
+    static SCOPED_FIELDS: RefCell<Vec<(u64, String, String)>> = const { RefCell::new(Vec::new()) };
+
+    /// The id of the next guard on this thread
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Adds a field to every log event that is logged on this thread until the returned guard is dropped, e.g. the id of
+/// the request that is being handled, so that log events can be correlated with it.  The default formatter adds the
+/// fields after the key-value pairs of the log event, and they are passed to a formatter set with
+/// [`CallLoggerBuilder::format_with`](crate::CallLoggerBuilder::format_with).
+///
+/// The fields are kept per thread, so they are not seen by log events from other threads or from async tasks that
+/// move between threads.
+///
+/// Example
+/// ```rust
+/// let _ = call_logger::CallLogger::new().init();
+/// {
+///     let _guard = call_logger::push_context("request_id", "7f3a");
+///     log::info!("handling request");
+/// }
+/// log::info!("no request id");
+/// ```
+#[must_use = "The field is removed when the guard is dropped"]
+pub fn push_context<K, V>(key: K, value: V) -> ContextGuard
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    let id = NEXT_ID.with(|next_id| {
+        let id = next_id.get();
+        next_id.set(id + 1);
+        id
+    });
+    SCOPED_FIELDS.with(|fields| fields.borrow_mut().push((id, key.into(), value.into())));
+    ContextGuard {
+        id,
+        _not_send: PhantomData,
+    }
+}
+
+/// Removes the field added by [`push_context`] when it is dropped.  It must be dropped on the thread that it was
+/// created on.
+pub struct ContextGuard {
+    id: u64,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        let _ = SCOPED_FIELDS.try_with(|fields| {
+            fields.borrow_mut().retain(|(id, _, _)| *id != self.id);
+        });
+    }
+}
+
+/// The fields that have been pushed on this thread, oldest first
+pub(crate) fn scoped_fields() -> Vec<(String, String)> {
+    SCOPED_FIELDS
+        .try_with(|fields| {
+            fields
+                .borrow()
+                .iter()
+                .map(|(_, key, value)| (key.clone(), value.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,192 @@
+use std::ops::Range;
+
+use crate::CallLoggerBuilder;
+
+impl CallLoggerBuilder {
+    /// Replaces the email addresses in the formatted output of each log event with `[email]`, as a stage of the
+    /// middleware, so that they aren't sent to the call target.  An address is a local part of letters, digits and
+    /// `._%+-`, then `@`, then a domain with at least two labels whose last label is at least two letters.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .scrub_emails()
+    ///     .init();
+    /// log::info!("password reset for someone@example.com");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn scrub_emails(self) -> CallLoggerBuilder {
+        self.with_middleware(|payload| Some(scrub_emails(payload)))
+    }
+
+    /// Replaces the IPv4 addresses in the formatted output of each log event with `[ipv4]`, as a stage of the
+    /// middleware.  An address is four numbers from 0 to 255 separated by dots, that isn't part of a longer run of
+    /// numbers and dots such as a version number, e.g. `1.2.3.4.5`.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .scrub_ipv4()
+    ///     .init();
+    /// log::warn!("too many login attempts from 203.0.113.7");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn scrub_ipv4(self) -> CallLoggerBuilder {
+        self.with_middleware(|payload| Some(scrub_ipv4(payload)))
+    }
+
+    /// Replaces the payment card numbers in the formatted output of each log event with `[card]`, as a stage of the
+    /// middleware.  A card number is 13 to 19 digits starting with 2 to 6, which may be grouped with single spaces or
+    /// hyphens, that pass the Luhn check, so that other long numbers such as order ids are mostly left alone.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .scrub_credit_cards()
+    ///     .init();
+    /// log::error!("payment declined for 4111 1111 1111 1111");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn scrub_credit_cards(self) -> CallLoggerBuilder {
+        self.with_middleware(|payload| Some(scrub_credit_cards(payload)))
+    }
+}
+
+/// Replaces the email addresses in the text with `[email]`
+pub(crate) fn scrub_emails(text: String) -> String {
+    let is_local = |b: u8| b.is_ascii_alphanumeric() || b"._%+-".contains(&b);
+    let is_domain = |b: u8| b.is_ascii_alphanumeric() || b == b'.' || b == b'-';
+    let bytes = text.as_bytes();
+    let mut matches: Vec<Range<usize>> = Vec::new();
+    for at in (0..bytes.len()).filter(|i| bytes[*i] == b'@') {
+        if matches.last().is_some_and(|last| at < last.end) {
+            continue;
+        }
+        let start = bytes[..at]
+            .iter()
+            .rposition(|b| !is_local(*b))
+            .map_or(0, |x| x + 1);
+        let start = start.max(matches.last().map_or(0, |last| last.end));
+        let mut end = bytes[at + 1..]
+            .iter()
+            .position(|b| !is_domain(*b))
+            .map_or(bytes.len(), |x| at + 1 + x);
+        // a sentence can end straight after the address
+        while end > at + 1 && matches!(bytes[end - 1], b'.' | b'-') {
+            end -= 1;
+        }
+        let domain = &text[at + 1..end];
+        let mut labels = domain.split('.');
+        let valid_domain = domain.contains('.')
+            && labels.all(|label| !label.is_empty() && !label.starts_with('-'))
+            && domain
+                .rsplit('.')
+                .next()
+                .is_some_and(|tld| tld.len() >= 2 && tld.bytes().all(|b| b.is_ascii_alphabetic()));
+        if start < at && valid_domain {
+            matches.push(start..end);
+        }
+    }
+    mask(text, &matches, "[email]")
+}
+
+/// Replaces the IPv4 addresses in the text with `[ipv4]`
+pub(crate) fn scrub_ipv4(text: String) -> String {
+    let bytes = text.as_bytes();
+    let is_part = |b: u8| b.is_ascii_digit() || b == b'.';
+    let mut matches = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() || (i > 0 && is_part(bytes[i - 1])) {
+            i += 1;
+            continue;
+        }
+        // the whole run of digits and dots is taken, so that a longer dotted number isn't partly masked
+        let end = bytes[i..]
+            .iter()
+            .position(|b| !is_part(*b))
+            .map_or(bytes.len(), |x| i + x);
+        let run = text[i..end].trim_end_matches('.');
+        let octets = run.split('.').collect::<Vec<_>>();
+        if octets.len() == 4
+            && octets
+                .iter()
+                .all(|octet| (1..=3).contains(&octet.len()) && octet.parse::<u8>().is_ok())
+        {
+            matches.push(i..i + run.len());
+        }
+        i = end;
+    }
+    mask(text, &matches, "[ipv4]")
+}
+
+/// Replaces the payment card numbers in the text with `[card]`
+pub(crate) fn scrub_credit_cards(text: String) -> String {
+    let bytes = text.as_bytes();
+    let mut matches = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() || (i > 0 && bytes[i - 1].is_ascii_digit()) {
+            i += 1;
+            continue;
+        }
+        // digits with single separators between them
+        let mut digits = Vec::new();
+        let mut end = i;
+        while end < bytes.len() {
+            if bytes[end].is_ascii_digit() {
+                digits.push(bytes[end] - b'0');
+                end += 1;
+            } else if matches!(bytes[end], b' ' | b'-')
+                && bytes.get(end + 1).is_some_and(u8::is_ascii_digit)
+            {
+                end += 1;
+            } else {
+                break;
+            }
+        }
+        // card numbers start with 2 to 6, which leaves out epoch timestamps in milliseconds and microseconds
+        if (13..=19).contains(&digits.len()) && (2..=6).contains(&digits[0]) && luhn(&digits) {
+            matches.push(i..end);
+        }
+        i = end;
+    }
+    mask(text, &matches, "[card]")
+}
+
+/// Whether the digits pass the Luhn check that payment card numbers are made to pass
+fn luhn(digits: &[u8]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| match (i % 2 == 1, u32::from(*digit) * 2) {
+            (false, _) => u32::from(*digit),
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// Replaces the ranges of the text, which are in order and don't overlap, with the mask
+fn mask(text: String, matches: &[Range<usize>], mask: &str) -> String {
+    if matches.is_empty() {
+        return text;
+    }
+    let mut masked = String::with_capacity(text.len());
+    let mut last = 0;
+    for range in matches {
+        masked.push_str(&text[last..range.start]);
+        masked.push_str(mask);
+        last = range.end;
+    }
+    masked.push_str(&text[last..]);
+    masked
+}
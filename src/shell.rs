@@ -0,0 +1,131 @@
+use std::{ffi::OsString, process::Command};
+
+#[cfg(any(windows, test))]
+use std::path::{Path, PathBuf};
+
+use crate::CallLoggerBuilder;
+
+/// The commands that are built into `cmd.exe` rather than being programs that can be run directly
+#[cfg(any(windows, test))]
+const CMD_BUILTINS: [&str; 17] = [
+    "cd", "cls", "copy", "date", "del", "dir", "echo", "md", "mkdir", "move", "rd", "ren", "rmdir",
+    "set", "start", "time", "type",
+];
+
+impl CallLoggerBuilder {
+    /// Runs the call target through the system shell, `cmd /C` on Windows and `sh -c` elsewhere, so that shell
+    /// builtins and scripts that are not executable can be called.  The payload is still passed as a separate argument
+    /// rather than being part of the shell command line.  On Windows, scripts ending in `.ps1` are run with
+    /// `powershell -File`, scripts ending in `.bat` or `.cmd` and the `cmd.exe` builtins like `echo` are run with
+    /// `cmd /C`, and targets without an extension are looked up with the extensions in `PATHEXT`, whether or not this
+    /// is set.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("scripts/to_file.sh test.log")
+    ///     .via_shell()
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn via_shell(mut self) -> CallLoggerBuilder {
+        self.dispatcher.via_shell = true;
+        self
+    }
+}
+
+/// Makes the command that runs the program with the arguments, through the shell if `via_shell` is set
+#[cfg(windows)]
+pub(crate) fn command(program: &str, args: &[&str], via_shell: bool) -> Command {
+    let (program, prefix) = windows_invocation(program, via_shell, |name| find_program(name));
+    let mut command = Command::new(program);
+    command.args(prefix).args(args);
+    command
+}
+
+/// Makes the command that runs the program with the arguments, through the shell if `via_shell` is set
+#[cfg(not(windows))]
+pub(crate) fn command(program: &str, args: &[&str], via_shell: bool) -> Command {
+    let (program, prefix) = unix_invocation(program, via_shell);
+    let mut command = Command::new(program);
+    command.args(prefix).args(args);
+    command
+}
+
+/// The program to run and the arguments that go before the call target's own, for `sh`.  The arguments are passed
+/// to the shell as positional parameters so that they are never parsed by it.
+#[cfg(not(windows))]
+fn unix_invocation(program: &str, via_shell: bool) -> (OsString, Vec<OsString>) {
+    if via_shell {
+        (
+            "sh".into(),
+            vec!["-c".into(), "\"$0\" \"$@\"".into(), program.into()],
+        )
+    } else {
+        (program.into(), Vec::new())
+    }
+}
+
+/// The program to run and the arguments that go before the call target's own, for PowerShell scripts, `cmd.exe`
+/// scripts and builtins, and programs found with `PATHEXT`
+#[cfg(any(windows, test))]
+pub(crate) fn windows_invocation<F>(
+    program: &str,
+    via_shell: bool,
+    find: F,
+) -> (OsString, Vec<OsString>)
+where
+    F: Fn(&str) -> Option<PathBuf>,
+{
+    let resolved = match Path::new(program).extension() {
+        Some(_) => PathBuf::from(program),
+        None => find(program).unwrap_or_else(|| PathBuf::from(program)),
+    };
+    let extension = resolved
+        .extension()
+        .map(|x| x.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("ps1") => (
+            "powershell".into(),
+            vec![
+                "-NoProfile".into(),
+                "-ExecutionPolicy".into(),
+                "Bypass".into(),
+                "-File".into(),
+                resolved.into(),
+            ],
+        ),
+        Some("bat") | Some("cmd") => ("cmd".into(), vec!["/C".into(), resolved.into()]),
+        _ if via_shell || CMD_BUILTINS.contains(&program.to_ascii_lowercase().as_str()) => {
+            ("cmd".into(), vec!["/C".into(), resolved.into()])
+        }
+        _ => (resolved.into(), Vec::new()),
+    }
+}
+
+/// Looks for the program with each of the extensions in `PATHEXT`, and `.ps1`, either where it is if it is a path or
+/// in each of the directories in `PATH`
+#[cfg(windows)]
+fn find_program(program: &str) -> Option<PathBuf> {
+    let extensions = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    let extensions = extensions
+        .split(';')
+        .filter(|x| !x.is_empty())
+        .chain(std::iter::once(".PS1"))
+        .collect::<Vec<&str>>();
+    let directories = if program.contains(['/', '\\']) {
+        vec![PathBuf::new()]
+    } else {
+        std::env::var_os("PATH")
+            .map(|path| std::env::split_paths(&path).collect())
+            .unwrap_or_default()
+    };
+    directories.iter().find_map(|directory| {
+        extensions.iter().find_map(|extension| {
+            let candidate = directory.join(format!("{program}{extension}"));
+            candidate.is_file().then_some(candidate)
+        })
+    })
+}
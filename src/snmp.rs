@@ -0,0 +1,234 @@
+use std::{
+    net::{ToSocketAddrs, UdpSocket},
+    sync::atomic::{AtomicI32, Ordering},
+    time::Instant,
+};
+
+use log::{Level, Record};
+
+use crate::{internal::report, BoxError, CallLoggerBuilder, CallTarget, FormatContext};
+
+/// The subtree of the OIDs of the trap and its variable bindings, which is the Net-SNMP experimental subtree, so that
+/// traps don't clash with the MIB of a real product
+const BASE_OID: [u32; 9] = [1, 3, 6, 1, 4, 1, 8072, 9999, 9999];
+
+/// `sysUpTime.0`, the first variable binding of every trap
+const SYS_UP_TIME: [u32; 9] = [1, 3, 6, 1, 2, 1, 1, 3, 0];
+
+/// `snmpTrapOID.0`, the second variable binding of every trap, with the OID of the trap as its value
+const SNMP_TRAP_OID: [u32; 11] = [1, 3, 6, 1, 6, 3, 1, 1, 4, 1, 0];
+
+/// Separates the fields of the payload that the trap is built from, as it is unlikely to be in a log event
+const SEPARATOR: char = '\0';
+
+impl CallLoggerBuilder {
+    /// Sends error log events as SNMPv2c traps to the SNMP manager at `manager_addr`, usually on port 162, instead of
+    /// calling the call target, so that a network operations center that only watches SNMP sees the errors of an
+    /// application.  The formatter is not used.
+    ///
+    /// The trap is `1.3.6.1.4.1.8072.9999.9999.0.1` and, after `sysUpTime` and `snmpTrapOID`, has the message of the
+    /// log event bound to `1.3.6.1.4.1.8072.9999.9999.1.1`, the target to `.1.2`, and the key-value pairs and static
+    /// fields as `key=value` strings bound to `.2.1`, `.2.2` and so on.  If the address cannot be resolved, the error
+    /// is printed and log events are dropped.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .snmp_trap("127.0.0.1:162", "public")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn snmp_trap<A, C>(mut self, manager_addr: A, community: C) -> CallLoggerBuilder
+    where
+        A: ToSocketAddrs,
+        C: Into<String>,
+    {
+        self.dispatcher.accepts = Some(Box::new(|_, record| record.level() == Level::Error));
+        self.formatter = Box::new(snmp_formatter);
+        self.dispatcher.target = Some(Box::new(TrapSender::new(manager_addr, community.into())));
+        self
+    }
+}
+
+/// Writes the target, message and fields of a log event separated by [`SEPARATOR`]
+fn snmp_formatter(buffer: &mut String, context: &FormatContext) {
+    buffer.push_str(context.record().target());
+    buffer.push(SEPARATOR);
+    let message = context.message();
+    match message.as_str() {
+        Some(message) => buffer.push_str(message),
+        None => buffer.push_str(&message.to_string()),
+    }
+    for (key, value) in context
+        .kv()
+        .iter()
+        .chain(context.static_fields().iter().map(|(k, v)| (k, v)))
+    {
+        buffer.push(SEPARATOR);
+        buffer.push_str(key);
+        buffer.push('=');
+        buffer.push_str(value);
+    }
+}
+
+struct TrapSender {
+    socket: Option<UdpSocket>,
+    community: String,
+    started: Instant,
+    request_id: AtomicI32,
+}
+
+impl TrapSender {
+    fn new<A>(addr: A, community: String) -> TrapSender
+    where
+        A: ToSocketAddrs,
+    {
+        let socket = addr
+            .to_socket_addrs()
+            .and_then(|mut addrs| {
+                addrs.next().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::NotFound, "no address found")
+                })
+            })
+            .and_then(|addr| {
+                let local = if addr.is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(addr)?;
+                Ok(socket)
+            });
+        let socket = match socket {
+            Ok(socket) => Some(socket),
+            Err(x) => {
+                report(format_args!("sending SNMP traps failed {x}"));
+                None
+            }
+        };
+        TrapSender {
+            socket,
+            community,
+            started: Instant::now(),
+            request_id: AtomicI32::new(1),
+        }
+    }
+
+    /// Encodes an SNMPv2-Trap-PDU in a v2c message
+    fn trap(&self, payload: &str) -> Vec<u8> {
+        let mut fields = payload.split(SEPARATOR);
+        let target = fields.next().unwrap_or_default();
+        let message = fields.next().unwrap_or_default();
+
+        let mut bindings = Vec::new();
+        // hundredths of a second, which wrap around as the counter is 32 bits
+        let up_time = (self.started.elapsed().as_millis() / 10) as u32;
+        var_bind(&mut bindings, &SYS_UP_TIME, 0x43, &unsigned(up_time));
+        var_bind(&mut bindings, &SNMP_TRAP_OID, 0x06, &oid(&[0, 1]));
+        var_bind(&mut bindings, &object(&[1, 1]), 0x04, message.as_bytes());
+        var_bind(&mut bindings, &object(&[1, 2]), 0x04, target.as_bytes());
+        for (index, field) in (1..).zip(fields) {
+            var_bind(&mut bindings, &object(&[2, index]), 0x04, field.as_bytes());
+        }
+
+        let mut pdu = Vec::new();
+        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        tlv(&mut pdu, 0x02, &integer(request_id));
+        tlv(&mut pdu, 0x02, &integer(0));
+        tlv(&mut pdu, 0x02, &integer(0));
+        tlv(&mut pdu, 0x30, &bindings);
+
+        let mut message = Vec::new();
+        // version 1 is SNMPv2c
+        tlv(&mut message, 0x02, &integer(1));
+        tlv(&mut message, 0x04, self.community.as_bytes());
+        tlv(&mut message, 0xA7, &pdu);
+        let mut packet = Vec::new();
+        tlv(&mut packet, 0x30, &message);
+        packet
+    }
+}
+
+impl CallTarget for TrapSender {
+    fn call(&self, payload: &str, _record: &Record) -> Result<(), BoxError> {
+        if let Some(socket) = &self.socket {
+            socket.send(&self.trap(payload))?;
+        }
+        Ok(())
+    }
+}
+
+/// An OID under [`BASE_OID`]
+fn object(arcs: &[u32]) -> Vec<u32> {
+    BASE_OID.iter().chain(arcs).copied().collect()
+}
+
+/// Appends a variable binding of an OID to a value with the tag `tag`
+fn var_bind(out: &mut Vec<u8>, name: &[u32], tag: u8, value: &[u8]) {
+    let mut binding = Vec::new();
+    tlv(&mut binding, 0x06, &encode_oid(name));
+    tlv(&mut binding, tag, value);
+    tlv(out, 0x30, &binding);
+}
+
+/// The encoded OID of an object under [`BASE_OID`]
+fn oid(arcs: &[u32]) -> Vec<u8> {
+    encode_oid(&object(arcs))
+}
+
+/// Appends a BER tag, length and value
+fn tlv(out: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    out.push(tag);
+    if value.len() < 0x80 {
+        out.push(value.len() as u8);
+    } else {
+        let length = value.len().to_be_bytes();
+        let skip = length.iter().take_while(|x| **x == 0).count();
+        out.push(0x80 | (length.len() - skip) as u8);
+        out.extend_from_slice(&length[skip..]);
+    }
+    out.extend_from_slice(value);
+}
+
+/// The shortest two's complement encoding of an integer
+fn integer(value: i32) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut skip = 0;
+    while skip < 3
+        && ((bytes[skip] == 0 && bytes[skip + 1] & 0x80 == 0)
+            || (bytes[skip] == 0xFF && bytes[skip + 1] & 0x80 != 0))
+    {
+        skip += 1;
+    }
+    bytes[skip..].to_vec()
+}
+
+/// The shortest encoding of an unsigned integer, e.g. `TimeTicks`
+fn unsigned(value: u32) -> Vec<u8> {
+    let mut bytes = vec![0];
+    bytes.extend_from_slice(&value.to_be_bytes());
+    let skip = bytes
+        .windows(2)
+        .take_while(|x| x[0] == 0 && x[1] & 0x80 == 0)
+        .count();
+    bytes[skip..].to_vec()
+}
+
+/// Encodes an OID, the first two arcs are combined and each arc is split into 7 bit groups
+fn encode_oid(arcs: &[u32]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let first = arcs.first().copied().unwrap_or(0) * 40 + arcs.get(1).copied().unwrap_or(0);
+    for arc in std::iter::once(first).chain(arcs.iter().skip(2).copied()) {
+        let mut groups = vec![(arc & 0x7F) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            groups.push((rest & 0x7F) as u8 | 0x80);
+            rest >>= 7;
+        }
+        out.extend(groups.iter().rev());
+    }
+    out
+}
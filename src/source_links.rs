@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+
+use crate::CallLoggerBuilder;
+
+impl CallLoggerBuilder {
+    /// Renders the source file and line of each log event as a link in the console and chat formatters, so that
+    /// clicking an alert opens the code that logged it.  The file of a log event is usually relative to the root of
+    /// the crate or workspace that was compiled, so relative files are joined to `root`, e.g.
+    /// `env!("CARGO_MANIFEST_DIR")`.  The link can also be written by other formatters with
+    /// [`FormatContext::source_location`](crate::FormatContext::source_location) and
+    /// [`FormatContext::source_url`](crate::FormatContext::source_url).
+    ///
+    /// Example - Open the code in VS Code from a terminal
+    /// ```rust
+    /// # use call_logger::{CallLogger, SourceLinks};
+    /// CallLogger::new()
+    ///     .colored_console_format()
+    ///     .with_source_links(SourceLinks::Vscode, env!("CARGO_MANIFEST_DIR"))
+    ///     .json_stdout()
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_source_links<P>(mut self, style: SourceLinks, root: P) -> CallLoggerBuilder
+    where
+        P: AsRef<Path>,
+    {
+        self.source_links = Some(SourceLinker {
+            style,
+            root: root.as_ref().to_path_buf(),
+        });
+        self
+    }
+}
+
+/// How the source file and line of a log event are rendered as a link, set with
+/// [`CallLoggerBuilder::with_source_links`]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceLinks {
+    /// A `vscode://file/` URL, which opens the file at the line in VS Code and the editors that share its URL
+    /// handler.  Chat formatters write it as a link on `file:line`, and the console formatter writes the URL itself,
+    /// which most terminals make clickable.
+    Vscode,
+    /// A `file://` URL, written in the console as an [OSC 8][osc8] terminal hyperlink on `file:line`, which terminals
+    /// that support it open with the application set for the file
+    ///
+    /// [osc8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+    Osc8,
+}
+
+/// The style of the source links, and the directory that relative source files are in
+pub(crate) struct SourceLinker {
+    style: SourceLinks,
+    root: PathBuf,
+}
+
+impl SourceLinker {
+    /// The URL of a line of a source file
+    pub(crate) fn url(&self, file: &str, line: u32, hostname: &str) -> String {
+        let path = encode_path(&self.root.join(file));
+        match self.style {
+            SourceLinks::Vscode => format!("vscode://file{path}:{line}"),
+            SourceLinks::Osc8 => format!("file://{hostname}{path}"),
+        }
+    }
+
+    /// The location of a line of a source file as it is written in the console
+    pub(crate) fn location(&self, file: &str, line: u32, hostname: &str) -> String {
+        let url = self.url(file, line, hostname);
+        match self.style {
+            SourceLinks::Vscode => url,
+            SourceLinks::Osc8 => format!("\x1b]8;;{url}\x1b\\{file}:{line}\x1b]8;;\x1b\\"),
+        }
+    }
+}
+
+/// Writes a path as the path of a URL, with forward slashes, a leading slash before a Windows drive, and the bytes
+/// that aren't allowed in a URL percent-encoded
+fn encode_path(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut encoded = String::with_capacity(path.len() + 1);
+    if !path.starts_with('/') {
+        encoded.push('/');
+    }
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~:".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
@@ -0,0 +1,210 @@
+use std::{
+    mem::take,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use log::{
+    kv::{Error, Key, Value, VisitSource},
+    Record,
+};
+use rusqlite::{params, Connection};
+
+use crate::{
+    dispatch::DispatcherSlot, escape_json, BoxError, CallError, CallErrorKind, CallLoggerBuilder,
+    CallTarget, Event,
+};
+
+impl CallLoggerBuilder {
+    /// Inserts log events into a table of a local SQLite database instead of calling the call target, so that an
+    /// application can keep queryable logs.  The database is opened in WAL mode and the table is created if needed
+    /// with the columns `ts` (milliseconds since the epoch), `level`, `module`, `msg` and `kv`, which holds the
+    /// key-value pairs as a JSON object.  Inserts are batched into a transaction of up to 100 rows which is
+    /// committed when it is full, a second after the first row was added, or when the logger is flushed or dropped.
+    /// The rows of a transaction that fails are reported and written to the dead-letter file as log events that
+    /// could not be delivered.  The formatter is not used by this target.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .sqlite("logs.db", "logs")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn sqlite<P, T>(mut self, path: P, table: T) -> CallLoggerBuilder
+    where
+        P: AsRef<Path>,
+        T: Into<String>,
+    {
+        self.dispatcher.target = Some(Box::new(SqliteSink {
+            path: path.as_ref().to_path_buf(),
+            table: quote_identifier(&table.into()),
+            connection: Mutex::new(None),
+            pending: Mutex::new(Pending::default()),
+            owner: self.dispatcher.owner.clone(),
+        }));
+        self
+    }
+}
+
+const BATCH_ROWS: usize = 100;
+const BATCH_DELAY: Duration = Duration::from_secs(1);
+
+struct SqliteSink {
+    path: PathBuf,
+    /// The name of the table, quoted so that it can be written in SQL
+    table: String,
+    connection: Mutex<Option<Connection>>,
+    pending: Mutex<Pending>,
+    owner: DispatcherSlot,
+}
+
+#[derive(Default)]
+struct Pending {
+    rows: Vec<Row>,
+    started: Option<Instant>,
+}
+
+struct Row {
+    ts: i64,
+    level: String,
+    module: Option<String>,
+    msg: String,
+    kv: String,
+    /// The formatted output and the details of the log event, which are reported if the row can't be inserted
+    payload: String,
+    event: Event,
+}
+
+impl SqliteSink {
+    /// Inserts the rows, or reports each of them as a log event that couldn't be delivered if they can't be.  The error
+    /// is returned for the last row instead when `last_is_current` is set, as the call for it hasn't returned.
+    fn insert_or_report(&self, mut rows: Vec<Row>, last_is_current: bool) -> Result<(), BoxError> {
+        let Err(x) = self.insert(&rows) else {
+            return Ok(());
+        };
+        let current = if last_is_current { rows.pop() } else { None };
+        let error = CallError::new(CallErrorKind::Target, x.to_string());
+        for row in &rows {
+            self.owner
+                .call_failed(&row.payload, &row.event, error.clone());
+        }
+        match current {
+            Some(_) => Err(x),
+            None => Ok(()),
+        }
+    }
+
+    fn insert(&self, rows: &[Row]) -> Result<(), BoxError> {
+        let mut connection = self.connection.lock().unwrap_or_else(|x| x.into_inner());
+        if connection.is_none() {
+            let opened = Connection::open(&self.path)?;
+            opened.pragma_update(None, "journal_mode", "WAL")?;
+            opened.execute_batch(&format!(
+                "CREATE TABLE IF NOT EXISTS {} (ts INTEGER NOT NULL, level TEXT NOT NULL, module TEXT, msg TEXT NOT NULL, kv TEXT NOT NULL)",
+                self.table
+            ))?;
+            *connection = Some(opened);
+        }
+        if let Some(connection) = connection.as_mut() {
+            let transaction = connection.transaction()?;
+            {
+                let mut statement = transaction.prepare_cached(&format!(
+                    "INSERT INTO {} (ts, level, module, msg, kv) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    self.table
+                ))?;
+                for row in rows {
+                    statement.execute(params![row.ts, row.level, row.module, row.msg, row.kv])?;
+                }
+            }
+            transaction.commit()?;
+        }
+        Ok(())
+    }
+}
+
+impl CallTarget for SqliteSink {
+    fn call(&self, payload: &str, record: &Record) -> Result<(), BoxError> {
+        let mut visitor = JsonVisitor {
+            json: String::new(),
+        };
+        let _ = record.key_values().visit(&mut visitor);
+        let row = Row {
+            ts: SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64,
+            level: record.level().to_string(),
+            module: record.module_path().map(|x| x.to_string()),
+            msg: record.args().to_string(),
+            kv: format!("{{{}}}", visitor.json),
+            payload: payload.to_string(),
+            event: Event::from(record),
+        };
+        let due = {
+            let mut pending = self.pending.lock().unwrap_or_else(|x| x.into_inner());
+            if pending.rows.is_empty() {
+                pending.started = Some(Instant::now());
+            }
+            pending.rows.push(row);
+            let late = pending
+                .started
+                .is_some_and(|started| started.elapsed() >= BATCH_DELAY);
+            if pending.rows.len() >= BATCH_ROWS || late {
+                take(&mut pending.rows)
+            } else {
+                Vec::new()
+            }
+        };
+        if !due.is_empty() {
+            self.insert_or_report(due, true)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&self) {
+        let due = take(&mut self.pending.lock().unwrap_or_else(|x| x.into_inner()).rows);
+        if !due.is_empty() {
+            let _ = self.insert_or_report(due, false);
+        }
+    }
+
+    fn flush_due(&self) -> Option<Duration> {
+        let due = {
+            let mut pending = self.pending.lock().unwrap_or_else(|x| x.into_inner());
+            let waited = pending.started.map(|started| started.elapsed());
+            match waited {
+                Some(waited) if waited < BATCH_DELAY => return Some(BATCH_DELAY - waited),
+                _ => take(&mut pending.rows),
+            }
+        };
+        if !due.is_empty() {
+            let _ = self.insert_or_report(due, false);
+        }
+        Some(BATCH_DELAY)
+    }
+}
+
+/// Quotes the name of a table so that it can be written in SQL, whatever characters it has
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// Visitor for writing the kv pairs in a log record as the members of a JSON object.
+struct JsonVisitor {
+    json: String,
+}
+
+impl<'kvs> VisitSource<'kvs> for JsonVisitor {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        if !self.json.is_empty() {
+            self.json.push(',');
+        }
+        self.json.push_str(&format!(
+            "\"{}\":\"{}\"",
+            escape_json(key.as_str()),
+            escape_json(&value.to_string())
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use log::Record;
+
+use crate::{BoxError, CallLoggerBuilder, CallTarget, ConfigError};
+
+/// How long a client has to send the headers of its request before it is disconnected
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl CallLoggerBuilder {
+    /// Serves the formatted output of log events as [Server-Sent Events][sse] on `addr` instead of calling the call
+    /// target, so that a browser or `curl -N` can tail the logs live.  A small HTTP server is started on a background
+    /// thread when the logger is built, which answers every request with an event stream, and each log event is sent
+    /// to all of the connected clients as a `data:` event.  The logger can't be built if the address can't be
+    /// listened on.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .serve_sse("127.0.0.1:9200")
+    ///     .init();
+    /// ```
+    ///
+    /// [sse]: https://html.spec.whatwg.org/multipage/server-sent-events.html
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn serve_sse<A>(mut self, addr: A) -> CallLoggerBuilder
+    where
+        A: ToSocketAddrs,
+    {
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        self.sse_server = Some(SseServer {
+            addrs: addr.to_socket_addrs().map(Iterator::collect),
+            clients: clients.clone(),
+        });
+        self.dispatcher.target = Some(Box::new(SseBroadcaster { clients }));
+        self
+    }
+}
+
+struct SseBroadcaster {
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+/// The address that the server-sent events are served on, which is listened on when the logger is built
+pub(crate) struct SseServer {
+    addrs: io::Result<Vec<SocketAddr>>,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl SseServer {
+    /// Listens on the address and accepts clients on a background thread
+    pub(crate) fn start(self) -> Result<(), ConfigError> {
+        let listener = self
+            .addrs
+            .and_then(|addrs| TcpListener::bind(&addrs[..]))
+            .map_err(|x| ConfigError::CannotListen(x.to_string()))?;
+        let clients = self.clients;
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // each client is answered on its own thread, so that a slow one doesn't hold up the others
+                let clients = clients.clone();
+                thread::spawn(move || accept_client(stream, &clients));
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Answers a connection with the headers of an event stream and adds it to the clients
+fn accept_client(mut stream: TcpStream, clients: &Mutex<Vec<TcpStream>>) {
+    // read and ignore the request headers, giving up on a client that doesn't send them
+    if stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).is_err() {
+        return;
+    }
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    loop {
+        match reader.read_line(&mut line) {
+            Ok(read) if read > 2 => line.clear(),
+            Ok(read) if read > 0 => break,
+            _ => return,
+        }
+    }
+    let headers = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\nAccess-Control-Allow-Origin: *\r\n\r\n";
+    // a client that stops reading is dropped rather than blocking the logger
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+    let mut clients = clients.lock().unwrap_or_else(|x| x.into_inner());
+    if stream.write_all(headers.as_bytes()).is_ok() {
+        clients.push(stream);
+    }
+}
+
+impl CallTarget for SseBroadcaster {
+    fn call(&self, payload: &str, _record: &Record) -> Result<(), BoxError> {
+        let mut event = String::new();
+        for line in payload.lines() {
+            event.push_str("data: ");
+            event.push_str(line);
+            event.push('\n');
+        }
+        event.push('\n');
+        let mut clients = self.clients.lock().unwrap_or_else(|x| x.into_inner());
+        clients.retain_mut(|client| client.write_all(event.as_bytes()).is_ok());
+        Ok(())
+    }
+}
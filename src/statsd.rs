@@ -0,0 +1,149 @@
+use std::{
+    fmt::Write,
+    net::{ToSocketAddrs, UdpSocket},
+};
+
+use log::{Level, Record};
+
+use crate::{internal::report, BoxError, CallLoggerBuilder, CallTarget, FormatContext};
+
+impl CallLoggerBuilder {
+    /// Counts log events per level as [StatsD][statsd] metrics sent to `addr` over UDP instead of calling the call
+    /// target, e.g. `log.records.error:1|c`, so that the rate of errors can be graphed and alerted on by the metrics
+    /// stack.  The formatter is not used.  If the address cannot be resolved, the error is printed and log events are
+    /// dropped.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .statsd("127.0.0.1:8125")
+    ///     .init();
+    /// ```
+    ///
+    /// [statsd]: https://github.com/statsd/statsd/blob/master/docs/metric_types.md
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn statsd<A>(mut self, addr: A) -> CallLoggerBuilder
+    where
+        A: ToSocketAddrs,
+    {
+        self.formatter = Box::new(statsd_formatter);
+        self.dispatcher.target = Some(Box::new(StatsdClient::new(addr)));
+        self
+    }
+
+    /// Counts log events per level as [DogStatsD][dogstatsd] metrics sent to `addr` over UDP instead of calling the
+    /// call target, and also sends warnings and errors as DogStatsD events, so that they appear in the event stream
+    /// when the Datadog agent is the only thing listening, e.g. on `127.0.0.1:8125`.  The count is
+    /// `log.records:1|c` tagged with the level and target of the log event, and the event has the level and target
+    /// as its title and the message as its text.  The formatter is not used.  If the address cannot be resolved, the
+    /// error is printed and log events are dropped.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .dogstatsd("127.0.0.1:8125")
+    ///     .init();
+    /// ```
+    ///
+    /// [dogstatsd]: https://docs.datadoghq.com/developers/dogstatsd/datagram_shell
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn dogstatsd<A>(mut self, addr: A) -> CallLoggerBuilder
+    where
+        A: ToSocketAddrs,
+    {
+        self.formatter = Box::new(dogstatsd_formatter);
+        self.dispatcher.target = Some(Box::new(StatsdClient::new(addr)));
+        self
+    }
+}
+
+fn statsd_formatter(buffer: &mut String, context: &FormatContext) {
+    buffer.push_str("log.records.");
+    buffer.push_str(&context.record().level().as_str().to_lowercase());
+    buffer.push_str(":1|c");
+}
+
+fn dogstatsd_formatter(buffer: &mut String, context: &FormatContext) {
+    let record = context.record();
+    let level = record.level().as_str().to_lowercase();
+    let tags = format!("#level:{level},target:{}", tag_value(record.target()));
+    // writing to a String cannot fail
+    let _ = write!(buffer, "log.records:1|c|{tags}");
+    if record.level() <= Level::Warn {
+        let title = format!("{} in {}", record.level(), record.target());
+        let message = context.message();
+        let text = match message.as_str() {
+            Some(message) => message.replace('\n', "\\n"),
+            None => message.to_string().replace('\n', "\\n"),
+        };
+        let alert_type = if record.level() == Level::Error {
+            "error"
+        } else {
+            "warning"
+        };
+        // metrics and events can share a datagram when they are on separate lines
+        let _ = write!(
+            buffer,
+            "\n_e{{{},{}}}:{title}|{text}|t:{alert_type}|{tags}",
+            title.len(),
+            text.len()
+        );
+    }
+}
+
+/// A tag value with the characters that separate the fields of a datagram replaced
+fn tag_value(value: &str) -> String {
+    value.replace([',', '|', '#', '\n'], "_")
+}
+
+/// Sends each payload as a datagram to the StatsD server
+struct StatsdClient {
+    socket: Option<UdpSocket>,
+}
+
+impl StatsdClient {
+    fn new<A>(addr: A) -> StatsdClient
+    where
+        A: ToSocketAddrs,
+    {
+        let socket = addr
+            .to_socket_addrs()
+            .and_then(|mut addrs| {
+                addrs.next().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::NotFound, "no address found")
+                })
+            })
+            .and_then(|addr| {
+                let local = if addr.is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(addr)?;
+                Ok(socket)
+            });
+        match socket {
+            Ok(socket) => StatsdClient {
+                socket: Some(socket),
+            },
+            Err(x) => {
+                report(format_args!("sending metrics to statsd failed {x}"));
+                StatsdClient { socket: None }
+            }
+        }
+    }
+}
+
+impl CallTarget for StatsdClient {
+    fn call(&self, payload: &str, _record: &Record) -> Result<(), BoxError> {
+        if let Some(socket) = &self.socket {
+            socket.send(payload.as_bytes())?;
+        }
+        Ok(())
+    }
+}
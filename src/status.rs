@@ -0,0 +1,136 @@
+use std::{ops::RangeInclusive, thread::sleep, time::Duration};
+
+use log::Level;
+
+use crate::{dispatch::Dispatcher, event_ttl, CallError, CallErrorKind, CallLoggerBuilder};
+
+/// The delay before the first retry, which grows with each attempt
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// What is done when a URL call target responds with a status, set with [`CallLoggerBuilder::on_status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StatusPolicy {
+    /// The log event has been delivered
+    Success,
+    /// Send the request again, up to this many more times, waiting a little longer before each attempt
+    Retry(u32),
+    /// The log event is dropped without being reported as a failure
+    Drop,
+    /// Send the request to this URL instead, e.g. for a payload that is too large for the call target
+    Fallback(String),
+    /// The log event could not be delivered, this is the default for any status that isn't `2xx`
+    Fail,
+}
+
+impl CallLoggerBuilder {
+    /// Treats responses from a URL call target with a status in `statuses` as successful, e.g. `409` from an API that
+    /// rejects duplicates.  Only `2xx` statuses are successful by default.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .treat_as_success(409..=409)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn treat_as_success(self, statuses: RangeInclusive<u16>) -> CallLoggerBuilder {
+        self.on_status(statuses, StatusPolicy::Success)
+    }
+
+    /// Sets what is done when a URL call target responds with a status in `statuses`.  Policies are checked in the
+    /// order they are set and the first one that matches the status is used.  Retries are made on the thread that
+    /// delivers the log event, so [`CallLoggerBuilder::with_workers`] should be used with [`StatusPolicy::Retry`] to
+    /// keep the application from waiting.  The policies are not used when a closure is set with
+    /// [`CallLoggerBuilder::with_http_transport`].
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::{CallLogger, StatusPolicy};
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .on_status(429..=429, StatusPolicy::Retry(3))
+    ///     .on_status(413..=413, StatusPolicy::Fallback("https://postman-echo.com/put".into()))
+    ///     .on_status(500..=599, StatusPolicy::Retry(2))
+    ///     .on_status(400..=499, StatusPolicy::Drop)
+    ///     .with_workers(2)
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn on_status(
+        mut self,
+        statuses: RangeInclusive<u16>,
+        policy: StatusPolicy,
+    ) -> CallLoggerBuilder {
+        self.dispatcher.status_policies.push((statuses, policy));
+        self
+    }
+}
+
+impl Dispatcher {
+    /// Makes the HTTP request with the built in HTTP client, following the policy for the status of the response
+    pub(crate) fn call_ureq(
+        &self,
+        url: &str,
+        params: &str,
+        level: Level,
+        content_type: &str,
+    ) -> Result<Option<u16>, CallError> {
+        let logged = event_ttl::logged_at();
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let status = self
+                .send_ureq(url, params, level, content_type)
+                .map_err(|x| CallError { attempts, ..x })?;
+            match self.status_policy(status) {
+                StatusPolicy::Success | StatusPolicy::Drop => return Ok(Some(status)),
+                StatusPolicy::Retry(retries) if attempts <= *retries => {
+                    // the log event is dropped rather than retried once it would be too old
+                    if self.expires(logged, RETRY_DELAY * attempts) {
+                        return Ok(Some(status));
+                    }
+                    sleep(RETRY_DELAY * attempts)
+                }
+                StatusPolicy::Fallback(fallback) => {
+                    let status = self.send_ureq(fallback, params, level, content_type)?;
+                    return match is_success(status) {
+                        true => Ok(Some(status)),
+                        false => Err(status_error(fallback, status, 1)),
+                    };
+                }
+                _ => return Err(status_error(url, status, attempts)),
+            }
+        }
+    }
+
+    /// The policy for a status, from the first policy that has been set for it or the default
+    fn status_policy(&self, status: u16) -> &StatusPolicy {
+        self.status_policies
+            .iter()
+            .find(|(statuses, _)| statuses.contains(&status))
+            .map(|(_, policy)| policy)
+            .unwrap_or(match is_success(status) {
+                true => &StatusPolicy::Success,
+                false => &StatusPolicy::Fail,
+            })
+    }
+}
+
+fn is_success(status: u16) -> bool {
+    (200..300).contains(&status)
+}
+
+fn status_error(url: &str, status: u16, attempts: u32) -> CallError {
+    CallError {
+        attempts,
+        ..CallError::new(
+            CallErrorKind::Http(status),
+            format!("{url}: status code {status}"),
+        )
+    }
+}
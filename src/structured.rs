@@ -0,0 +1,145 @@
+use std::cell::Cell;
+
+use log::{
+    kv::{ToValue, Value},
+    Level, Record,
+};
+
+use crate::{with_buffer, CallLogger};
+
+thread_local! {
+    /// Whether the record being logged on this thread is a structured event
+    static SENDING_EVENT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Starts a structured event called `name`, e.g. for a business event such as a finished deployment or a new
+/// customer, which is sent to the logger without the `log` macros.  The event goes through the same formatters,
+/// middleware and targets as log events, with the name as the message, `event` as the target and the fields as the
+/// key-value pairs, but it is always delivered whatever the level filters are.  It is still held while the logger is
+/// paused or silenced.
+///
+/// Example
+/// ```rust
+/// # use call_logger::CallLogger;
+/// CallLogger::new().init();
+/// call_logger::event("deployment_finished")
+///     .field("version", "1.4.2")
+///     .field("duration_secs", 73)
+///     .send();
+/// ```
+pub fn event<T>(name: T) -> StructuredEvent
+where
+    T: Into<String>,
+{
+    StructuredEvent {
+        name: name.into(),
+        level: Level::Info,
+        fields: Vec::new(),
+    }
+}
+
+/// A structured event started with [`event`], which is delivered with [`StructuredEvent::send`]
+#[derive(Clone, Debug)]
+pub struct StructuredEvent {
+    name: String,
+    level: Level,
+    fields: Vec<(String, FieldValue)>,
+}
+
+/// The value of a field of a structured event, which keeps numbers and booleans as they are
+#[derive(Clone, Debug)]
+enum FieldValue {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+}
+
+impl StructuredEvent {
+    /// Adds a field to the event.  Numbers and booleans are kept as they are, so that they are written as JSON values
+    /// with the `kv_serde` feature, and anything else is written as a string.
+    #[inline]
+    #[must_use = "You must call send() to deliver the event"]
+    pub fn field<K, V>(mut self, key: K, value: V) -> StructuredEvent
+    where
+        K: Into<String>,
+        V: ToValue,
+    {
+        let value = value.to_value();
+        let value = if let Some(value) = value.to_bool() {
+            FieldValue::Bool(value)
+        } else if let Some(value) = value.to_i64() {
+            FieldValue::I64(value)
+        } else if let Some(value) = value.to_u64() {
+            FieldValue::U64(value)
+        } else if let Some(value) = value.to_f64() {
+            FieldValue::F64(value)
+        } else {
+            FieldValue::Str(value.to_string())
+        };
+        self.fields.push((key.into(), value));
+        self
+    }
+
+    /// Sets the level of the event, which is `Info` unless this is called.  The level is only used by the formatters
+    /// and the targets that filter on it, as the event is delivered whatever the level filters are.
+    #[inline]
+    #[must_use = "You must call send() to deliver the event"]
+    pub fn with_level(mut self, level: Level) -> StructuredEvent {
+        self.level = level;
+        self
+    }
+
+    /// Delivers the event to the logger that was set up with [`CallLogger::init`].  It is passed to the logger with
+    /// [`log::logger`], so this also works when the logger is wrapped by another logger that passes it log events.
+    pub fn send(self) {
+        self.with_record(|record| {
+            SENDING_EVENT.with(|sending| sending.set(true));
+            log::logger().log(record);
+            SENDING_EVENT.with(|sending| sending.set(false));
+        });
+    }
+
+    /// Delivers the event to `logger`, e.g. one that has been built but isn't the logger of the `log` crate
+    pub fn send_to(self, logger: &CallLogger) {
+        self.with_record(|record| logger.deliver_event(record));
+    }
+
+    /// Calls the closure with the record of the event
+    fn with_record(&self, f: impl FnOnce(&Record)) {
+        let kvs = self
+            .fields
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    FieldValue::Bool(value) => Value::from(*value),
+                    FieldValue::I64(value) => Value::from(*value),
+                    FieldValue::U64(value) => Value::from(*value),
+                    FieldValue::F64(value) => Value::from(*value),
+                    FieldValue::Str(value) => Value::from(value.as_str()),
+                };
+                (key.as_str(), value)
+            })
+            .collect::<Vec<_>>();
+        f(&Record::builder()
+            .args(format_args!("{}", self.name))
+            .level(self.level)
+            .target("event")
+            .key_values(&kvs)
+            .build());
+    }
+}
+
+impl CallLogger {
+    /// Formats and delivers a structured event, without the level filters
+    pub(crate) fn deliver_event(&self, record: &Record) {
+        with_buffer(|buffer| self.format_and_deliver(buffer, record));
+    }
+}
+
+/// Whether the record being logged on this thread is a structured event, which is only true once, so that the log
+/// events logged while it is delivered are filtered as usual
+pub(crate) fn take_sending_event() -> bool {
+    SENDING_EVENT.with(|sending| sending.replace(false))
+}
@@ -0,0 +1,167 @@
+use std::{fmt::Arguments, sync::Arc};
+
+use crate::{dispatch::Dispatcher, CallLoggerBuilder, CallTarget, ContextFormatter, FormatContext};
+
+impl CallLoggerBuilder {
+    /// Adds a target that each log event is delivered to as well as the call target, e.g. to send errors to a chat
+    /// service while also writing them to a file.  The target uses the formatter of the logger unless it has one of its
+    /// own, so that each target can be sent the format it expects.  The level filters and middleware of the logger
+    /// apply to every target.
+    ///
+    /// Example - Send plain JSON to stdout and a message to Discord
+    /// ```rust
+    /// # use call_logger::{CallLogger, Target};
+    /// CallLogger::new()
+    ///     .json_stdout()
+    ///     .with_target(Target::http("https://postman-echo.com/post").format(|_, message, record| {
+    ///         format!("{{\"content\":\"[{}] {}\"}}", record.level(), message)
+    ///     }))
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn with_target(mut self, target: Target) -> CallLoggerBuilder {
+        self.targets.push(target);
+        self
+    }
+}
+
+/// A target that log events are delivered to as well as the call target of the logger, added with
+/// [`CallLoggerBuilder::with_target`].  A target can have its own formatter, otherwise the formatter of the logger is
+/// used.
+pub struct Target {
+    /// Where and how the formatted output is delivered
+    pub(crate) dispatcher: Dispatcher,
+
+    /// The formatter for this target, instead of the formatter of the logger
+    pub(crate) formatter: Option<Box<ContextFormatter>>,
+}
+
+impl Target {
+    /// Creates a target that calls an application, script or URL, the same as
+    /// [`CallLoggerBuilder::with_call_target`].
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::Target;
+    /// let target = Target::new("echo");
+    /// ```
+    pub fn new<T>(call_target: T) -> Target
+    where
+        T: Into<String>,
+    {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.call_target = call_target.into();
+        Target {
+            dispatcher,
+            formatter: None,
+        }
+    }
+
+    /// Creates a target that posts the formatted output of each log event to a URL.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::Target;
+    /// let target = Target::http("https://postman-echo.com/post");
+    /// ```
+    pub fn http<T>(url: T) -> Target
+    where
+        T: Into<String>,
+    {
+        Target::new(url)
+    }
+
+    /// Creates a target that delivers the formatted output of each log event to a [`CallTarget`], the same as
+    /// [`CallLoggerBuilder::with_custom_target`].  Closures that accept the payload and the record can be used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::Target;
+    /// let target = Target::custom(|payload: &str, _: &log::Record| {
+    ///     eprintln!("{payload}");
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn custom<T>(target: T) -> Target
+    where
+        T: CallTarget + 'static,
+    {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.target = Some(Box::new(target));
+        Target {
+            dispatcher,
+            formatter: None,
+        }
+    }
+
+    /// Sets the formatter of this target, in the same way as [`CallLoggerBuilder::format`].
+    #[inline]
+    #[must_use]
+    pub fn format<F>(mut self, formatter: F) -> Target
+    where
+        F: Fn(Option<String>, &Arguments, &log::Record) -> String + Sync + Send + 'static,
+    {
+        self.formatter = Some(Box::new(move |buffer, context| {
+            let timestamp = context.timestamp().map(str::to_string);
+            buffer.push_str(&formatter(timestamp, context.message(), context.record()))
+        }));
+        self
+    }
+
+    /// Sets a formatter of this target that writes into a buffer, in the same way as
+    /// [`CallLoggerBuilder::format_into`].
+    #[inline]
+    #[must_use]
+    pub fn format_into<F>(mut self, formatter: F) -> Target
+    where
+        F: Fn(&mut String, Option<String>, &Arguments, &log::Record) + Sync + Send + 'static,
+    {
+        self.formatter = Some(Box::new(move |buffer, context| {
+            let timestamp = context.timestamp().map(str::to_string);
+            formatter(buffer, timestamp, context.message(), context.record())
+        }));
+        self
+    }
+
+    /// Sets a formatter of this target that is passed a [`FormatContext`], in the same way as
+    /// [`CallLoggerBuilder::format_with`].
+    #[inline]
+    #[must_use]
+    pub fn format_with<F>(mut self, formatter: F) -> Target
+    where
+        F: Fn(&FormatContext) -> String + Sync + Send + 'static,
+    {
+        self.formatter = Some(Box::new(move |buffer, context| {
+            buffer.push_str(&formatter(context))
+        }));
+        self
+    }
+
+    /// Names this target for formatters, in the same way as [`CallLoggerBuilder::with_channel_name`].
+    #[inline]
+    #[must_use]
+    pub fn with_channel_name<T>(mut self, name: T) -> Target
+    where
+        T: Into<String>,
+    {
+        self.dispatcher.channel_name = Some(name.into());
+        self
+    }
+}
+
+/// A target added with [`CallLoggerBuilder::with_target`] once the logger is built, with the dispatcher shared with
+/// the threads that deliver in the background
+pub(crate) struct BuiltTarget {
+    pub(crate) dispatcher: Arc<Dispatcher>,
+    pub(crate) formatter: Option<Box<ContextFormatter>>,
+}
+
+impl From<Target> for BuiltTarget {
+    fn from(target: Target) -> Self {
+        BuiltTarget {
+            dispatcher: Arc::new(target.dispatcher),
+            formatter: target.formatter,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::{Level, Record};
+
+use crate::{dispatch::Dispatcher, fingerprint::FingerprintSlot, CallLoggerBuilder, Target};
+
+impl CallLoggerBuilder {
+    /// Also delivers a warning to `target` when it is the `count`th with the same fingerprint within `window`, e.g. to
+    /// page someone when a warning that can be ignored once keeps happening, as simple threshold alerting without an
+    /// alert manager.  The fingerprint of a log event is made from its module and message, unless it is set with
+    /// [`CallLoggerBuilder::fingerprint_with`].  The count starts again
+    /// once the target has been called, so it is called again on every `count`th warning while they keep coming.  The
+    /// warnings are still delivered to the call target and the other targets as usual, and the target is sent
+    /// nothing else.
+    ///
+    /// Example - Page on the fifth failed retry within ten minutes
+    /// ```rust
+    /// # use call_logger::{CallLogger, Target};
+    /// # use std::time::Duration;
+    /// CallLogger::new()
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .escalate_after(
+    ///         5,
+    ///         Duration::from_secs(10 * 60),
+    ///         Target::http("https://events.pagerduty.com/v2/enqueue"),
+    ///     )
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn escalate_after(
+        mut self,
+        count: u32,
+        window: Duration,
+        mut target: Target,
+    ) -> CallLoggerBuilder {
+        target.dispatcher.threshold = Some(Threshold {
+            count: count.max(1) as usize,
+            window,
+            seen: Mutex::new(HashMap::new()),
+        });
+        self.targets.push(target);
+        self
+    }
+}
+
+/// The number of warnings with the same fingerprint within a window that a target is called for
+pub(crate) struct Threshold {
+    count: usize,
+    window: Duration,
+    /// When each fingerprint was seen within the window, oldest first
+    seen: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl Threshold {
+    /// Counts the log event if it is a warning, and returns whether it is the one that reaches the threshold
+    fn reached(&self, record: &Record, fingerprint: &FingerprintSlot) -> bool {
+        if record.level() != Level::Warn {
+            return false;
+        }
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(|x| x.into_inner());
+        // the fingerprints that haven't been seen within the window are forgotten
+        seen.retain(|_, times| {
+            while times
+                .front()
+                .is_some_and(|time| now.duration_since(*time) >= self.window)
+            {
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+        let times = seen.entry(fingerprint.of(record)).or_default();
+        times.push_back(now);
+        if times.len() >= self.count {
+            times.clear();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Dispatcher {
+    /// Whether the log event is held back because the threshold of this target hasn't been reached
+    pub(crate) fn below_threshold(&self, record: &Record) -> bool {
+        self.threshold
+            .as_ref()
+            .is_some_and(|threshold| !threshold.reached(record, &self.fingerprint))
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::Level;
+
+use crate::{base64, encode_query, CallLoggerBuilder, FormatContext};
+
+/// The most messages that are sent in each period
+const MAX_MESSAGES: u32 = 5;
+
+/// The period that the number of messages is limited over
+const PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// The longest body of a message that Twilio accepts
+const MAX_BODY: usize = 1600;
+
+impl CallLoggerBuilder {
+    /// Sends error log events as SMS messages with [Twilio][twilio], so that critical events reach a phone when email
+    /// and chat aren't being watched.  At most five messages are sent an hour and the other log events are dropped, so
+    /// that a burst of errors doesn't become a burst of messages.  The message is the level, target and message of the
+    /// log event and the formatter is not used.  `from` is a Twilio phone number and `to` is the number to send to,
+    /// and WhatsApp messages can be sent by prefixing both with `whatsapp:`, e.g. `whatsapp:+15005550006`.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .twilio_sms("ACXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX", "my-auth-token", "+15005550006", "+15558675310")
+    ///     .init();
+    /// ```
+    ///
+    /// [twilio]: https://www.twilio.com/docs/messaging/api/message-resource#create-a-message-resource
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn twilio_sms<S, T, F, R>(
+        mut self,
+        account_sid: S,
+        auth_token: T,
+        from: F,
+        to: R,
+    ) -> CallLoggerBuilder
+    where
+        S: AsRef<str>,
+        T: AsRef<str>,
+        F: AsRef<str>,
+        R: AsRef<str>,
+    {
+        let credentials = format!("{}:{}", account_sid.as_ref(), auth_token.as_ref());
+        self.dispatcher.http_headers.push((
+            "Authorization".to_string(),
+            format!("Basic {}", base64(credentials.as_bytes())),
+        ));
+        self.dispatcher.http_headers.push((
+            "Content-Type".to_string(),
+            "application/x-www-form-urlencoded".to_string(),
+        ));
+        let sent = Mutex::new((Instant::now(), 0));
+        self.dispatcher.accepts = Some(Box::new(move |_, record| {
+            if record.level() != Level::Error {
+                return false;
+            }
+            let mut sent = sent.lock().unwrap_or_else(|x| x.into_inner());
+            let (period_start, count) = &mut *sent;
+            if period_start.elapsed() >= PERIOD {
+                *period_start = Instant::now();
+                *count = 0;
+            }
+            *count += 1;
+            *count <= MAX_MESSAGES
+        }));
+        let to_from = format!(
+            "To={}&From={}&Body=",
+            encode_query(to.as_ref()),
+            encode_query(from.as_ref())
+        );
+        self.formatter =
+            Box::new(move |buffer, context| twilio_formatter(buffer, &to_from, context));
+        self.with_call_target(format!(
+            "https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
+            account_sid.as_ref()
+        ))
+    }
+}
+
+fn twilio_formatter(buffer: &mut String, to_from: &str, context: &FormatContext) {
+    let body = format!(
+        "[{}] {}: {}",
+        context.level(),
+        context.record().target(),
+        context.message()
+    );
+    buffer.push_str(to_from);
+    buffer.push_str(&encode_query(
+        &body.chars().take(MAX_BODY).collect::<String>(),
+    ));
+}
@@ -0,0 +1,143 @@
+use log::{kv::Key, Record};
+
+use crate::{dispatch::Dispatcher, filter::glob_match, CallLoggerBuilder, Target};
+
+impl CallLoggerBuilder {
+    /// Only allows log events to be sent to URLs with one of the `schemes`, e.g. `["https"]` so that they are never
+    /// sent over plaintext HTTP, even when the call target comes from an environment variable or a configuration
+    /// file.  The URLs are checked when the logger is built, and the URLs picked for each log event are checked
+    /// before it is sent.  This applies to the targets added with [`CallLoggerBuilder::with_target`] as well.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// let result = CallLogger::new()
+    ///     .with_call_target("http://example.com/logs")
+    ///     .allow_schemes(&["https"])
+    ///     .build();
+    /// assert!(result.is_err());
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn allow_schemes<T>(mut self, schemes: &[T]) -> CallLoggerBuilder
+    where
+        T: AsRef<str>,
+    {
+        self.dispatcher.allowed_schemes = Some(
+            schemes
+                .iter()
+                .map(|scheme| scheme.as_ref().to_ascii_lowercase())
+                .collect(),
+        );
+        self
+    }
+
+    /// Only allows log events to be sent to URLs whose host matches one of the glob `hosts`, where `*` matches any
+    /// number of characters and `?` matches a single character, e.g. `*.example.com`.  Hosts are compared without
+    /// regard to case, and IPv6 addresses are in square brackets.  The URLs are checked in the same way as
+    /// [`CallLoggerBuilder::allow_schemes`].
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://logs.example.com/ingest")
+    ///     .allow_schemes(&["https"])
+    ///     .allow_hosts(&["logs.example.com", "*.internal.example.com"])
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn allow_hosts<T>(mut self, hosts: &[T]) -> CallLoggerBuilder
+    where
+        T: AsRef<str>,
+    {
+        self.dispatcher.allowed_hosts = Some(
+            hosts
+                .iter()
+                .map(|host| host.as_ref().to_ascii_lowercase())
+                .collect(),
+        );
+        self
+    }
+}
+
+impl CallLoggerBuilder {
+    /// Sends each log event that has the key-value pair `key` to the URL in its value instead of the call target, e.g.
+    /// to notify the webhook of a customer, so that the recipient can be picked where the event is logged.  The URL
+    /// must be allowed by [`CallLoggerBuilder::allow_hosts`], and by [`CallLoggerBuilder::allow_schemes`] if it is set,
+    /// otherwise the log event fails with [`CallErrorKind::Unsupported`](crate::CallErrorKind::Unsupported).  The
+    /// logger can't be built without the allowed hosts, as anything that is logged could pick where log events are
+    /// sent, or without a URL call target, which is used for the log events without the key.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .with_call_target("https://hooks.example.com/ops")
+    ///     .target_from_kv("notify_url")
+    ///     .allow_schemes(&["https"])
+    ///     .allow_hosts(&["hooks.example.com", "*.customers.example.com"])
+    ///     .init();
+    /// log::error!(notify_url = "https://acme.customers.example.com/hook"; "export failed");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn target_from_kv<T>(mut self, key: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        self.dispatcher.url_key = Some(key.into());
+        self
+    }
+}
+
+impl Target {
+    /// Sends each log event that has the key-value pair `key` to the URL in its value instead of the URL of this
+    /// target, in the same way as [`CallLoggerBuilder::target_from_kv`].
+    #[inline]
+    #[must_use]
+    pub fn target_from_kv<T>(mut self, key: T) -> Target
+    where
+        T: Into<String>,
+    {
+        self.dispatcher.url_key = Some(key.into());
+        self
+    }
+}
+
+impl Dispatcher {
+    /// The URL in the key-value pair of the log event that picks where it is sent, if there is one
+    pub(crate) fn kv_url(&self, record: &Record) -> Option<String> {
+        let key = self.url_key.as_deref()?;
+        let url = record.key_values().get(Key::from_str(key))?.to_string();
+        Some(url.trim().to_string())
+    }
+
+    /// Checks that a URL has one of the allowed schemes and hosts, or the reason that it doesn't
+    pub(crate) fn check_url_policy(&self, url: &str) -> Result<(), &'static str> {
+        let (scheme, rest) = url.split_once("://").unwrap_or_default();
+        if let Some(schemes) = &self.allowed_schemes {
+            if !schemes.iter().any(|x| x.eq_ignore_ascii_case(scheme)) {
+                return Err("its scheme is not one of those allowed");
+            }
+        }
+        if let Some(hosts) = &self.allowed_hosts {
+            let host = url_host(rest).to_ascii_lowercase();
+            if !hosts.iter().any(|pattern| glob_match(pattern, &host)) {
+                return Err("its host is not one of those allowed");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The host of the part of a URL after the scheme, with an IPv6 address in square brackets
+fn url_host(rest: &str) -> &str {
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit('@').next().unwrap_or_default();
+    match host_port.find(']') {
+        Some(end) if host_port.starts_with('[') => &host_port[..=end],
+        _ => host_port.split(':').next().unwrap_or_default(),
+    }
+}
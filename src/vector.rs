@@ -0,0 +1,114 @@
+use std::{io::Write, net::TcpStream, sync::Mutex, time::Duration};
+
+use log::Record;
+
+use crate::{push_json_escaped, BoxError, CallLoggerBuilder, CallTarget, FormatContext};
+
+/// How long a write to the socket source can block before the connection is treated as lost
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl CallLoggerBuilder {
+    /// Sends each log event to [Vector][vector] as a line of JSON with the `timestamp`, `message`, `level` and `host`
+    /// that Vector's log schema expects, followed by the target and key-value pairs of the log event, so that Vector
+    /// can parse it with `decoding.codec = "json"` and no remapping.  The formatter is not used.
+    ///
+    /// `addr` is either the URL of an [`http_server`][http] source, e.g. `http://localhost:8080`, which the JSON is
+    /// posted to as `application/x-ndjson`, or the `host:port` of a TCP [`socket`][socket] source, e.g.
+    /// `localhost:9000`, which the JSON is written to as a newline delimited line.  The connection to a socket source
+    /// is made when the first log event is sent and made again after it is lost.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .vector("http://localhost:8080")
+    ///     .init();
+    /// ```
+    ///
+    /// [vector]: https://vector.dev/docs/about/under-the-hood/architecture/data-model/log/
+    /// [http]: https://vector.dev/docs/reference/configuration/sources/http_server/
+    /// [socket]: https://vector.dev/docs/reference/configuration/sources/socket/
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn vector<A>(mut self, addr: A) -> CallLoggerBuilder
+    where
+        A: Into<String>,
+    {
+        let addr = addr.into();
+        self.formatter = Box::new(vector_formatter);
+        if addr.starts_with("http://") || addr.starts_with("https://") {
+            self.dispatcher.http_headers.push((
+                "Content-Type".to_string(),
+                "application/x-ndjson".to_string(),
+            ));
+            self.with_call_target(addr)
+        } else {
+            self.dispatcher.target = Some(Box::new(SocketSource {
+                addr,
+                stream: Mutex::new(None),
+            }));
+            self
+        }
+    }
+}
+
+/// Writes a log event as JSON with the fields of the log schema of Vector, which other agents also understand
+pub(crate) fn vector_formatter(buffer: &mut String, context: &FormatContext) {
+    buffer.push('{');
+    if let Some(timestamp) = context.timestamp() {
+        buffer.push_str("\"timestamp\":\"");
+        push_json_escaped(buffer, timestamp);
+        buffer.push_str("\",");
+    }
+    buffer.push_str("\"message\":\"");
+    let message = context.message();
+    match message.as_str() {
+        Some(message) => push_json_escaped(buffer, message),
+        None => push_json_escaped(buffer, &message.to_string()),
+    }
+    buffer.push_str("\",\"level\":\"");
+    push_json_escaped(buffer, &context.level().to_lowercase());
+    buffer.push_str("\",\"host\":\"");
+    push_json_escaped(buffer, context.hostname());
+    buffer.push_str("\",\"target\":\"");
+    push_json_escaped(buffer, context.record().target());
+    buffer.push('"');
+    for (key, value) in context
+        .kv()
+        .iter()
+        .chain(context.scoped_fields().iter().map(|(k, v)| (k, v)))
+        .chain(context.static_fields().iter().map(|(k, v)| (k, v)))
+    {
+        buffer.push_str(",\"");
+        push_json_escaped(buffer, key);
+        buffer.push_str("\":\"");
+        push_json_escaped(buffer, value);
+        buffer.push('"');
+    }
+    buffer.push('}');
+}
+
+/// Writes each log event as a line to a TCP socket source of Vector
+struct SocketSource {
+    addr: String,
+    stream: Mutex<Option<TcpStream>>,
+}
+
+impl CallTarget for SocketSource {
+    fn call(&self, payload: &str, _record: &Record) -> Result<(), BoxError> {
+        let mut stream = self.stream.lock().unwrap_or_else(|x| x.into_inner());
+        if stream.is_none() {
+            let connected = TcpStream::connect(self.addr.as_str())?;
+            connected.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            *stream = Some(connected);
+        }
+        if let Some(connected) = stream.as_mut() {
+            if let Err(x) = connected.write_all(format!("{payload}\n").as_bytes()) {
+                // connect again for the next log event
+                *stream = None;
+                return Err(x.into());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use log::Level;
+
+use crate::{opsgenie::has_resolved, push_json_escaped, CallLoggerBuilder, FormatContext};
+
+/// The REST endpoint of the generic integration of VictorOps
+const REST_URL: &str = "https://alert.victorops.com/integrations/generic/20131114/alert";
+
+impl CallLoggerBuilder {
+    /// Sends log events to the [REST endpoint][rest] of VictorOps, now Splunk On-Call, so that on-call rotations can
+    /// be driven by the log events of an application.  The `integration_key` is the key in the URL of the REST
+    /// endpoint shown in the integration settings, and the `routing_key` picks the team that is paged.  The
+    /// `message_type` is `CRITICAL` for errors, `WARNING` for warnings and `INFO` for info log events, or `RECOVERY`
+    /// for a log event with the key-value pair `resolved = true`, and debug and trace log events are not sent.  The
+    /// `entity_id` is the target of the log event, so that a recovery resolves the incident of the same target.  The
+    /// formatter is not used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .victorops("my-integration-key", "my-team")
+    ///     .init();
+    /// ```
+    ///
+    /// [rest]: https://help.victorops.com/knowledge-base/rest-endpoint-integration-guide/
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn victorops<K, R>(mut self, integration_key: K, routing_key: R) -> CallLoggerBuilder
+    where
+        K: AsRef<str>,
+        R: AsRef<str>,
+    {
+        self.dispatcher.accepts = Some(Box::new(|payload, _| !payload.is_empty()));
+        self.formatter = Box::new(victorops_formatter);
+        self.with_call_target(format!(
+            "{REST_URL}/{}/{}",
+            integration_key.as_ref(),
+            routing_key.as_ref()
+        ))
+    }
+}
+
+fn victorops_formatter(buffer: &mut String, context: &FormatContext) {
+    let record = context.record();
+    let message_type = match record.level() {
+        _ if has_resolved(record) => "RECOVERY",
+        Level::Error => "CRITICAL",
+        Level::Warn => "WARNING",
+        Level::Info => "INFO",
+        _ => return,
+    };
+    let message = context.message().to_string();
+    buffer.push_str("{\"message_type\":\"");
+    buffer.push_str(message_type);
+    buffer.push_str("\",\"entity_id\":\"");
+    push_json_escaped(buffer, record.target());
+    buffer.push_str("\",\"entity_display_name\":\"");
+    push_json_escaped(buffer, message.lines().next().unwrap_or_default());
+    buffer.push_str("\",\"state_message\":\"");
+    push_json_escaped(buffer, &message);
+    buffer.push_str("\",\"host_name\":\"");
+    push_json_escaped(buffer, context.hostname());
+    buffer.push_str("\",\"monitoring_tool\":\"call_logger\"}");
+}
@@ -0,0 +1,173 @@
+use log::Level;
+
+use crate::{encode_query, push_json_escaped, CallLoggerBuilder, FormatContext};
+
+impl CallLoggerBuilder {
+    /// Posts each log event to a [Mattermost incoming webhook][webhook] as a message attachment with the level and
+    /// target as the title, the message as the text and a color for the level.  The formatter is not used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .mattermost_webhook("https://mattermost.example.com/hooks/xxx-generatedkey-xxx")
+    ///     .init();
+    /// ```
+    ///
+    /// [webhook]: https://developers.mattermost.com/integrate/webhooks/incoming/
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn mattermost_webhook<U>(mut self, url: U) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+    {
+        self.formatter = Box::new(mattermost_formatter);
+        self.with_call_target(url)
+    }
+
+    /// Posts each log event to a [Rocket.Chat incoming webhook][webhook], with the level and target as the text of
+    /// the message and the message of the log event in an attachment with a color for the level.  The formatter is
+    /// not used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .rocketchat_webhook("https://rocketchat.example.com/hooks/xxx/yyy")
+    ///     .init();
+    /// ```
+    ///
+    /// [webhook]: https://docs.rocket.chat/use-rocket.chat/workspace-administration/integrations
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn rocketchat_webhook<U>(mut self, url: U) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+    {
+        self.formatter = Box::new(rocketchat_formatter);
+        self.with_call_target(url)
+    }
+
+    /// Triggers the [IFTTT webhooks][ifttt] applet of `event` for each log event, with the level, target and message
+    /// of the log event as `value1`, `value2` and `value3`.  The `key` is the key shown in the settings of the
+    /// webhooks service.  The formatter is not used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .ifttt("my-key", "my_app_error")
+    ///     .init();
+    /// ```
+    ///
+    /// [ifttt]: https://ifttt.com/maker_webhooks
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn ifttt<K, E>(mut self, key: K, event: E) -> CallLoggerBuilder
+    where
+        K: AsRef<str>,
+        E: AsRef<str>,
+    {
+        self.formatter = Box::new(ifttt_formatter);
+        self.with_call_target(format!(
+            "https://maker.ifttt.com/trigger/{}/with/key/{}",
+            encode_query(event.as_ref()),
+            key.as_ref()
+        ))
+    }
+
+    /// Posts each log event to a [Zapier catch hook][zapier] as a flat JSON object with the `level`, `target` and
+    /// `message` of the log event and its key-value pairs, so that each of them can be used as a field in the steps of
+    /// the Zap.  The formatter is not used.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .zapier_hook("https://hooks.zapier.com/hooks/catch/123456/abcdef/")
+    ///     .init();
+    /// ```
+    ///
+    /// [zapier]: https://help.zapier.com/hc/en-us/articles/8496288690317-Trigger-Zaps-from-webhooks
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn zapier_hook<U>(mut self, url: U) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+    {
+        self.formatter = Box::new(flat_json_formatter);
+        self.with_call_target(url)
+    }
+}
+
+/// The color of each level in chat services
+pub(crate) fn level_color(level: Level) -> &'static str {
+    match level {
+        Level::Error => "#d32f2f",
+        Level::Warn => "#f57c00",
+        Level::Info => "#388e3c",
+        Level::Debug => "#1976d2",
+        Level::Trace => "#757575",
+    }
+}
+
+fn mattermost_formatter(buffer: &mut String, context: &FormatContext) {
+    let record = context.record();
+    let message = context.message().to_string();
+    buffer.push_str("{\"attachments\":[{\"fallback\":\"[");
+    push_json_escaped(buffer, context.level());
+    buffer.push_str("] ");
+    push_json_escaped(buffer, &message);
+    buffer.push_str("\",\"color\":\"");
+    buffer.push_str(level_color(record.level()));
+    buffer.push_str("\",\"title\":\"");
+    push_json_escaped(buffer, context.level());
+    buffer.push(' ');
+    push_json_escaped(buffer, record.target());
+    buffer.push_str("\",\"text\":\"");
+    push_json_escaped(buffer, &message);
+    buffer.push_str("\"}]}");
+}
+
+fn rocketchat_formatter(buffer: &mut String, context: &FormatContext) {
+    let record = context.record();
+    buffer.push_str("{\"text\":\"*");
+    push_json_escaped(buffer, context.level());
+    buffer.push_str("* ");
+    push_json_escaped(buffer, record.target());
+    buffer.push_str("\",\"attachments\":[{\"text\":\"");
+    push_json_escaped(buffer, &context.message().to_string());
+    buffer.push_str("\",\"color\":\"");
+    buffer.push_str(level_color(record.level()));
+    buffer.push_str("\"}]}");
+}
+
+fn ifttt_formatter(buffer: &mut String, context: &FormatContext) {
+    buffer.push_str("{\"value1\":\"");
+    push_json_escaped(buffer, context.level());
+    buffer.push_str("\",\"value2\":\"");
+    push_json_escaped(buffer, context.record().target());
+    buffer.push_str("\",\"value3\":\"");
+    push_json_escaped(buffer, &context.message().to_string());
+    buffer.push_str("\"}");
+}
+
+/// Writes a JSON object with the level, target, key-value pairs and message of a log event, for services that take
+/// any flat JSON object
+pub(crate) fn flat_json_formatter(buffer: &mut String, context: &FormatContext) {
+    buffer.push_str("{\"level\":\"");
+    push_json_escaped(buffer, context.level());
+    buffer.push_str("\",\"target\":\"");
+    push_json_escaped(buffer, context.record().target());
+    buffer.push_str("\",");
+    for (key, value) in context.kv() {
+        buffer.push('"');
+        push_json_escaped(buffer, key);
+        buffer.push_str("\":\"");
+        push_json_escaped(buffer, value);
+        buffer.push_str("\",");
+    }
+    buffer.push_str("\"message\":\"");
+    push_json_escaped(buffer, &context.message().to_string());
+    buffer.push_str("\"}");
+}
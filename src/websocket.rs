@@ -0,0 +1,177 @@
+use std::{
+    io::ErrorKind,
+    net::{TcpStream, ToSocketAddrs},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::Record;
+use tungstenite::{
+    client::IntoClientRequest, client_tls, stream::MaybeTlsStream, Error, Message, WebSocket,
+};
+
+use crate::{BoxError, CallLoggerBuilder, CallTarget};
+
+/// The delays between attempts to reconnect double from the minimum up to the maximum
+const MIN_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// How long connecting, the handshake and each write can take before the connection is treated as failed
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl CallLoggerBuilder {
+    /// Streams the formatted output of each log event as a text frame over a persistent WebSocket connection to
+    /// `url` (`ws://` or `wss://`) instead of calling the call target, e.g. to feed a live log dashboard.  The
+    /// connection is made when the first log event is sent.  If the connection fails or is lost, log events are
+    /// dropped until a reconnect succeeds, with the delay between reconnect attempts doubling from half a second up
+    /// to a minute.  Connecting and the handshake time out after 10 seconds.  Frames from the server are read before
+    /// each log event is sent, so pings are answered and a close from the server is seen.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .websocket("ws://localhost:9000/logs")
+    ///     .init();
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn websocket<U>(mut self, url: U) -> CallLoggerBuilder
+    where
+        U: Into<String>,
+    {
+        self.dispatcher.target = Some(Box::new(WebSocketTarget {
+            url: url.into(),
+            state: Mutex::new(State {
+                socket: None,
+                backoff: MIN_BACKOFF,
+                retry_at: None,
+            }),
+        }));
+        self
+    }
+}
+
+struct WebSocketTarget {
+    url: String,
+    state: Mutex<State>,
+}
+
+struct State {
+    socket: Option<WebSocket<MaybeTlsStream<TcpStream>>>,
+    backoff: Duration,
+    retry_at: Option<Instant>,
+}
+
+impl State {
+    fn failed(&mut self) {
+        self.socket = None;
+        self.retry_at = Some(Instant::now() + self.backoff);
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+impl WebSocketTarget {
+    /// Connects to the URL and makes the handshake, with a timeout for each
+    fn connect(&self) -> Result<WebSocket<MaybeTlsStream<TcpStream>>, BoxError> {
+        let request = self.url.as_str().into_client_request()?;
+        let uri = request.uri();
+        let host = uri
+            .host()
+            .ok_or_else(|| format!("{} has no host", self.url))?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let port = uri
+            .port_u16()
+            .unwrap_or(if uri.scheme_str() == Some("wss") {
+                443
+            } else {
+                80
+            });
+        let mut last_error = None;
+        let mut stream = None;
+        for addr in (host, port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(connected) => {
+                    stream = Some(connected);
+                    break;
+                }
+                Err(x) => last_error = Some(x),
+            }
+        }
+        let stream = match (stream, last_error) {
+            (Some(stream), _) => stream,
+            (None, Some(x)) => return Err(x.into()),
+            (None, None) => return Err(format!("{host} has no addresses").into()),
+        };
+        stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+        let (socket, _) = client_tls(request, stream).map_err(|x| x.to_string())?;
+        Ok(socket)
+    }
+}
+
+/// Reads the frames that the server has sent without waiting for more, which queues the replies to pings and closes
+fn drain(socket: &mut WebSocket<MaybeTlsStream<TcpStream>>) -> Result<(), BoxError> {
+    let Some(stream) = tcp_stream(socket.get_ref()) else {
+        return Ok(());
+    };
+    stream.set_nonblocking(true)?;
+    let result = loop {
+        match socket.read() {
+            Ok(_) => {}
+            Err(Error::Io(x)) if x.kind() == ErrorKind::WouldBlock => break Ok(()),
+            Err(x) => break Err(x.into()),
+        }
+    };
+    if let Some(stream) = tcp_stream(socket.get_ref()) {
+        stream.set_nonblocking(false)?;
+    }
+    result
+}
+
+fn tcp_stream(stream: &MaybeTlsStream<TcpStream>) -> Option<&TcpStream> {
+    match stream {
+        MaybeTlsStream::Plain(stream) => Some(stream),
+        MaybeTlsStream::Rustls(stream) => Some(stream.get_ref()),
+        _ => None,
+    }
+}
+
+impl CallTarget for WebSocketTarget {
+    fn call(&self, payload: &str, _record: &Record) -> Result<(), BoxError> {
+        let mut state = self.state.lock().unwrap_or_else(|x| x.into_inner());
+        if state.socket.is_none() {
+            if let Some(retry_at) = state.retry_at {
+                if Instant::now() < retry_at {
+                    return Err(format!("waiting to reconnect to {}", self.url).into());
+                }
+            }
+            match self.connect() {
+                Ok(socket) => {
+                    state.socket = Some(socket);
+                    state.backoff = MIN_BACKOFF;
+                    state.retry_at = None;
+                }
+                Err(x) => {
+                    state.failed();
+                    return Err(x);
+                }
+            }
+        }
+        if let Some(socket) = state.socket.as_mut() {
+            if let Err(x) =
+                drain(socket).and_then(|_| Ok(socket.send(Message::Text(payload.to_string()))?))
+            {
+                state.failed();
+                return Err(x);
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|x| x.into_inner());
+        if let Some(socket) = state.socket.as_mut() {
+            let _ = socket.flush();
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{CallLoggerBuilder, FormatContext};
+
+impl CallLoggerBuilder {
+    /// Formats each log event as an XML document for endpoints that only accept XML, e.g. SOAP listeners and legacy
+    /// enterprise service buses.  The `root_element` contains a `ts`, `level`, `module` and `msg` element, followed by
+    /// an element for each key-value pair, scoped field and static field of the log event.  Text is escaped, and
+    /// characters that keys can't have in an XML name are replaced with `_`, e.g.
+    /// `<logEvent><ts>...</ts><level>INFO</level><module>app</module><msg>logged in</msg><user_id>42</user_id></logEvent>`.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .xml_format("logEvent")
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .init();
+    /// log::info!(user_id = 42; "logged in");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn xml_format<T>(mut self, root_element: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        let root = xml_name(&root_element.into());
+        self.formatter =
+            Box::new(move |buffer, context| xml_formatter(buffer, &root, false, context));
+        self
+    }
+
+    /// Formats each log event as an XML document in the same way as [`CallLoggerBuilder::xml_format`], except that
+    /// the key-value pairs, scoped fields and static fields of the log event are attributes of the `root_element`
+    /// instead of child elements, e.g. `<logEvent user_id="42"><ts>...</ts><level>INFO</level>...</logEvent>`.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .xml_format_with_attributes("logEvent")
+    ///     .with_call_target("https://postman-echo.com/post")
+    ///     .init();
+    /// log::info!(user_id = 42; "logged in");
+    /// ```
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn xml_format_with_attributes<T>(mut self, root_element: T) -> CallLoggerBuilder
+    where
+        T: Into<String>,
+    {
+        let root = xml_name(&root_element.into());
+        self.formatter =
+            Box::new(move |buffer, context| xml_formatter(buffer, &root, true, context));
+        self
+    }
+}
+
+fn xml_formatter(buffer: &mut String, root: &str, attributes: bool, context: &FormatContext) {
+    let fields = context
+        .kv()
+        .iter()
+        .chain(context.scoped_fields().iter().map(|(k, v)| (k, v)))
+        .chain(context.static_fields().iter().map(|(k, v)| (k, v)));
+    buffer.push('<');
+    buffer.push_str(root);
+    if attributes {
+        // an element can't have the same attribute twice, so only the first of each name is written
+        let mut written = Vec::new();
+        for (key, value) in fields.clone() {
+            let name = xml_name(key);
+            if !written.contains(&name) {
+                buffer.push(' ');
+                buffer.push_str(&name);
+                buffer.push_str("=\"");
+                push_xml_escaped(buffer, value);
+                buffer.push('"');
+                written.push(name);
+            }
+        }
+    }
+    buffer.push('>');
+    if let Some(timestamp) = context.timestamp() {
+        push_xml_element(buffer, "ts", timestamp);
+    }
+    push_xml_element(buffer, "level", context.level());
+    push_xml_element(
+        buffer,
+        "module",
+        context.record().module_path().unwrap_or_default(),
+    );
+    let message = context.message();
+    match message.as_str() {
+        Some(message) => push_xml_element(buffer, "msg", message),
+        None => push_xml_element(buffer, "msg", &message.to_string()),
+    }
+    if !attributes {
+        for (key, value) in fields {
+            push_xml_element(buffer, &xml_name(key), value);
+        }
+    }
+    buffer.push_str("</");
+    buffer.push_str(root);
+    buffer.push('>');
+}
+
+fn push_xml_element(buffer: &mut String, name: &str, text: &str) {
+    buffer.push('<');
+    buffer.push_str(name);
+    buffer.push('>');
+    push_xml_escaped(buffer, text);
+    buffer.push_str("</");
+    buffer.push_str(name);
+    buffer.push('>');
+}
+
+/// Writes text so that it can be the content of an element or the value of an attribute, leaving out the control
+/// characters that XML 1.0 doesn't allow
+fn push_xml_escaped(buffer: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => buffer.push_str("&amp;"),
+            '<' => buffer.push_str("&lt;"),
+            '>' => buffer.push_str("&gt;"),
+            '"' => buffer.push_str("&quot;"),
+            '\'' => buffer.push_str("&apos;"),
+            '\t' | '\n' | '\r' => buffer.push(c),
+            c if c.is_control() => {}
+            c => buffer.push(c),
+        }
+    }
+}
+
+/// Makes a valid XML name of a key, replacing the characters that a name can't have with `_`
+fn xml_name(key: &str) -> String {
+    let mut name = key
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
@@ -0,0 +1,73 @@
+use crate::{base64, encode_query, CallLogger, CallLoggerBuilder, FormatContext};
+
+impl CallLoggerBuilder {
+    /// Sends each log event to a topic of a [Zulip][zulip] stream with the messages API.  The message is the level and
+    /// message of the log event, with a link to the source once [`CallLoggerBuilder::with_source_links`] is set,
+    /// followed by the JSON of the default formatter in a code block.  The `site` is the URL of the Zulip organization,
+    /// e.g.  `https://example.zulipchat.com`, and the `bot_email` and `api_key` are the credentials of a bot that can
+    /// post to the stream.
+    ///
+    /// Example
+    /// ```rust
+    /// # use call_logger::CallLogger;
+    /// CallLogger::new()
+    ///     .zulip("https://example.zulipchat.com", "logger-bot@example.zulipchat.com", "my-api-key", "ops", "my_app")
+    ///     .init();
+    /// ```
+    ///
+    /// [zulip]: https://zulip.com/api/send-message
+    #[inline]
+    #[must_use = "You must call init() before logging"]
+    pub fn zulip<U, E, K, S, T>(
+        mut self,
+        site: U,
+        bot_email: E,
+        api_key: K,
+        stream: S,
+        topic: T,
+    ) -> CallLoggerBuilder
+    where
+        U: AsRef<str>,
+        E: AsRef<str>,
+        K: AsRef<str>,
+        S: AsRef<str>,
+        T: AsRef<str>,
+    {
+        let credentials = format!("{}:{}", bot_email.as_ref(), api_key.as_ref());
+        self.dispatcher.http_headers.push((
+            "Authorization".to_string(),
+            format!("Basic {}", base64(credentials.as_bytes())),
+        ));
+        self.dispatcher.http_headers.push((
+            "Content-Type".to_string(),
+            "application/x-www-form-urlencoded".to_string(),
+        ));
+        let to = format!(
+            "type=stream&to={}&topic={}&content=",
+            encode_query(stream.as_ref()),
+            encode_query(topic.as_ref())
+        );
+        self.formatter = Box::new(move |buffer, context| zulip_formatter(buffer, &to, context));
+        self.with_call_target(format!(
+            "{}/api/v1/messages",
+            site.as_ref().trim_end_matches('/')
+        ))
+    }
+}
+
+fn zulip_formatter(buffer: &mut String, to: &str, context: &FormatContext) {
+    let mut json = String::new();
+    CallLogger::json_formatter(&mut json, context);
+    let record = context.record();
+    let source = match (context.source_url(), record.file(), record.line()) {
+        (Some(url), Some(file), Some(line)) => format!(" ([{file}:{line}]({url}))"),
+        _ => String::new(),
+    };
+    let content = format!(
+        "**{}** {}{source}\n```json\n{json}\n```",
+        context.level(),
+        context.message()
+    );
+    buffer.push_str(to);
+    buffer.push_str(&encode_query(&content));
+}